@@ -0,0 +1,46 @@
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=DATACLOAK_UPDATE_HEADER");
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml is valid");
+
+    // The header is generated into OUT_DIR so builds leave the checkout
+    // alone; DATACLOAK_UPDATE_HEADER=1 refreshes the checked-in copy. A
+    // header that fails to generate must not break the library build, so
+    // report it and keep the checked-in copy
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+            bindings.write_to_file(out_dir.join("datacloak.h"));
+            if env::var_os("DATACLOAK_UPDATE_HEADER").is_some() {
+                bindings.write_to_file(crate_dir.join("include/datacloak.h"));
+            }
+        }
+        Err(e) => println!("cargo:warning=Failed to generate datacloak.h: {}", e),
+    }
+
+    #[cfg(feature = "grpc")]
+    compile_protos();
+}
+
+/// Generates the gRPC messages and server trait for `src/grpc.rs`.
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    println!("cargo:rerun-if-changed=proto/datacloak.proto");
+
+    // Use the bundled protoc unless the caller points PROTOC at another one
+    if env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("bundled protoc is available");
+        env::set_var("PROTOC", protoc);
+    }
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/datacloak.proto"], &["proto"])
+        .expect("proto/datacloak.proto compiles");
+}
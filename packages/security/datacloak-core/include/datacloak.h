@@ -0,0 +1,183 @@
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#ifndef DATACLOAK_H
+#define DATACLOAK_H
+
+
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Version of the C ABI described by `include/datacloak.h`.
+#define DATACLOAK_ABI_VERSION 1
+
+#define DATACLOAK_OK 0
+
+#define DATACLOAK_ERR_PATTERN_COMPILE 1
+
+#define DATACLOAK_ERR_TEXT_TOO_LARGE 2
+
+#define DATACLOAK_ERR_TIMEOUT 3
+
+#define DATACLOAK_ERR_INVALID_CONFIG 4
+
+#define DATACLOAK_ERR_VAULT 5
+
+#define DATACLOAK_ERR_CRYPTO 6
+
+#define DATACLOAK_ERR_MAPPING_CONFLICT 7
+
+#define DATACLOAK_ERR_IO 8
+
+#define DATACLOAK_ERR_INVALID_ARGUMENT 9
+
+#define DATACLOAK_ERR_CANCELLED 10
+
+#define DATACLOAK_ERR_INTERNAL 99
+
+// A single detection. Strings are owned by the enclosing
+// `DataCloakFindingList`; offsets are bytes into the scanned text.
+typedef struct DataCloakFinding {
+  char *pii_type;
+  char *sample;
+  char *masked;
+  size_t start;
+  size_t end;
+  double confidence;
+} DataCloakFinding;
+
+// Detections returned by `datacloak_detect_list`. Release with
+// `datacloak_free_findings`.
+typedef struct DataCloakFindingList {
+  struct DataCloakFinding *items;
+  size_t len;
+} DataCloakFindingList;
+
+// A length-prefixed UTF-16 string allocated by the library. Release with
+// `datacloak_free_utf16`.
+typedef struct DataCloakUtf16Buffer {
+  uint16_t *data;
+  size_t len;
+} DataCloakUtf16Buffer;
+
+// Receives each streaming finding as a NUL-terminated JSON object whose
+// `start`/`end` are byte offsets from the start of the stream. The string is
+// only valid for the duration of the call. Nullable on the C side, but
+// rejected by `datacloak_stream_begin` when null.
+typedef void (*DataCloakFindingCallback)(const char *finding_json, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates an engine from a JSON-encoded `DataCloakConfig`, or from the
+// defaults when `json_config` is null. Fields missing from the document keep
+// their defaults. Release the engine with `datacloak_destroy`.
+int datacloak_engine_create(const char *json_config, void **out_engine);
+
+// Detects PII in `text`, writing a JSON array of detections to `out_json`.
+// Free the result with `datacloak_free_string`.
+int datacloak_detect(void *engine, const char *text, char **out_json);
+
+// Detects PII in a byte buffer of `len` bytes, which need not be UTF-8 or
+// NUL-terminated. UTF-16 is recognized by its byte order mark or zero
+// bytes, and other invalid UTF-8 is decoded as Latin-1, so samples in the
+// JSON written to `out_json` are UTF-8 either way.
+int datacloak_detect_bytes(void *engine, const uint8_t *data, size_t len, char **out_json);
+
+// Detects PII in `text`, writing the detections as a `DataCloakFindingList`
+// to `out_list`.
+int datacloak_detect_list(void *engine, const char *text, struct DataCloakFindingList **out_list);
+
+void datacloak_free_findings(struct DataCloakFindingList *list);
+
+// Detects PII in `len` UTF-16 code units, e.g. a .NET `string`, writing a
+// JSON array of detections to `out_json`. Offsets in the JSON count UTF-16
+// code units, so they index the caller's string directly.
+int datacloak_detect_pii_utf16(void *engine,
+                               const uint16_t *data,
+                               size_t len,
+                               struct DataCloakUtf16Buffer *out_json);
+
+// UTF-16 counterpart of `datacloak_mask`; offsets count UTF-16 code units.
+int datacloak_mask_text_utf16(void *engine,
+                              const uint16_t *data,
+                              size_t len,
+                              struct DataCloakUtf16Buffer *out_json);
+
+void datacloak_free_utf16(struct DataCloakUtf16Buffer buffer);
+
+// Masks `text`, writing the JSON-encoded `MaskingResult` to `out_json`.
+// Free the result with `datacloak_free_string`.
+int datacloak_mask(void *engine, const char *text, char **out_json);
+
+void *datacloak_create(void);
+
+// Creates an engine from a JSON-encoded `DataCloakConfig`. Returns null if
+// `json_config` is null, the JSON is invalid or the config fails validation.
+void *datacloak_create_with_config(const char *json_config);
+
+void datacloak_destroy(void *engine);
+
+char *datacloak_detect_pii(void *engine, const char *text);
+
+char *datacloak_detect_pii_bytes(void *engine, const uint8_t *data, size_t len);
+
+char *datacloak_mask_text(void *engine, const char *text);
+
+// Creates a pool of `size` engines sharing one config (defaults when
+// `json_config` is null). Pool calls are safe from any number of threads.
+// Release the pool with `datacloak_pool_destroy`.
+int datacloak_pool_create(size_t size, const char *json_config, void **out_pool);
+
+// Same as `datacloak_detect`, on the next engine of the pool.
+int datacloak_pool_detect(void *pool, const char *text, char **out_json);
+
+// Same as `datacloak_mask`, on the next engine of the pool.
+int datacloak_pool_mask(void *pool, const char *text, char **out_json);
+
+void datacloak_pool_destroy(void *pool);
+
+// Starts a streaming scan on `engine`, which must outlive the stream.
+// `callback` is invoked with `user_data` for every finding, from within
+// `datacloak_stream_feed` and `datacloak_stream_finish`.
+int datacloak_stream_begin(void *engine,
+                           DataCloakFindingCallback callback,
+                           void *user_data,
+                           void **out_stream);
+
+// Feeds the next chunk of UTF-8 text. Chunks may split a value, or a
+// multi-byte character, anywhere; a partial character is held back until
+// the next feed completes it.
+int datacloak_stream_feed(void *stream, const char *chunk);
+
+// Reports the findings left in the final chunk and releases the stream,
+// whatever the returned status.
+int datacloak_stream_finish(void *stream);
+
+// Cancels a stream from any thread. A feed in progress stops at its next
+// pattern pass, and it and every later feed return `DATACLOAK_ERR_CANCELLED`.
+// The stream must still be released with `datacloak_stream_finish`, which
+// must not race with this call.
+int datacloak_cancel(void *stream);
+
+void datacloak_free_string(char *s);
+
+// Returns `DATACLOAK_ABI_VERSION` as compiled into this library.
+uint32_t datacloak_abi_version(void);
+
+char *datacloak_version(void);
+
+// Returns the message of the last error raised on this thread, or null if
+// the previous call succeeded. Free the result with `datacloak_free_string`.
+char *datacloak_last_error(void);
+
+// Returns the `DataCloakError::code` of the last error raised on this
+// thread, or 0 if the previous call succeeded.
+int datacloak_last_error_code(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* DATACLOAK_H */
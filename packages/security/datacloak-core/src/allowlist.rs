@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use regex::RegexSet;
+
+use crate::{obfuscated, DataCloakConfig, DataCloakEngine, DataCloakError};
+
+/// Compiled `allowed_email_domains`, `allowed_values` and
+/// `allowed_patterns`. Domains and values are lowercase, domains without a
+/// leading `@`.
+#[derive(Debug, Default)]
+pub(crate) struct Allowlist {
+    email_domains: Vec<String>,
+    values: HashSet<String>,
+    patterns: Option<RegexSet>,
+}
+
+impl Allowlist {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Result<Self, DataCloakError> {
+        let patterns = if config.allowed_patterns.is_empty() {
+            None
+        } else {
+            // Patterns must match the whole value
+            let anchored = config
+                .allowed_patterns
+                .iter()
+                .map(|p| format!("^(?:{})$", p));
+            let set = RegexSet::new(anchored).map_err(|e| DataCloakError::PatternCompile {
+                name: "allowed_patterns".to_string(),
+                message: e.to_string(),
+            })?;
+            Some(set)
+        };
+        Ok(Self {
+            email_domains: config
+                .allowed_email_domains
+                .iter()
+                .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+                .collect(),
+            values: config
+                .allowed_values
+                .iter()
+                .map(|value| value.trim().to_lowercase())
+                .collect(),
+            patterns,
+        })
+    }
+
+    fn allows_value(&self, value: &str) -> bool {
+        (!self.values.is_empty() && self.values.contains(&value.to_lowercase()))
+            || self
+                .patterns
+                .as_ref()
+                .is_some_and(|set| set.is_match(value))
+    }
+
+    fn allows_email(&self, email: &str) -> bool {
+        let Some((_, domain)) = email.rsplit_once('@') else {
+            return false;
+        };
+        self.email_domains
+            .iter()
+            .any(|allowed| domain_matches(domain, allowed))
+    }
+}
+
+/// Whether `domain` is `allowed`, a lowercase domain, or one of its
+/// subdomains.
+pub(crate) fn domain_matches(domain: &str, allowed: &str) -> bool {
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    domain == allowed
+        || domain
+            .strip_suffix(allowed)
+            .is_some_and(|sub| sub.ends_with('.'))
+}
+
+impl DataCloakEngine {
+    /// Whether a `pii_type` match is allowlisted, so neither reported nor
+    /// masked.
+    pub(crate) fn is_allowlisted(&self, pii_type: &str, sample: &str) -> bool {
+        self.allowlist.allows_value(sample)
+            || (pii_type == "email" && self.allowlist.allows_email(sample))
+            || (pii_type == "email"
+                && obfuscated::deobfuscate_email(sample)
+                    .is_some_and(|email| self.allowlist.allows_email(&email)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_email_domains_are_skipped() {
+        let config = DataCloakConfig::builder()
+            .allowed_email_domains(["ourcompany.com", "@Service.io"])
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine
+            .mask_text(
+                "From noreply@ourcompany.com via ops@mail.OurCompany.com and \
+                 bot@service.io to jane@gmail.com, cc fake@notourcompany.com",
+            )
+            .unwrap();
+
+        let emails: Vec<_> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.sample.as_str())
+            .collect();
+        assert_eq!(emails, ["jane@gmail.com", "fake@notourcompany.com"]);
+        assert_eq!(result.metadata.allowlisted, 3);
+        assert!(result.masked_text.contains("noreply@ourcompany.com"));
+        assert!(DataCloakConfig::builder()
+            .allowed_email_domains(["@"])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_allowed_values_and_patterns_are_skipped() {
+        let config = DataCloakConfig::builder()
+            .allowed_values(["000-00-0000", "4111 1111 1111 1111"])
+            .allowed_patterns([r"(?i)[^@]+@example\.com"])
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine
+            .mask_text(
+                "SSN 000-00-0000 or 123-45-6789, card 4111 1111 1111 1111, \
+                 mail Test@Example.com or jane@gmail.com",
+            )
+            .unwrap();
+
+        let found: Vec<_> = result
+            .detected_pii
+            .iter()
+            .map(|pii| (pii.pii_type.as_str(), pii.sample.as_str()))
+            .collect();
+        assert_eq!(found, [("email", "jane@gmail.com"), ("ssn", "123-45-6789")]);
+        assert_eq!(result.metadata.allowlisted, 3);
+
+        let bad = DataCloakConfig::builder()
+            .allowed_patterns(["("])
+            .build()
+            .unwrap();
+        assert!(DataCloakEngine::new(bad).is_err());
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compression::{Decoder, Encoder};
+use crate::{AttachmentPolicy, Compression, DataCloakEngine, DataCloakError, PIIDetectionResult};
+
+const ZIP_MAGIC: &[&[u8]] = &[b"PK\x03\x04", b"PK\x05\x06"];
+
+/// Offset of the `ustar` magic in a tar header block.
+const TAR_MAGIC_OFFSET: usize = 257;
+
+/// Container layouts understood by `mask_archive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Zip,
+    /// ustar or GNU tar, optionally gzip or zstd compressed.
+    Tar,
+}
+
+/// Findings in one file of an archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntryReport {
+    /// Path of the entry inside the archive.
+    pub path: String,
+    /// How the entry was masked: `json`, `ndjson`, `csv`, `xml`, `html`,
+    /// `eml`, `sql` or `text`, or `binary` for entries copied unchanged
+    /// because they are not UTF-8.
+    pub handler: String,
+    pub counts_by_type: HashMap<String, u64>,
+    /// Why the handler for the entry's extension failed. The entry was then
+    /// masked as plain text instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Totals from `mask_archive`, with one entry per file in archive order.
+/// Directories and links are copied without being reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveReport {
+    pub format: ArchiveFormat,
+    /// Compression around a tar archive, reused for the output.
+    pub compression: Compression,
+    pub entries: Vec<ArchiveEntryReport>,
+    /// Set when any entry hit a match limit or the memory budget.
+    pub limits_exceeded: bool,
+}
+
+impl DataCloakEngine {
+    /// Masks every file inside the zip or tar archive at `input` and writes
+    /// an archive of the same format to `output`. Each entry is masked with
+    /// the handler for its extension, such as `mask_json` for `.json` or
+    /// `mask_csv` for `.csv`, and as plain text otherwise. Entries that are
+    /// not UTF-8 are copied unchanged and reported as `binary`, so findings
+    /// inside images, PDFs or nested archives are not masked. Entry names,
+    /// zip compression methods and tar headers are kept.
+    pub fn mask_archive<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        input: P,
+        output: Q,
+    ) -> Result<ArchiveReport, DataCloakError> {
+        let (format, compression) = detect_archive(input.as_ref())?;
+        let reader = File::open(input.as_ref()).map_err(io_error)?;
+        let writer = File::create(output.as_ref()).map_err(io_error)?;
+        let mut report = ArchiveReport {
+            format,
+            compression,
+            entries: Vec::new(),
+            limits_exceeded: false,
+        };
+
+        match format {
+            ArchiveFormat::Zip => self.mask_zip(reader, writer, &mut report)?,
+            ArchiveFormat::Tar => {
+                let reader = Decoder::new(BufReader::new(reader))?;
+                let writer = Encoder::new(BufWriter::new(writer), compression)?;
+                self.mask_tar(reader, writer, &mut report)?;
+            }
+        }
+        Ok(report)
+    }
+
+    fn mask_zip(
+        &self,
+        reader: File,
+        writer: File,
+        report: &mut ArchiveReport,
+    ) -> Result<(), DataCloakError> {
+        let mut archive = zip::ZipArchive::new(BufReader::new(reader)).map_err(zip_error)?;
+        let mut zip = zip::ZipWriter::new(BufWriter::new(writer));
+
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index).map_err(zip_error)?;
+            let mut options =
+                zip::write::SimpleFileOptions::default().compression_method(file.compression());
+            if let Some(mode) = file.unix_mode() {
+                options = options.unix_permissions(mode);
+            }
+            if let Some(modified) = file.last_modified() {
+                options = options.last_modified_time(modified);
+            }
+            let name = file.name().to_string();
+            if file.is_dir() {
+                zip.add_directory(name, options).map_err(zip_error)?;
+                continue;
+            }
+
+            let mut content = Vec::new();
+            file.read_to_end(&mut content).map_err(io_error)?;
+            let masked = self.mask_entry(&name, content, report)?;
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(&masked).map_err(io_error)?;
+        }
+
+        zip.finish().map_err(zip_error)?.flush().map_err(io_error)
+    }
+
+    fn mask_tar(
+        &self,
+        reader: Decoder<BufReader<File>>,
+        writer: Encoder<BufWriter<File>>,
+        report: &mut ArchiveReport,
+    ) -> Result<(), DataCloakError> {
+        let mut archive = tar::Archive::new(reader);
+        let mut builder = tar::Builder::new(writer);
+
+        for entry in archive.entries().map_err(io_error)? {
+            let mut entry = entry.map_err(io_error)?;
+            let path = entry.path().map_err(io_error)?.into_owned();
+            let mut header = entry.header().clone();
+            if !header.entry_type().is_file() {
+                builder
+                    .append_data(&mut header, &path, std::io::empty())
+                    .map_err(io_error)?;
+                continue;
+            }
+
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).map_err(io_error)?;
+            let masked = self.mask_entry(&path.to_string_lossy(), content, report)?;
+            header.set_size(masked.len() as u64);
+            builder
+                .append_data(&mut header, &path, &masked[..])
+                .map_err(io_error)?;
+        }
+
+        builder
+            .into_inner()
+            .map_err(io_error)?
+            .finish()
+            .map_err(io_error)?
+            .flush()
+            .map_err(io_error)
+    }
+
+    /// Masks one file with the handler for its extension, recording its
+    /// findings in `report`.
+    fn mask_entry(
+        &self,
+        path: &str,
+        content: Vec<u8>,
+        report: &mut ArchiveReport,
+    ) -> Result<Vec<u8>, DataCloakError> {
+        let mut entry = ArchiveEntryReport {
+            path: path.to_string(),
+            ..ArchiveEntryReport::default()
+        };
+        let Ok(text) = std::str::from_utf8(&content) else {
+            entry.handler = "binary".to_string();
+            report.entries.push(entry);
+            return Ok(content);
+        };
+
+        let extension = Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let masked = match self.mask_by_extension(&extension, text) {
+            Ok(Some((handler, masked))) => {
+                entry.handler = handler.to_string();
+                Some(masked)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                entry.error = Some(e.to_string());
+                None
+            }
+        };
+        let masked = match masked {
+            Some(masked) => masked,
+            None => {
+                entry.handler = "text".to_string();
+                let mut out = Vec::with_capacity(content.len());
+                let result = self.mask_stream(text.as_bytes(), &mut out, Compression::None)?;
+                EntryOutput {
+                    bytes: out,
+                    counts_by_type: result.counts_by_type,
+                    limits_exceeded: result.limits_exceeded,
+                }
+            }
+        };
+
+        entry.counts_by_type = masked.counts_by_type;
+        report.limits_exceeded |= masked.limits_exceeded;
+        report.entries.push(entry);
+        Ok(masked.bytes)
+    }
+
+    /// Runs the structured handler for `extension`, or returns `None` when
+    /// the entry should be masked as plain text.
+    fn mask_by_extension(
+        &self,
+        extension: &str,
+        text: &str,
+    ) -> Result<Option<(&'static str, EntryOutput)>, DataCloakError> {
+        let output = match extension {
+            "json" => {
+                let document: serde_json::Value = serde_json::from_str(text)
+                    .map_err(|e| DataCloakError::InvalidArgument(e.to_string()))?;
+                let result = self.mask_json(&document)?;
+                let bytes = serde_json::to_vec(&result.masked)
+                    .map_err(|e| DataCloakError::Internal(e.to_string()))?;
+                let mut output = EntryOutput::new(bytes, &result.detected_pii);
+                output.limits_exceeded = result.metadata.limits_exceeded;
+                ("json", output)
+            }
+            "ndjson" | "jsonl" => {
+                let mut bytes = Vec::with_capacity(text.len());
+                let result = self.process_ndjson(text.as_bytes(), &mut bytes)?;
+                let output = EntryOutput {
+                    bytes,
+                    counts_by_type: result.counts_by_type,
+                    limits_exceeded: result.limits_exceeded,
+                };
+                ("ndjson", output)
+            }
+            #[cfg(feature = "csv")]
+            "csv" | "tsv" => {
+                let options = if extension == "tsv" {
+                    crate::CsvOptions::tsv()
+                } else {
+                    crate::CsvOptions::default()
+                };
+                let mut bytes = Vec::with_capacity(text.len());
+                let result = self.mask_csv(text.as_bytes(), &mut bytes, &options)?;
+                let output = EntryOutput {
+                    bytes,
+                    counts_by_type: result.counts_by_type,
+                    limits_exceeded: result.limits_exceeded,
+                };
+                ("csv", output)
+            }
+            #[cfg(feature = "xml")]
+            "xml" => ("xml", self.mask_xml(text)?.into()),
+            "html" | "htm" => ("html", self.mask_html(text)?.into()),
+            "eml" => ("eml", self.mask_eml(text, AttachmentPolicy::Keep)?.into()),
+            "sql" => ("sql", self.mask_sql(text)?.into()),
+            _ => return Ok(None),
+        };
+        Ok(Some(output))
+    }
+}
+
+/// A masked entry and its totals.
+struct EntryOutput {
+    bytes: Vec<u8>,
+    counts_by_type: HashMap<String, u64>,
+    limits_exceeded: bool,
+}
+
+impl EntryOutput {
+    fn new(bytes: Vec<u8>, findings: &[PIIDetectionResult]) -> Self {
+        let mut counts_by_type = HashMap::new();
+        for pii in findings {
+            *counts_by_type.entry(pii.pii_type.clone()).or_insert(0) += 1;
+        }
+        Self {
+            bytes,
+            counts_by_type,
+            limits_exceeded: false,
+        }
+    }
+}
+
+impl From<crate::DocumentMaskingResult> for EntryOutput {
+    fn from(result: crate::DocumentMaskingResult) -> Self {
+        let mut output = EntryOutput::new(result.masked.into_bytes(), &result.detected_pii);
+        output.limits_exceeded = result.metadata.limits_exceeded;
+        output
+    }
+}
+
+/// Identifies a zip archive by its local header, or a tar archive, possibly
+/// compressed, by the `ustar` magic of its first header block.
+fn detect_archive(path: &Path) -> Result<(ArchiveFormat, Compression), DataCloakError> {
+    let mut file = File::open(path).map_err(io_error)?;
+    let mut magic = [0; 4];
+    let read = file.read(&mut magic).map_err(io_error)?;
+    if ZIP_MAGIC.contains(&&magic[..read]) {
+        return Ok((ArchiveFormat::Zip, Compression::None));
+    }
+    let compression = Compression::detect(&magic[..read]);
+
+    let file = File::open(path).map_err(io_error)?;
+    let mut block = Vec::with_capacity(512);
+    Decoder::new(BufReader::new(file))?
+        .take(512)
+        .read_to_end(&mut block)
+        .map_err(io_error)?;
+    if block.get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + 5) == Some(&b"ustar"[..]) {
+        return Ok((ArchiveFormat::Tar, compression));
+    }
+    Err(DataCloakError::InvalidArgument(format!(
+        "{} is not a zip or tar archive",
+        path.display()
+    )))
+}
+
+fn io_error(e: std::io::Error) -> DataCloakError {
+    DataCloakError::Io(e.to_string())
+}
+
+fn zip_error(e: zip::result::ZipError) -> DataCloakError {
+    DataCloakError::Io(format!("zip: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    #[test]
+    fn test_mask_tar_uses_handler_per_entry() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("datacloak-archive-{}.tar", std::process::id()));
+        let output = dir.join(format!(
+            "datacloak-archive-{}-masked.tar",
+            std::process::id()
+        ));
+
+        let mut builder = tar::Builder::new(File::create(&input).unwrap());
+        let entries: [(&str, &[u8]); 3] = [
+            ("logs/app.log", b"login a@example.com\n"),
+            ("data/users.json", b"{\"email\":\"b@example.com\"}"),
+            ("img/logo.png", b"\x89PNG\r\n\x1a\n\xff a@example.com"),
+        ];
+        for (path, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, path, content).unwrap();
+        }
+        builder.into_inner().unwrap().flush().unwrap();
+
+        let report = engine.mask_archive(&input, &output).unwrap();
+        assert_eq!(report.format, ArchiveFormat::Tar);
+        let handlers: Vec<&str> = report.entries.iter().map(|e| e.handler.as_str()).collect();
+        assert_eq!(handlers, ["text", "json", "binary"]);
+        assert_eq!(report.entries[1].path, "data/users.json");
+        assert_eq!(report.entries[1].counts_by_type["email"], 1);
+
+        let mut masked = tar::Archive::new(File::open(&output).unwrap());
+        let mut contents = Vec::new();
+        for entry in masked.entries().unwrap() {
+            let mut text = Vec::new();
+            entry.unwrap().read_to_end(&mut text).unwrap();
+            contents.push(text);
+        }
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+
+        assert_eq!(contents[0], b"login [EMAIL_1]\n");
+        assert_eq!(contents[1], b"{\"email\":\"[EMAIL_2]\"}");
+        assert_eq!(contents[2], entries[2].1);
+    }
+}
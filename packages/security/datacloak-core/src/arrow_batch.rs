@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, ArrayRef, GenericStringArray, OffsetSizeTrait, RecordBatch};
+use arrow_schema::{DataType, Schema};
+use serde::{Deserialize, Serialize};
+
+use crate::{DataCloakEngine, DataCloakError, PIIDetectionResult};
+
+/// Which string columns of a batch or Parquet file are masked. Columns left
+/// out pass through untouched and get no `ColumnFindings`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnSelection {
+    /// Every `Utf8` and `LargeUtf8` column.
+    #[default]
+    All,
+    /// Only the named columns.
+    Only(Vec<String>),
+    /// Every string column except the named ones.
+    Except(Vec<String>),
+}
+
+impl ColumnSelection {
+    pub fn includes(&self, name: &str) -> bool {
+        match self {
+            ColumnSelection::All => true,
+            ColumnSelection::Only(names) => names.iter().any(|n| n == name),
+            ColumnSelection::Except(names) => !names.iter().any(|n| n == name),
+        }
+    }
+
+    /// Fails on names `schema` doesn't have, so a misspelt column can't
+    /// silently go unmasked.
+    pub(crate) fn validate(&self, schema: &Schema) -> Result<(), DataCloakError> {
+        let names = match self {
+            ColumnSelection::All => return Ok(()),
+            ColumnSelection::Only(names) | ColumnSelection::Except(names) => names,
+        };
+        match names
+            .iter()
+            .find(|name| schema.field_with_name(name).is_err())
+        {
+            Some(name) => Err(DataCloakError::InvalidArgument(format!(
+                "Unknown column `{}`",
+                name
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Findings in one string column of a masked batch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnFindings {
+    pub name: String,
+    /// Values in which at least one finding was masked.
+    pub cells_masked: u64,
+    pub counts_by_type: HashMap<String, u64>,
+}
+
+impl ColumnFindings {
+    #[cfg(feature = "parquet")]
+    pub(crate) fn merge(&mut self, other: ColumnFindings) {
+        self.cells_masked += other.cells_masked;
+        for (pii_type, count) in other.counts_by_type {
+            *self.counts_by_type.entry(pii_type).or_insert(0) += count;
+        }
+    }
+
+    fn record(&mut self, results: &[PIIDetectionResult]) {
+        if !results.is_empty() {
+            self.cells_masked += 1;
+            for pii in results {
+                *self.counts_by_type.entry(pii.pii_type.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+/// Output of `mask_record_batch`.
+#[derive(Debug, Clone)]
+pub struct BatchMaskingResult {
+    /// A batch with the input's schema; string columns are replaced, all
+    /// other columns are shared with the input.
+    pub batch: RecordBatch,
+    /// One entry per masked `Utf8`/`LargeUtf8` column, in schema order.
+    pub columns: Vec<ColumnFindings>,
+    /// Set when any value hit a match limit or the memory budget.
+    pub limits_exceeded: bool,
+}
+
+impl DataCloakEngine {
+    /// Masks every `Utf8` and `LargeUtf8` column of `batch`, reading values
+    /// straight out of the Arrow buffers. Nulls stay null and the schema is
+    /// unchanged. Each value must fit within `max_text_length`.
+    pub fn mask_record_batch(
+        &self,
+        batch: &RecordBatch,
+    ) -> Result<BatchMaskingResult, DataCloakError> {
+        self.mask_record_batch_columns(batch, &ColumnSelection::All)
+    }
+
+    /// `mask_record_batch` for the string columns `selection` picks; the
+    /// rest are shared with the input like non-string columns.
+    pub fn mask_record_batch_columns(
+        &self,
+        batch: &RecordBatch,
+        selection: &ColumnSelection,
+    ) -> Result<BatchMaskingResult, DataCloakError> {
+        let schema = batch.schema();
+        selection.validate(&schema)?;
+        let mut arrays = Vec::with_capacity(batch.num_columns());
+        let mut columns = Vec::new();
+        let mut limits_exceeded = false;
+
+        for (field, array) in schema.fields().iter().zip(batch.columns()) {
+            if !selection.includes(field.name()) {
+                arrays.push(Arc::clone(array));
+                continue;
+            }
+            let masked: ArrayRef = match field.data_type() {
+                DataType::Utf8 => {
+                    let (masked, findings, limited) =
+                        self.mask_string_array(field.name(), array.as_string::<i32>())?;
+                    columns.push(findings);
+                    limits_exceeded |= limited;
+                    Arc::new(masked)
+                }
+                DataType::LargeUtf8 => {
+                    let (masked, findings, limited) =
+                        self.mask_string_array(field.name(), array.as_string::<i64>())?;
+                    columns.push(findings);
+                    limits_exceeded |= limited;
+                    Arc::new(masked)
+                }
+                _ => Arc::clone(array),
+            };
+            arrays.push(masked);
+        }
+
+        let batch = RecordBatch::try_new(schema, arrays)
+            .map_err(|e| DataCloakError::Internal(format!("Arrow: {}", e)))?;
+        Ok(BatchMaskingResult {
+            batch,
+            columns,
+            limits_exceeded,
+        })
+    }
+
+    fn mask_string_array<O: OffsetSizeTrait>(
+        &self,
+        name: &str,
+        array: &GenericStringArray<O>,
+    ) -> Result<(GenericStringArray<O>, ColumnFindings, bool), DataCloakError> {
+        let mut findings = ColumnFindings {
+            name: name.to_string(),
+            ..ColumnFindings::default()
+        };
+        let mut limits_exceeded = false;
+        let mut values = Vec::with_capacity(array.len());
+
+        for value in array.iter() {
+            let Some(value) = value else {
+                values.push(None);
+                continue;
+            };
+            let result = self.mask_text(value)?;
+            findings.record(&result.detected_pii);
+            limits_exceeded |= result.metadata.limits_exceeded;
+            values.push(Some(result.masked_text));
+        }
+
+        Ok((values.into_iter().collect(), findings, limits_exceeded))
+    }
+
+    /// Findings per selected string column of `batch` without building
+    /// masked arrays; `cells_masked` counts the values masking would change.
+    #[cfg(feature = "parquet")]
+    pub(crate) fn scan_record_batch(
+        &self,
+        batch: &RecordBatch,
+        selection: &ColumnSelection,
+    ) -> Result<(Vec<ColumnFindings>, bool), DataCloakError> {
+        let schema = batch.schema();
+        selection.validate(&schema)?;
+        let mut columns = Vec::new();
+        let mut limits_exceeded = false;
+        for (field, array) in schema.fields().iter().zip(batch.columns()) {
+            if !selection.includes(field.name()) {
+                continue;
+            }
+            let values: Vec<Option<&str>> = match field.data_type() {
+                DataType::Utf8 => array.as_string::<i32>().iter().collect(),
+                DataType::LargeUtf8 => array.as_string::<i64>().iter().collect(),
+                _ => continue,
+            };
+            let mut findings = ColumnFindings {
+                name: field.name().to_string(),
+                ..ColumnFindings::default()
+            };
+            for value in values.into_iter().flatten() {
+                let detections = self.detect_pii_cancellable(value, None)?;
+                findings.record(&detections.results);
+                limits_exceeded |= detections.limits_exceeded;
+            }
+            columns.push(findings);
+        }
+        Ok((columns, limits_exceeded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+    use arrow_array::{Int64Array, LargeStringArray, StringArray};
+    use arrow_schema::{Field, Schema};
+
+    #[test]
+    fn test_mask_record_batch_replaces_string_columns() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("contact", DataType::Utf8, true),
+            Field::new("notes", DataType::LargeUtf8, true),
+        ]));
+        let ids: ArrayRef = Arc::new(Int64Array::from(vec![1, 2]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::clone(&ids),
+                Arc::new(StringArray::from(vec![Some("a@example.com"), None])),
+                Arc::new(LargeStringArray::from(vec!["none", "ssn 123-45-6789"])),
+            ],
+        )
+        .unwrap();
+
+        let result = engine.mask_record_batch(&batch).unwrap();
+        assert!(Arc::ptr_eq(result.batch.column(0), &ids));
+        let contact = result.batch.column(1).as_string::<i32>();
+        assert_eq!(contact.value(0), "[EMAIL_1]");
+        assert!(contact.is_null(1));
+        let notes = result.batch.column(2).as_string::<i64>();
+        assert_eq!(notes.value(1), "ssn [SSN_1]");
+
+        assert_eq!(result.columns.len(), 2);
+        assert_eq!(result.columns[0].name, "contact");
+        assert_eq!(result.columns[0].counts_by_type["email"], 1);
+        assert_eq!(result.columns[1].cells_masked, 1);
+
+        let selection = ColumnSelection::Only(vec!["notes".to_string()]);
+        let result = engine
+            .mask_record_batch_columns(&batch, &selection)
+            .unwrap();
+        assert!(Arc::ptr_eq(result.batch.column(1), batch.column(1)));
+        assert_eq!(
+            result.batch.column(2).as_string::<i64>().value(1),
+            "ssn [SSN_1]"
+        );
+        assert_eq!(result.columns.len(), 1);
+        assert_eq!(result.columns[0].name, "notes");
+
+        let typo = ColumnSelection::Except(vec!["note".to_string()]);
+        assert!(matches!(
+            engine.mask_record_batch_columns(&batch, &typo),
+            Err(DataCloakError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use crate::{DataCloakEngine, DataCloakError, MaskingResult, PIIDetectionResult};
+
+impl DataCloakEngine {
+    /// Runs `detect_pii` on tokio's blocking pool, so long regex scans don't
+    /// stall the async executor. Requires a running tokio runtime.
+    pub async fn detect_pii_async(
+        self: Arc<Self>,
+        text: String,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        run_blocking(move || self.detect_pii(&text)).await
+    }
+
+    /// Runs `mask_text` on tokio's blocking pool. Requires a running tokio
+    /// runtime.
+    pub async fn mask_text_async(
+        self: Arc<Self>,
+        text: String,
+    ) -> Result<MaskingResult, DataCloakError> {
+        run_blocking(move || self.mask_text(&text)).await
+    }
+}
+
+async fn run_blocking<T, F>(scan: F) -> Result<T, DataCloakError>
+where
+    F: FnOnce() -> Result<T, DataCloakError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(scan)
+        .await
+        .map_err(|e| DataCloakError::Internal(format!("Scan task failed: {}", e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_async_scans_run_on_blocking_pool() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let engine = Arc::new(DataCloakEngine::new(DataCloakConfig::default()).unwrap());
+
+        let (detected, masked) = runtime.block_on(async {
+            let detected = Arc::clone(&engine)
+                .detect_pii_async("mail jane@example.com".to_string())
+                .await;
+            let masked = Arc::clone(&engine)
+                .mask_text_async("SSN 123-45-6789".to_string())
+                .await;
+            (detected, masked)
+        });
+
+        assert_eq!(detected.unwrap()[0].sample, "jane@example.com");
+        assert_eq!(masked.unwrap().masked_text, "SSN ***-**-6789");
+    }
+}
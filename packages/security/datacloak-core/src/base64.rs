@@ -0,0 +1,71 @@
+use crate::error::DataCloakError;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard padded base64 (RFC 4648) without line breaks.
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (i, &b)| group | ((b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((group >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes standard base64, ignoring ASCII whitespace such as the line
+/// breaks of MIME bodies. Padding is optional.
+pub(crate) fn decode_base64(text: &str) -> Result<Vec<u8>, DataCloakError> {
+    let invalid = || DataCloakError::InvalidArgument("Invalid base64 data".to_string());
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut group = 0u32;
+    let mut bits = 0;
+    let mut padding = false;
+
+    for b in text.bytes().filter(|b| !b.is_ascii_whitespace()) {
+        if b == b'=' {
+            padding = true;
+            continue;
+        }
+        if padding {
+            return Err(invalid());
+        }
+        let value = ALPHABET.iter().position(|&c| c == b).ok_or_else(invalid)?;
+        group = (group << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((group >> bits) as u8);
+            group &= (1 << bits) - 1;
+        }
+    }
+    if bits >= 6 {
+        return Err(invalid());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        for input in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            let encoded = encode_base64(input.as_bytes());
+            assert_eq!(decode_base64(&encoded).unwrap(), input.as_bytes());
+        }
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(decode_base64("Zm9v\r\nYmFy").unwrap(), b"foobar");
+        assert!(decode_base64("Zm9v!").is_err());
+    }
+}
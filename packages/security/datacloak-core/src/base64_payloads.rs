@@ -0,0 +1,151 @@
+use crate::base64::{decode_base64, encode_base64};
+use crate::cancellation::CancellationToken;
+use crate::{splice_masks, DataCloakEngine, DataCloakError, Detections, PIIDetectionResult};
+
+/// Shortest run of base64, padding included, worth decoding. Shorter runs
+/// are mostly ordinary words and identifiers.
+const MIN_PAYLOAD_LEN: usize = 16;
+
+/// Runs of standard base64 in `text` long and well-formed enough to be an
+/// encoded payload, as `(start, end)`.
+fn candidate_spans(text: &str) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let in_alphabet = |b: u8| b.is_ascii_alphanumeric() || b == b'+' || b == b'/';
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !in_alphabet(bytes[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && in_alphabet(bytes[i]) {
+            i += 1;
+        }
+        let unpadded = i;
+        while i < bytes.len() && i - unpadded < 2 && bytes[i] == b'=' {
+            i += 1;
+        }
+        let len = i - start;
+        let padded_properly = i == unpadded || len % 4 == 0;
+        if len >= MIN_PAYLOAD_LEN && padded_properly {
+            spans.push((start, i));
+        }
+    }
+    spans
+}
+
+/// The text `encoded` decodes to, if it is printable UTF-8 rather than
+/// binary data or a word that happens to be valid base64.
+fn decode_payload(encoded: &str) -> Option<String> {
+    let decoded = String::from_utf8(decode_base64(encoded).ok()?).ok()?;
+    let printable = decoded
+        .chars()
+        .all(|c| !c.is_control() || c.is_whitespace());
+    (printable && !decoded.trim().is_empty()).then_some(decoded)
+}
+
+impl DataCloakEngine {
+    /// Scans the content of the base64 payloads in `text` and adds what it
+    /// finds to `detections`, located at the encoded span. The mask of such
+    /// a finding is the payload re-encoded with its PII masked, so it still
+    /// decodes.
+    pub(crate) fn detect_in_base64(
+        &self,
+        text: &str,
+        cancel: Option<&CancellationToken>,
+        budget: Option<usize>,
+        detections: &mut Detections,
+    ) -> Result<(), DataCloakError> {
+        for (start, end) in candidate_spans(text) {
+            let encoded = &text[start..end];
+            let Some(decoded) = decode_payload(encoded) else {
+                continue;
+            };
+            let allocated: usize = detections
+                .results
+                .iter()
+                .map(PIIDetectionResult::allocated_bytes)
+                .sum();
+            let remaining = budget.map(|budget| budget.saturating_sub(allocated));
+            let inner = self.detect_within_budget(&decoded, cancel, remaining)?;
+            detections.limits_exceeded |= inner.limits_exceeded;
+            detections.warnings.extend(inner.warnings);
+            detections.validation.failed += inner.validation.failed;
+            detections.validation.suppressed += inner.validation.suppressed;
+            detections.validation.allowlisted += inner.validation.allowlisted;
+            detections.validation.already_masked += inner.validation.already_masked;
+            if inner.results.is_empty() {
+                continue;
+            }
+
+            let masked = encode_base64(splice_masks(&decoded, &inner.results).as_bytes());
+            for pii in inner.results {
+                let found = detections.results.len();
+                if self.config.max_findings.is_some_and(|max| found >= max) {
+                    detections.limits_exceeded = true;
+                    detections
+                        .warnings
+                        .push(format!("Stopped after max_findings ({}) findings", found));
+                    return Ok(());
+                }
+                detections.results.push(PIIDetectionResult {
+                    sample: encoded.to_string(),
+                    masked: masked.clone(),
+                    start,
+                    end,
+                    snippet: None,
+                    normalized: Some(pii.normalized.unwrap_or(pii.sample)),
+                    ..pii
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_candidate_spans_need_length_and_padding() {
+        let payload = encode_base64(b"john@example.com");
+        let text = format!("id=abc payload={} internationalization x==", payload);
+        let spans: Vec<_> = candidate_spans(&text)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect();
+        assert_eq!(spans, [payload.as_str(), "internationalization"]);
+        assert_eq!(decode_payload("internationalization"), None);
+    }
+
+    #[test]
+    fn test_pii_inside_base64_is_reported_at_the_encoded_span() {
+        let payload = encode_base64(br#"{"email":"john@example.com"}"#);
+        let text = format!("event=login payload={} status=ok", payload);
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        assert!(engine.detect_pii(&text).unwrap().is_empty());
+
+        let config = DataCloakConfig::builder()
+            .decode_base64(true)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine.mask_text(&text).unwrap();
+        assert_eq!(result.detected_pii.len(), 1);
+        let pii = &result.detected_pii[0];
+        assert_eq!(pii.pii_type, "email");
+        assert_eq!(pii.sample, payload);
+        assert_eq!(&text[pii.start..pii.end], payload);
+        assert_eq!(pii.normalized.as_deref(), Some("john@example.com"));
+
+        let masked = decode_base64(&pii.masked).unwrap();
+        assert_eq!(masked, br#"{"email":"j***@example.com"}"#);
+        assert_eq!(
+            result.masked_text,
+            format!("event=login payload={} status=ok", pii.masked)
+        );
+    }
+}
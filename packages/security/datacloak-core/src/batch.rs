@@ -0,0 +1,49 @@
+use rayon::prelude::*;
+
+use crate::{DataCloakEngine, DataCloakError, MaskingResult, PIIDetectionResult};
+
+impl DataCloakEngine {
+    /// Runs `detect_pii` on every text across the rayon thread pool. Results
+    /// come back in input order, each with its own error, so one oversized
+    /// record doesn't fail the batch.
+    pub fn detect_pii_batch(
+        &self,
+        texts: &[&str],
+    ) -> Vec<Result<Vec<PIIDetectionResult>, DataCloakError>> {
+        texts.par_iter().map(|text| self.detect_pii(text)).collect()
+    }
+
+    /// Runs `mask_text` on every text across the rayon thread pool, in input
+    /// order. With placeholder masking, values are still numbered once per
+    /// engine, but which record claims a number first is not deterministic.
+    pub fn mask_text_batch(&self, texts: &[&str]) -> Vec<Result<MaskingResult, DataCloakError>> {
+        texts.par_iter().map(|text| self.mask_text(text)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_batch_preserves_input_order() {
+        let config = DataCloakConfig::builder()
+            .max_text_length(40)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let records: Vec<String> = (0..200).map(|i| format!("user{}@example.com", i)).collect();
+        let mut texts: Vec<&str> = records.iter().map(String::as_str).collect();
+        texts.push("this record is far too long for the configured limit");
+
+        let masked = engine.mask_text_batch(&texts);
+        assert_eq!(masked.len(), 201);
+        assert_eq!(masked[7].as_ref().unwrap().masked_text, "u***@example.com");
+        assert!(masked[200].is_err());
+
+        let detected = engine.detect_pii_batch(&texts[..3]);
+        assert_eq!(detected[2].as_ref().unwrap()[0].sample, "user2@example.com");
+    }
+}
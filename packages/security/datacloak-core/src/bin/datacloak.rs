@@ -0,0 +1,159 @@
+//! Command-line masking for files and shell pipelines.
+//!
+//! ```text
+//! datacloak mask [--stream] [--config FILE] [INPUT]
+//! ```
+//!
+//! Reads `INPUT`, or stdin when it is omitted or `-`, and writes the masked
+//! text to stdout. `--stream` masks one line at a time and writes each line
+//! as soon as it is masked, so memory stays bounded by the longest line and
+//! the command can sit in a live pipeline such as
+//! `tail -f app.log | datacloak mask --stream`. gzip and zstd input is
+//! decompressed when the matching feature is enabled.
+//!
+//! Lines that aren't valid UTF-8 are decoded as Latin-1 and reported on
+//! stderr rather than stopping the stream.
+//!
+//! The config comes from `--config` (JSON, YAML or TOML), or the defaults,
+//! overlaid with `DATACLOAK_*` environment variables either way.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::process::ExitCode;
+
+use datacloak_core::{Compression, DataCloakConfig, DataCloakEngine, DataCloakError};
+
+const USAGE: &str = "usage: datacloak mask [--stream] [--config FILE] [INPUT]";
+
+struct Options {
+    stream: bool,
+    config: Option<String>,
+    input: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The reader of a pipeline went away, e.g. `| head`
+        Err(DataCloakError::Io(message)) if message.contains("Broken pipe") => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("datacloak: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    match args.next().as_deref() {
+        Some("mask") => {}
+        Some("-h" | "--help") | None => return Err("datacloak: missing command".to_string()),
+        Some(other) => return Err(format!("datacloak: unknown command `{}`", other)),
+    }
+
+    let mut options = Options {
+        stream: false,
+        config: None,
+        input: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stream" => options.stream = true,
+            "--config" => {
+                options.config = Some(args.next().ok_or("datacloak: --config needs a file")?);
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("datacloak: unknown option `{}`", flag));
+            }
+            _ if options.input.is_some() => {
+                return Err("datacloak: only one input may be given".to_string());
+            }
+            _ => options.input = Some(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), DataCloakError> {
+    let config = match &options.config {
+        Some(path) => DataCloakConfig::from_file(path)?.with_env_overrides()?,
+        None => DataCloakConfig::from_env()?,
+    };
+    let engine = DataCloakEngine::new(config)?;
+
+    let input: Box<dyn Read> = match options.input.as_deref() {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => {
+            Box::new(File::open(path).map_err(|e| DataCloakError::Io(format!("{}: {}", path, e)))?)
+        }
+    };
+    // Stdout is line buffered, so streamed lines leave as soon as they are
+    // masked
+    let mut stdout = io::stdout().lock();
+
+    if options.stream {
+        let report = engine.mask_stream(input, &mut stdout, Compression::None)?;
+        for warning in &report.warnings {
+            eprintln!("datacloak: {}", warning);
+        }
+        return Ok(());
+    }
+
+    let mut text = String::new();
+    BufReader::new(input)
+        .read_to_string(&mut text)
+        .map_err(|e| DataCloakError::Io(e.to_string()))?;
+    let result = engine.mask_text(&text)?;
+    stdout
+        .write_all(result.masked_text.as_bytes())
+        .and_then(|()| stdout.flush())
+        .map_err(|e| DataCloakError::Io(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse(&["mask", "--stream", "--config", "c.yaml", "in.log"]).unwrap();
+        assert!(options.stream);
+        assert_eq!(options.config.as_deref(), Some("c.yaml"));
+        assert_eq!(options.input.as_deref(), Some("in.log"));
+
+        let options = parse(&["mask"]).unwrap();
+        assert!(!options.stream);
+        assert!(options.config.is_none() && options.input.is_none());
+    }
+
+    #[test]
+    fn test_parse_args_rejects_bad_usage() {
+        assert_eq!(
+            parse(&["mask", "--verbose"]).err().unwrap(),
+            "datacloak: unknown option `--verbose`"
+        );
+        assert_eq!(
+            parse(&["mask", "a.log", "b.log"]).err().unwrap(),
+            "datacloak: only one input may be given"
+        );
+        assert_eq!(
+            parse(&["mask", "--config"]).err().unwrap(),
+            "datacloak: --config needs a file"
+        );
+        assert_eq!(
+            parse(&["unmask"]).err().unwrap(),
+            "datacloak: unknown command `unmask`"
+        );
+        assert_eq!(parse(&[]).err().unwrap(), "datacloak: missing command");
+    }
+}
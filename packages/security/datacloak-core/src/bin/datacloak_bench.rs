@@ -0,0 +1,145 @@
+//! Throughput benchmarks over synthetic corpora.
+//!
+//! Run with `cargo run --release --features bench --bin datacloak-bench`.
+//! Criterion's usual arguments apply, e.g. `-- detect` to filter groups.
+//! `DATACLOAK_BENCH_BYTES` sets the corpus size (default 64 KiB).
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use datacloak_core::{DataCloakConfig, DataCloakEngine, MaskingStrategy};
+
+const DEFAULT_CORPUS_BYTES: usize = 64 * 1024;
+
+/// Fraction of generated tokens that are PII values.
+const DENSITIES: [(&str, f64); 3] = [("clean", 0.0), ("sparse", 0.01), ("dense", 0.2)];
+
+const DETECTORS: [&str; 4] = ["email", "phone", "ssn", "credit_card"];
+
+const FILLER: [&str; 12] = [
+    "the", "customer", "reported", "that", "order", "arrived", "late", "and", "asked", "for", "a",
+    "refund",
+];
+
+fn corpus_bytes() -> usize {
+    std::env::var("DATACLOAK_BENCH_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CORPUS_BYTES)
+}
+
+/// Deterministic text of about `bytes` bytes in which roughly `density` of
+/// the words are PII values of every built-in type.
+fn corpus(bytes: usize, density: f64) -> String {
+    let mut state = 0x5eed_u64;
+    let mut next = move || {
+        // xorshift64*: fast and reproducible across runs
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    };
+
+    let mut text = String::with_capacity(bytes + 32);
+    while text.len() < bytes {
+        let roll = (next() % 10_000) as f64 / 10_000.0;
+        let n = next();
+        if roll < density {
+            match n % 4 {
+                0 => text.push_str(&format!("user{}@example.com", n % 1000)),
+                1 => text.push_str(&format!("555-{:03}-{:04}", n % 1000, n % 10_000)),
+                2 => text.push_str(&format!(
+                    "{:03}-{:02}-{:04}",
+                    100 + n % 800,
+                    n % 100,
+                    n % 10_000
+                )),
+                _ => text.push_str("4111 1111 1111 1111"),
+            }
+        } else {
+            text.push_str(FILLER[(n % FILLER.len() as u64) as usize]);
+        }
+        text.push(' ');
+    }
+    text
+}
+
+fn engine(config: DataCloakConfig) -> DataCloakEngine {
+    DataCloakEngine::new(config).expect("benchmark config is valid")
+}
+
+fn config_for_corpus(bytes: usize) -> DataCloakConfig {
+    DataCloakConfig {
+        max_text_length: bytes * 2,
+        ..DataCloakConfig::default()
+    }
+}
+
+fn bench_detectors(c: &mut Criterion) {
+    let bytes = corpus_bytes();
+    let mut group = c.benchmark_group("detect");
+    for (label, density) in DENSITIES {
+        let text = corpus(bytes, density);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+
+        let all = engine(config_for_corpus(bytes));
+        group.bench_with_input(BenchmarkId::new("all", label), &text, |b, text| {
+            b.iter(|| all.detect_pii(black_box(text)).unwrap())
+        });
+
+        for detector in DETECTORS {
+            let single = engine(DataCloakConfig {
+                enabled_types: [detector.to_string()].into_iter().collect(),
+                ..config_for_corpus(bytes)
+            });
+            group.bench_with_input(BenchmarkId::new(detector, label), &text, |b, text| {
+                b.iter(|| single.detect_pii(black_box(text)).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_masking(c: &mut Criterion) {
+    let bytes = corpus_bytes();
+    let strategies = [
+        ("partial", MaskingStrategy::Partial),
+        ("placeholder", MaskingStrategy::Placeholder),
+        ("hmac", MaskingStrategy::Hmac { key: vec![7; 32] }),
+        (
+            "salted_hash",
+            MaskingStrategy::SaltedHash {
+                salt: vec![7; 16],
+                hex_length: 16,
+            },
+        ),
+        (
+            "synthesize",
+            MaskingStrategy::Synthesize { seed: vec![7; 32] },
+        ),
+        (
+            "format_preserving",
+            MaskingStrategy::FormatPreserving { key: vec![7; 32] },
+        ),
+    ];
+
+    let mut group = c.benchmark_group("mask");
+    for (label, density) in DENSITIES {
+        let text = corpus(bytes, density);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+
+        for (name, strategy) in &strategies {
+            let masker = engine(DataCloakConfig {
+                masking_strategy: strategy.clone(),
+                ..config_for_corpus(bytes)
+            });
+            group.bench_with_input(BenchmarkId::new(*name, label), &text, |b, text| {
+                b.iter(|| masker.mask_text(black_box(text)).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_detectors, bench_masking);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Lets another thread abort an in-flight scan. Clones share state, so keep
+/// one clone and hand another to the scan; scans check it between pattern
+/// passes and chunks and stop with `DataCloakError::Cancelled`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn check(&self) -> Result<(), crate::DataCloakError> {
+        if self.is_cancelled() {
+            Err(crate::DataCloakError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::io::{self, BufRead, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::DataCloakError;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// zstd level used when compressing masked output.
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// Compression of a stream. Inputs are recognized by their magic bytes, so
+/// this is only chosen explicitly for output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    #[default]
+    None,
+    /// Requires the `gzip` feature.
+    Gzip,
+    /// Requires the `zstd` feature.
+    Zstd,
+}
+
+impl Compression {
+    /// Identifies gzip and zstd data from its first bytes. Neither magic
+    /// number can start valid UTF-8, so text is never mistaken for either.
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Reader that decompresses its input when it starts with a gzip or zstd
+/// magic number and passes it through otherwise.
+pub(crate) enum Decoder<R: BufRead> {
+    Plain(R),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::read::MultiGzDecoder<R>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::read::Decoder<'static, R>),
+}
+
+impl<R: BufRead> Decoder<R> {
+    pub(crate) fn new(mut reader: R) -> Result<Self, DataCloakError> {
+        let header = reader
+            .fill_buf()
+            .map_err(|e| DataCloakError::Io(e.to_string()))?;
+        match Compression::detect(header) {
+            Compression::None => Ok(Decoder::Plain(reader)),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Decoder::Gzip(flate2::read::MultiGzDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::read::Decoder::with_buffer(reader)
+                .map(Decoder::Zstd)
+                .map_err(|e| DataCloakError::Io(e.to_string())),
+            #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+            compression => Err(unsupported(compression, "input")),
+        }
+    }
+}
+
+impl<R: BufRead> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Decoder::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "gzip")]
+            Decoder::Gzip(reader) => reader.read(buf),
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Writer compressing into `W`. `finish` must be called to write the
+/// trailer of a compressed stream.
+pub(crate) enum Encoder<W: Write> {
+    Plain(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> Encoder<W> {
+    pub(crate) fn new(writer: W, compression: Compression) -> Result<Self, DataCloakError> {
+        match compression {
+            Compression::None => Ok(Encoder::Plain(writer)),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Encoder::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            ))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::write::Encoder::new(writer, ZSTD_LEVEL)
+                .map(Encoder::Zstd)
+                .map_err(|e| DataCloakError::Io(e.to_string())),
+            #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+            compression => Err(unsupported(compression, "output")),
+        }
+    }
+
+    /// Completes the compressed stream and flushes the inner writer.
+    pub(crate) fn finish(self) -> io::Result<W> {
+        // Without compression features `Plain` is the only variant
+        #[cfg(not(any(feature = "gzip", feature = "zstd")))]
+        let Encoder::Plain(mut writer) = self;
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        let mut writer = match self {
+            Encoder::Plain(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(writer) => writer.write(buf),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(writer) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(writer) => writer.flush(),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(writer) => writer.flush(),
+        }
+    }
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn unsupported(compression: Compression, direction: &str) -> DataCloakError {
+    let feature = match compression {
+        Compression::Zstd => "zstd",
+        _ => "gzip",
+    };
+    DataCloakError::InvalidArgument(format!(
+        "{:?} {} requires the `{}` feature",
+        compression, direction, feature
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_compression_by_magic_bytes() {
+        assert_eq!(Compression::detect(&[0x1f, 0x8b, 8, 0]), Compression::Gzip);
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+            Compression::Zstd
+        );
+        assert_eq!(Compression::detect(b"plain text"), Compression::None);
+        assert_eq!(Compression::detect(&[0x1f]), Compression::None);
+    }
+}
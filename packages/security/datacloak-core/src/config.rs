@@ -0,0 +1,1089 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::DataCloakError;
+use crate::format_preserving::FormatPreservingCipher;
+use crate::jsonpath::JsonPath;
+use crate::language::Language;
+use crate::locales::{Locale, RegionProfile};
+use crate::templates::MaskTemplate;
+use crate::views::MaskingView;
+
+/// PII types detected by the built-in patterns.
+pub const BUILTIN_TYPES: [&str; 4] = ["email", "phone", "ssn", "credit_card"];
+
+/// PII types detected without patterns: names from the name lists or a NER
+/// model (with the `ner` feature), organizations from legal-form suffixes,
+/// `organization_names` or a NER model. `organization` is not enabled by
+/// default.
+pub const ENTITY_TYPES: [&str; 2] = ["person", "organization"];
+
+/// PII types detected by the patterns of the configured `locales`, besides
+/// international `phone` formats.
+pub const LOCALE_TYPES: [&str; 10] = [
+    "postal_code",
+    "nino",
+    "de_tax_id",
+    "nir",
+    "dni",
+    "codice_fiscale",
+    "bsn",
+    "sin",
+    "cpf",
+    "aadhaar",
+];
+
+/// Serializes with snake_case enum names and hex-encoded keys. Missing fields
+/// take their default values, so partial documents are valid configs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DataCloakConfig {
+    pub enable_redos_protection: bool,
+    pub email_validation: EmailValidation,
+    pub credit_card_validation: CreditCardValidation,
+    pub phone_validation: PhoneValidation,
+    pub max_text_length: usize,
+    pub regex_timeout_ms: u64,
+    pub masking_strategy: MaskingStrategy,
+    /// Per-type templates overriding the built-in partial masks, e.g.
+    /// `"email" => "{first1}***@{domain}"` or `"ssn" => "XXX-XX-{last4}"`.
+    pub mask_templates: HashMap<String, String>,
+    /// Number of characters left visible by partial masking, per type. Emails
+    /// reveal the first characters of the local part (default 1), other types
+    /// the trailing digits (default 4). Zero fully redacts the value.
+    pub reveal_lengths: HashMap<String, usize>,
+    /// Masking view per caller role for `mask_for_role`. Roles not listed
+    /// get `MaskingView::Full`.
+    pub role_views: HashMap<String, MaskingView>,
+    /// PII types the engine reports. Types not listed here are still
+    /// compiled but skipped during detection.
+    pub enabled_types: HashSet<String>,
+    /// Countries whose phone, postal code and national ID formats are
+    /// detected in addition to the built-in US-style patterns.
+    pub locales: Vec<Locale>,
+    /// Regions whose countries' locale packs are detected, in addition to
+    /// `locales`.
+    pub regions: Vec<RegionProfile>,
+    /// Additional detectors as type name → regex. Custom types are always
+    /// active and are masked as `***` unless a template is configured.
+    /// Streaming scans only find matches longer than `max_match_len` whole
+    /// when they don't straddle a chunk boundary.
+    pub custom_patterns: HashMap<String, String>,
+    /// Domains whose email addresses are not PII, such as
+    /// `noreply@ourcompany.com`. Subdomains are included; matching addresses
+    /// are neither reported nor masked.
+    pub allowed_email_domains: Vec<String>,
+    /// Detect emails obfuscated as `john [at] example [dot] com` or
+    /// `john at example dot com`, and mask them as the plain address.
+    pub obfuscated_emails: bool,
+    /// Values that are never PII, such as test fixtures like `000-00-0000`
+    /// or `4111 1111 1111 1111`. Compared case-insensitively with the whole
+    /// match, of any type.
+    pub allowed_values: Vec<String>,
+    /// Regexes for values that are never PII, e.g. `[^@]+@example\.com`.
+    /// A pattern must match the whole value.
+    pub allowed_patterns: Vec<String>,
+    /// Terms always detected and masked as the `custom` type, whatever the
+    /// enabled types, such as employee names or project codenames. Matched
+    /// ASCII case-insensitively as whole words. See `parse_term_list` for
+    /// loading a wordlist.
+    pub denylist: Vec<String>,
+    /// Stop collecting matches of a type after this many. `None` is unlimited.
+    /// Masking stops at the limit too, leaving later values unmasked, so
+    /// callers should check `limits_exceeded` in the result.
+    pub max_matches_per_type: Option<usize>,
+    /// Stop a scan once this many findings were collected across all types.
+    pub max_findings: Option<usize>,
+    /// Approximate bytes a single scan may allocate for findings and, when
+    /// masking, the original and masked copies of the text. Findings past the
+    /// budget are dropped with a warning instead of growing without bound.
+    pub memory_budget_bytes: Option<usize>,
+    /// Longest value, in bytes, streaming scans are sure to report whole.
+    /// This much text is carried across each chunk boundary, so a longer
+    /// match straddling one may be split or missed. At most 64 KiB.
+    pub max_match_len: usize,
+    /// JSONPath → PII type for `mask_json`: string and number values under
+    /// these paths are always masked as that type, whether or not a pattern
+    /// matches them, e.g. `"$.customer.*" => "customer"`.
+    pub json_mask_paths: HashMap<String, String>,
+    /// JSONPaths `mask_json` and `detect_pii_json` never scan or modify, e.g.
+    /// `"$.metadata"`. Takes precedence over `json_mask_paths`.
+    pub json_skip_paths: Vec<String>,
+    /// Characters examined on each side of a match for context keywords.
+    /// Zero turns keyword scoring off.
+    pub keyword_window: usize,
+    /// PII type → words that raise the confidence of a match of that type
+    /// found within `keyword_window` of them, e.g. `"ssn" => ["ssn"]` for
+    /// `SSN: 123-45-6789`. Words match case-insensitively and whole; setting
+    /// a type replaces its built-in list.
+    pub boost_keywords: HashMap<String, Vec<String>>,
+    /// PII type → words that lower the confidence of a nearby match, e.g.
+    /// `"phone" => ["order"]` for `order 5551234567`. A boost keyword in the
+    /// same window wins.
+    pub penalty_keywords: HashMap<String, Vec<String>>,
+    /// Language → PII type → boost keywords used on text in that language,
+    /// in addition to `boost_keywords`, e.g. French `"phone" => ["portable"]`.
+    pub localized_boost_keywords: HashMap<Language, HashMap<String, Vec<String>>>,
+    /// Language → PII type → penalty keywords, like `localized_boost_keywords`.
+    pub localized_penalty_keywords: HashMap<Language, HashMap<String, Vec<String>>>,
+    /// Identify the language of each scanned text and use only that
+    /// language's localized keywords. When off, or when a text is too short
+    /// to tell, the keywords of every language apply.
+    pub language_detection: bool,
+    /// Scan a copy of each text folded to NFKC, with Cyrillic and Greek
+    /// lookalikes of Latin letters replaced, so `５５５－１２３－４５６７`
+    /// or a homoglyph email is still caught. Findings keep the original
+    /// offsets and sample; `normalized` holds the folded form.
+    pub normalize_unicode: bool,
+    /// Decode runs of base64 that hold printable text, like encoded payload
+    /// fields in logs, and scan what they contain. Findings are reported at
+    /// the encoded span and masked by re-encoding the masked content.
+    pub decode_base64: bool,
+    /// Decode `%XX` escapes, as in query strings and form bodies, before
+    /// scanning, so `john%40example.com` is found. Masks of such values are
+    /// percent-encoded in turn.
+    pub decode_percent_encoding: bool,
+    /// Leave alone matches that overlap masks this engine produces, like
+    /// `***-**-6789` or `[EMAIL_1]`, so masking masked text changes nothing.
+    pub skip_masked_values: bool,
+    /// Characters of surrounding text included on each side of a finding
+    /// as its `snippet`, with PII in it masked. Zero leaves snippets out.
+    pub snippet_context_chars: usize,
+    /// Attach to each finding the signals that produced its confidence.
+    pub explain_confidence: bool,
+    /// Matches must score above this to be reported.
+    pub confidence_threshold: f64,
+    /// Thresholds overriding `confidence_threshold` for single PII types.
+    pub type_confidence_thresholds: HashMap<String, f64>,
+    /// Report matches at or below the threshold with `low_confidence` set
+    /// instead of dropping them. They are masked like any other finding.
+    pub report_low_confidence: bool,
+    /// First names for dictionary name detection, with their relative
+    /// frequency (any positive scale, e.g. census percentages). Common
+    /// names score higher. See `parse_name_list` for loading a file.
+    pub first_names: HashMap<String, f64>,
+    /// Surnames for dictionary name detection, like `first_names`.
+    pub surnames: HashMap<String, f64>,
+    /// Organizations, such as employers, detected by name in addition to
+    /// those ending in a legal form like `Inc.` or `GmbH`. Matched
+    /// case-insensitively as whole words, with any legal form after them.
+    /// Only reported with `organization` in `enabled_types`.
+    pub organization_names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailValidation {
+    Regex,
+    Validator,
+    Hybrid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditCardValidation {
+    Basic,
+    Luhn,
+    Full,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneValidation {
+    /// Any match of the phone patterns is a phone number.
+    Pattern,
+    /// Check matches against the numbering plans of the `phonenumber` crate
+    /// and report valid ones in E.164 form. Requires the `phonenumber`
+    /// feature.
+    PhoneNumber,
+}
+
+impl FromStr for EmailValidation {
+    type Err = DataCloakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "regex" => Ok(EmailValidation::Regex),
+            "validator" => Ok(EmailValidation::Validator),
+            "hybrid" => Ok(EmailValidation::Hybrid),
+            _ => Err(DataCloakError::InvalidConfig(format!(
+                "Unknown email validation mode '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+impl FromStr for CreditCardValidation {
+    type Err = DataCloakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "basic" => Ok(CreditCardValidation::Basic),
+            "luhn" => Ok(CreditCardValidation::Luhn),
+            "full" => Ok(CreditCardValidation::Full),
+            _ => Err(DataCloakError::InvalidConfig(format!(
+                "Unknown credit card validation mode '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+impl FromStr for PhoneValidation {
+    type Err = DataCloakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pattern" => Ok(PhoneValidation::Pattern),
+            "phone_number" => Ok(PhoneValidation::PhoneNumber),
+            _ => Err(DataCloakError::InvalidConfig(format!(
+                "Unknown phone validation mode '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+/// `Debug` prints keys, salts and seeds as `"<redacted>"`, so logging a
+/// config or engine never leaks them.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MaskingStrategy {
+    /// Partially reveal the value, e.g. `j***@example.com` or `***-**-6789`.
+    Partial,
+    /// Replace the value with an HMAC-SHA256 pseudonym under the given key, so
+    /// the same value always maps to the same token.
+    Hmac {
+        #[serde(with = "crate::hex::serde_hex")]
+        key: Vec<u8>,
+    },
+    /// Encrypt credit card numbers and SSNs with FF1 under a 32-byte key,
+    /// producing values of identical length and format that still pass
+    /// validation. Other types fall back to partial masking.
+    FormatPreserving {
+        #[serde(with = "crate::hex::serde_hex")]
+        key: Vec<u8>,
+    },
+    /// Replace the value with a salted SHA-256 digest truncated to
+    /// `hex_length` characters and prefixed by type, e.g. `email:ab12cd34`.
+    /// Irreversible, but equal values still produce equal digests.
+    SaltedHash {
+        #[serde(with = "crate::hex::serde_hex")]
+        salt: Vec<u8>,
+        hex_length: usize,
+    },
+    /// Replace the value with a realistic fake of the same type (plausible
+    /// emails and phone numbers, Luhn-valid card numbers). The seed makes the
+    /// replacement deterministic and should be kept secret.
+    Synthesize {
+        #[serde(with = "crate::hex::serde_hex")]
+        seed: Vec<u8>,
+    },
+    /// Replace each distinct value with a numbered typed placeholder such as
+    /// `[EMAIL_1]` or `[PHONE_2]`, numbered in order of first appearance.
+    /// Assignments persist for the engine's lifetime and can be carried to
+    /// other engines with `export_mapping` / `import_mapping`.
+    Placeholder,
+}
+
+impl fmt::Debug for MaskingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const REDACTED: &str = "<redacted>";
+        match self {
+            MaskingStrategy::Partial => f.write_str("Partial"),
+            MaskingStrategy::Hmac { .. } => f.debug_struct("Hmac").field("key", &REDACTED).finish(),
+            MaskingStrategy::FormatPreserving { .. } => f
+                .debug_struct("FormatPreserving")
+                .field("key", &REDACTED)
+                .finish(),
+            MaskingStrategy::SaltedHash { hex_length, .. } => f
+                .debug_struct("SaltedHash")
+                .field("salt", &REDACTED)
+                .field("hex_length", hex_length)
+                .finish(),
+            MaskingStrategy::Synthesize { .. } => f
+                .debug_struct("Synthesize")
+                .field("seed", &REDACTED)
+                .finish(),
+            MaskingStrategy::Placeholder => f.write_str("Placeholder"),
+        }
+    }
+}
+
+const DEFAULT_KEYWORD_WINDOW: usize = 32;
+
+const DEFAULT_MAX_MATCH_LEN: usize = 256;
+const MAX_MATCH_LEN_LIMIT: usize = 64 * 1024;
+
+/// Keywords per PII type.
+type KeywordTable = [(&'static str, &'static [&'static str])];
+
+const DEFAULT_BOOST_KEYWORDS: &KeywordTable = &[
+    ("ssn", &["ssn", "social security", "taxpayer"]),
+    (
+        "credit_card",
+        &["card", "credit", "visa", "mastercard", "amex", "discover"],
+    ),
+    (
+        "phone",
+        &["phone", "tel", "call", "mobile", "cell", "fax", "contact"],
+    ),
+];
+
+const DEFAULT_PENALTY_KEYWORDS: &KeywordTable = &[
+    ("ssn", &["order", "invoice", "ticket"]),
+    (
+        "credit_card",
+        &["order", "invoice", "tracking", "serial", "isbn"],
+    ),
+    (
+        "phone",
+        &["order", "invoice", "tracking", "account", "reference"],
+    ),
+];
+
+const DEFAULT_LOCALIZED_BOOST_KEYWORDS: &[(Language, &KeywordTable)] = &[
+    (
+        Language::French,
+        &[
+            ("ssn", &["sécurité sociale", "nir"]),
+            ("credit_card", &["carte", "carte bancaire", "cb"]),
+            ("phone", &["téléphone", "tél", "portable", "appeler", "joindre"]),
+        ],
+    ),
+    (
+        Language::German,
+        &[
+            ("ssn", &["sozialversicherungsnummer", "steuer-id"]),
+            ("credit_card", &["karte", "kreditkarte"]),
+            ("phone", &["telefon", "handy", "mobil", "anrufen", "rufnummer"]),
+        ],
+    ),
+    (
+        Language::Spanish,
+        &[
+            ("ssn", &["seguro social"]),
+            ("credit_card", &["tarjeta", "crédito"]),
+            ("phone", &["teléfono", "móvil", "celular", "llamar"]),
+        ],
+    ),
+    (
+        Language::Italian,
+        &[
+            ("credit_card", &["carta", "carta di credito"]),
+            ("phone", &["telefono", "cellulare", "chiamare"]),
+        ],
+    ),
+    (
+        Language::Portuguese,
+        &[
+            ("credit_card", &["cartão", "crédito"]),
+            ("phone", &["telefone", "celular", "telemóvel", "ligar"]),
+        ],
+    ),
+    (
+        Language::Dutch,
+        &[
+            ("credit_card", &["kaart", "creditcard"]),
+            ("phone", &["telefoon", "mobiel", "bellen"]),
+        ],
+    ),
+];
+
+const DEFAULT_LOCALIZED_PENALTY_KEYWORDS: &[(Language, &KeywordTable)] = &[
+    (
+        Language::French,
+        &[("phone", &["commande", "facture", "suivi", "référence"])],
+    ),
+    (
+        Language::German,
+        &[("phone", &["bestellung", "rechnung", "sendungsnummer", "kundennummer"])],
+    ),
+    (
+        Language::Spanish,
+        &[("phone", &["pedido", "factura", "seguimiento", "referencia"])],
+    ),
+    (
+        Language::Italian,
+        &[("phone", &["ordine", "fattura", "riferimento"])],
+    ),
+    (
+        Language::Portuguese,
+        &[("phone", &["pedido", "fatura", "referência"])],
+    ),
+    (
+        Language::Dutch,
+        &[("phone", &["bestelling", "factuur", "referentie"])],
+    ),
+];
+
+fn localized_keyword_table(
+    table: &[(Language, &KeywordTable)],
+) -> HashMap<Language, HashMap<String, Vec<String>>> {
+    table
+        .iter()
+        .map(|&(language, keywords)| (language, keyword_table(keywords)))
+        .collect()
+}
+
+fn keyword_table(table: &KeywordTable) -> HashMap<String, Vec<String>> {
+    table
+        .iter()
+        .map(|(pii_type, words)| {
+            let words = words.iter().map(|w| w.to_string()).collect();
+            (pii_type.to_string(), words)
+        })
+        .collect()
+}
+
+impl Default for DataCloakConfig {
+    fn default() -> Self {
+        Self {
+            enable_redos_protection: true,
+            email_validation: EmailValidation::Validator,
+            credit_card_validation: CreditCardValidation::Luhn,
+            phone_validation: PhoneValidation::Pattern,
+            max_text_length: 100_000,
+            regex_timeout_ms: 1000,
+            masking_strategy: MaskingStrategy::Partial,
+            mask_templates: HashMap::new(),
+            reveal_lengths: HashMap::new(),
+            role_views: HashMap::new(),
+            // Organizations are opt-in: legal-form suffixes are too ambiguous
+            // to mask by default
+            enabled_types: BUILTIN_TYPES
+                .iter()
+                .chain(&ENTITY_TYPES)
+                .chain(&LOCALE_TYPES)
+                .filter(|&&t| t != "organization")
+                .map(|t| t.to_string())
+                .collect(),
+            locales: Vec::new(),
+            regions: Vec::new(),
+            custom_patterns: HashMap::new(),
+            allowed_email_domains: Vec::new(),
+            obfuscated_emails: true,
+            allowed_values: Vec::new(),
+            allowed_patterns: Vec::new(),
+            denylist: Vec::new(),
+            max_matches_per_type: None,
+            max_findings: None,
+            memory_budget_bytes: None,
+            max_match_len: DEFAULT_MAX_MATCH_LEN,
+            json_mask_paths: HashMap::new(),
+            json_skip_paths: Vec::new(),
+            keyword_window: DEFAULT_KEYWORD_WINDOW,
+            boost_keywords: keyword_table(DEFAULT_BOOST_KEYWORDS),
+            penalty_keywords: keyword_table(DEFAULT_PENALTY_KEYWORDS),
+            localized_boost_keywords: localized_keyword_table(DEFAULT_LOCALIZED_BOOST_KEYWORDS),
+            localized_penalty_keywords: localized_keyword_table(
+                DEFAULT_LOCALIZED_PENALTY_KEYWORDS,
+            ),
+            language_detection: true,
+            normalize_unicode: false,
+            decode_base64: false,
+            decode_percent_encoding: false,
+            skip_masked_values: true,
+            snippet_context_chars: 0,
+            explain_confidence: false,
+            confidence_threshold: 0.6,
+            type_confidence_thresholds: HashMap::new(),
+            report_low_confidence: false,
+            first_names: HashMap::new(),
+            surnames: HashMap::new(),
+            organization_names: Vec::new(),
+        }
+    }
+}
+
+impl DataCloakConfig {
+    pub fn builder() -> DataCloakConfigBuilder {
+        DataCloakConfigBuilder::default()
+    }
+
+    /// `locales` followed by those of `regions`, without repeats.
+    pub fn all_locales(&self) -> Vec<Locale> {
+        let regions = self.regions.iter().flat_map(|region| region.locales());
+        let mut locales: Vec<Locale> = Vec::new();
+        for &locale in self.locales.iter().chain(regions) {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
+        locales
+    }
+
+    /// The confidence a `pii_type` match must exceed to be reported.
+    pub fn confidence_threshold_for(&self, pii_type: &str) -> f64 {
+        self.type_confidence_thresholds
+            .get(pii_type)
+            .copied()
+            .unwrap_or(self.confidence_threshold)
+    }
+
+    /// Checks the configuration for values the engine can't work with.
+    pub fn validate(&self) -> Result<(), DataCloakError> {
+        if self.max_text_length == 0 {
+            return Err(DataCloakError::InvalidConfig(
+                "max_text_length must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.regex_timeout_ms == 0 {
+            return Err(DataCloakError::InvalidConfig(
+                "regex_timeout_ms must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.enabled_types.is_empty() {
+            return Err(DataCloakError::InvalidConfig(
+                "At least one PII type must be enabled".to_string(),
+            ));
+        }
+
+        if let MaskingStrategy::Hmac { key } = &self.masking_strategy {
+            if key.is_empty() {
+                return Err(DataCloakError::InvalidConfig(
+                    "HMAC masking requires a non-empty key".to_string(),
+                ));
+            }
+        }
+
+        if let MaskingStrategy::SaltedHash { hex_length, .. } = &self.masking_strategy {
+            if *hex_length == 0 || *hex_length > 64 {
+                return Err(DataCloakError::InvalidConfig(format!(
+                    "Salted hash length must be between 1 and 64 hex characters, got {}",
+                    hex_length
+                )));
+            }
+        }
+
+        if let MaskingStrategy::FormatPreserving { key } = &self.masking_strategy {
+            if key.len() != FormatPreservingCipher::KEY_LEN {
+                return Err(DataCloakError::InvalidConfig(format!(
+                    "Format-preserving encryption requires a {}-byte key, got {}",
+                    FormatPreservingCipher::KEY_LEN,
+                    key.len()
+                )));
+            }
+        }
+
+        #[cfg(not(feature = "phonenumber"))]
+        if matches!(self.phone_validation, PhoneValidation::PhoneNumber) {
+            return Err(DataCloakError::InvalidConfig(
+                "Phone number validation requires the phonenumber feature".to_string(),
+            ));
+        }
+
+        if self.memory_budget_bytes == Some(0) {
+            return Err(DataCloakError::InvalidConfig(
+                "memory_budget_bytes must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.max_match_len == 0 || self.max_match_len > MAX_MATCH_LEN_LIMIT {
+            return Err(DataCloakError::InvalidConfig(format!(
+                "max_match_len must be between 1 and {}",
+                MAX_MATCH_LEN_LIMIT
+            )));
+        }
+
+        if self.max_matches_per_type == Some(0) || self.max_findings == Some(0) {
+            return Err(DataCloakError::InvalidConfig(
+                "Match limits must be greater than zero".to_string(),
+            ));
+        }
+
+        let thresholds = self.type_confidence_thresholds.values();
+        if std::iter::once(&self.confidence_threshold)
+            .chain(thresholds)
+            .any(|threshold| !(0.0..=1.0).contains(threshold))
+        {
+            return Err(DataCloakError::InvalidConfig(
+                "Confidence thresholds must be between 0 and 1".to_string(),
+            ));
+        }
+
+        let mut frequencies = self.first_names.values().chain(self.surnames.values());
+        if frequencies.any(|&f| !(f.is_finite() && f > 0.0)) {
+            return Err(DataCloakError::InvalidConfig(
+                "Name frequencies must be positive".to_string(),
+            ));
+        }
+
+        let keywords = self
+            .boost_keywords
+            .values()
+            .chain(self.penalty_keywords.values())
+            .chain(
+                self.localized_boost_keywords
+                    .values()
+                    .chain(self.localized_penalty_keywords.values())
+                    .flat_map(HashMap::values),
+            );
+        if keywords.flatten().any(|word| word.trim().is_empty()) {
+            return Err(DataCloakError::InvalidConfig(
+                "Context keywords must not be empty".to_string(),
+            ));
+        }
+
+        let mut domains = self.allowed_email_domains.iter();
+        if domains.any(|domain| domain.trim().trim_start_matches('@').is_empty()) {
+            return Err(DataCloakError::InvalidConfig(
+                "Allowed email domains must not be empty".to_string(),
+            ));
+        }
+
+        if self.organization_names.iter().any(|name| name.trim().is_empty()) {
+            return Err(DataCloakError::InvalidConfig(
+                "Organization names must not be empty".to_string(),
+            ));
+        }
+
+        if self.denylist.iter().any(|term| term.trim().is_empty()) {
+            return Err(DataCloakError::InvalidConfig(
+                "Denylist terms must not be empty".to_string(),
+            ));
+        }
+
+        for template in self.mask_templates.values() {
+            MaskTemplate::parse(template)?;
+        }
+
+        for path in self.json_mask_paths.keys().chain(&self.json_skip_paths) {
+            JsonPath::parse(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a `DataCloakConfig`, starting from the defaults and validating the
+/// result in `build`.
+#[derive(Debug, Clone, Default)]
+pub struct DataCloakConfigBuilder {
+    config: DataCloakConfig,
+}
+
+impl DataCloakConfigBuilder {
+    pub fn enable_redos_protection(mut self, enabled: bool) -> Self {
+        self.config.enable_redos_protection = enabled;
+        self
+    }
+
+    pub fn email_validation(mut self, validation: EmailValidation) -> Self {
+        self.config.email_validation = validation;
+        self
+    }
+
+    pub fn credit_card_validation(mut self, validation: CreditCardValidation) -> Self {
+        self.config.credit_card_validation = validation;
+        self
+    }
+
+    pub fn phone_validation(mut self, validation: PhoneValidation) -> Self {
+        self.config.phone_validation = validation;
+        self
+    }
+
+    pub fn max_text_length(mut self, max: usize) -> Self {
+        self.config.max_text_length = max;
+        self
+    }
+
+    pub fn regex_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.config.regex_timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn masking_strategy(mut self, strategy: MaskingStrategy) -> Self {
+        self.config.masking_strategy = strategy;
+        self
+    }
+
+    pub fn mask_template(mut self, pii_type: &str, template: &str) -> Self {
+        self.config
+            .mask_templates
+            .insert(pii_type.to_string(), template.to_string());
+        self
+    }
+
+    pub fn reveal_length(mut self, pii_type: &str, length: usize) -> Self {
+        self.config
+            .reveal_lengths
+            .insert(pii_type.to_string(), length);
+        self
+    }
+
+    pub fn role_view(mut self, role: &str, view: MaskingView) -> Self {
+        self.config.role_views.insert(role.to_string(), view);
+        self
+    }
+
+    pub fn custom_pattern(mut self, pii_type: &str, pattern: &str) -> Self {
+        self.config
+            .custom_patterns
+            .insert(pii_type.to_string(), pattern.to_string());
+        self
+    }
+
+    pub fn max_matches_per_type(mut self, max: usize) -> Self {
+        self.config.max_matches_per_type = Some(max);
+        self
+    }
+
+    pub fn max_findings(mut self, max: usize) -> Self {
+        self.config.max_findings = Some(max);
+        self
+    }
+
+    pub fn memory_budget_bytes(mut self, bytes: usize) -> Self {
+        self.config.memory_budget_bytes = Some(bytes);
+        self
+    }
+
+    pub fn max_match_len(mut self, bytes: usize) -> Self {
+        self.config.max_match_len = bytes;
+        self
+    }
+
+    /// Always masks JSON values under `path` as `pii_type`.
+    pub fn json_mask_path(mut self, path: &str, pii_type: &str) -> Self {
+        self.config
+            .json_mask_paths
+            .insert(path.to_string(), pii_type.to_string());
+        self
+    }
+
+    /// Leaves JSON values under `path` untouched.
+    pub fn json_skip_path(mut self, path: &str) -> Self {
+        self.config.json_skip_paths.push(path.to_string());
+        self
+    }
+
+    pub fn keyword_window(mut self, chars: usize) -> Self {
+        self.config.keyword_window = chars;
+        self
+    }
+
+    /// Replaces the words that raise confidence of nearby `pii_type` matches.
+    pub fn boost_keywords(mut self, pii_type: &str, words: &[&str]) -> Self {
+        self.config.boost_keywords.insert(
+            pii_type.to_string(),
+            words.iter().map(|w| w.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Replaces the words that lower confidence of nearby `pii_type` matches.
+    pub fn penalty_keywords(mut self, pii_type: &str, words: &[&str]) -> Self {
+        self.config.penalty_keywords.insert(
+            pii_type.to_string(),
+            words.iter().map(|w| w.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Replaces the words that raise confidence of nearby `pii_type`
+    /// matches in `language` text.
+    pub fn localized_boost_keywords(
+        mut self,
+        language: Language,
+        pii_type: &str,
+        words: &[&str],
+    ) -> Self {
+        self.config
+            .localized_boost_keywords
+            .entry(language)
+            .or_default()
+            .insert(
+                pii_type.to_string(),
+                words.iter().map(|w| w.to_string()).collect(),
+            );
+        self
+    }
+
+    /// Replaces the words that lower confidence of nearby `pii_type`
+    /// matches in `language` text.
+    pub fn localized_penalty_keywords(
+        mut self,
+        language: Language,
+        pii_type: &str,
+        words: &[&str],
+    ) -> Self {
+        self.config
+            .localized_penalty_keywords
+            .entry(language)
+            .or_default()
+            .insert(
+                pii_type.to_string(),
+                words.iter().map(|w| w.to_string()).collect(),
+            );
+        self
+    }
+
+    pub fn language_detection(mut self, enabled: bool) -> Self {
+        self.config.language_detection = enabled;
+        self
+    }
+
+    pub fn normalize_unicode(mut self, enabled: bool) -> Self {
+        self.config.normalize_unicode = enabled;
+        self
+    }
+
+    pub fn decode_base64(mut self, enabled: bool) -> Self {
+        self.config.decode_base64 = enabled;
+        self
+    }
+
+    pub fn decode_percent_encoding(mut self, enabled: bool) -> Self {
+        self.config.decode_percent_encoding = enabled;
+        self
+    }
+
+    pub fn skip_masked_values(mut self, enabled: bool) -> Self {
+        self.config.skip_masked_values = enabled;
+        self
+    }
+
+    pub fn snippet_context_chars(mut self, chars: usize) -> Self {
+        self.config.snippet_context_chars = chars;
+        self
+    }
+
+    pub fn explain_confidence(mut self, enabled: bool) -> Self {
+        self.config.explain_confidence = enabled;
+        self
+    }
+
+    pub fn confidence_threshold(mut self, threshold: f64) -> Self {
+        self.config.confidence_threshold = threshold;
+        self
+    }
+
+    pub fn type_confidence_threshold(mut self, pii_type: &str, threshold: f64) -> Self {
+        self.config
+            .type_confidence_thresholds
+            .insert(pii_type.to_string(), threshold);
+        self
+    }
+
+    pub fn report_low_confidence(mut self, enabled: bool) -> Self {
+        self.config.report_low_confidence = enabled;
+        self
+    }
+
+    /// Replaces the first-name list with `(name, frequency)` pairs.
+    pub fn first_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = (S, f64)>,
+        S: Into<String>,
+    {
+        self.config.first_names = names.into_iter().map(|(n, f)| (n.into(), f)).collect();
+        self
+    }
+
+    /// Replaces the surname list with `(name, frequency)` pairs.
+    pub fn surnames<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = (S, f64)>,
+        S: Into<String>,
+    {
+        self.config.surnames = names.into_iter().map(|(n, f)| (n.into(), f)).collect();
+        self
+    }
+
+    /// Replaces the domains whose email addresses are not PII.
+    pub fn allowed_email_domains<I, S>(mut self, domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.allowed_email_domains = domains.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn obfuscated_emails(mut self, enabled: bool) -> Self {
+        self.config.obfuscated_emails = enabled;
+        self
+    }
+
+    /// Replaces the values that are never PII.
+    pub fn allowed_values<I, S>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.allowed_values = values.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Replaces the patterns of values that are never PII.
+    pub fn allowed_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.allowed_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Replaces the terms that are always masked.
+    pub fn denylist<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.denylist = terms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Replaces the listed organization names.
+    pub fn organization_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.organization_names = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Replaces the locales whose formats are detected.
+    pub fn locales(mut self, locales: impl IntoIterator<Item = Locale>) -> Self {
+        self.config.locales = locales.into_iter().collect();
+        self
+    }
+
+    /// Replaces the regions whose locale packs are detected.
+    pub fn regions(mut self, regions: impl IntoIterator<Item = RegionProfile>) -> Self {
+        self.config.regions = regions.into_iter().collect();
+        self
+    }
+
+    /// Replaces the set of enabled PII types.
+    pub fn enabled_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.enabled_types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn build(self) -> Result<DataCloakConfig, DataCloakError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_validates() {
+        let config = DataCloakConfig::builder()
+            .max_text_length(500)
+            .reveal_length("ssn", 0)
+            .enabled_types(["email", "ssn"])
+            .build()
+            .unwrap();
+        assert_eq!(config.max_text_length, 500);
+        assert_eq!(config.enabled_types.len(), 2);
+
+        assert!(DataCloakConfig::builder()
+            .regex_timeout_ms(0)
+            .build()
+            .is_err());
+        assert!(DataCloakConfig::builder()
+            .enabled_types(Vec::<String>::new())
+            .build()
+            .is_err());
+        assert!(DataCloakConfig::builder()
+            .max_findings(0)
+            .build()
+            .is_err());
+        assert!(DataCloakConfig::builder()
+            .max_match_len(64 * 1024 + 1)
+            .build()
+            .is_err());
+        assert!(DataCloakConfig::builder()
+            .mask_template("email", "{nope}")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_config_serde_round_trip() {
+        let config = DataCloakConfig::builder()
+            .credit_card_validation(CreditCardValidation::Basic)
+            .masking_strategy(MaskingStrategy::SaltedHash {
+                salt: vec![0xde, 0xad],
+                hex_length: 12,
+            })
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""credit_card_validation":"basic""#));
+        assert!(json.contains(r#""salt":"dead""#));
+
+        let restored: DataCloakConfig = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            restored.masking_strategy,
+            MaskingStrategy::SaltedHash { ref salt, hex_length: 12 } if salt == &[0xde, 0xad]
+        ));
+        assert!(matches!(
+            restored.credit_card_validation,
+            CreditCardValidation::Basic
+        ));
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let secret = b"do-not-log-this-key-0123456789ab".to_vec();
+        let bytes = format!("{:?}", secret);
+        let bytes = bytes.trim_matches(['[', ']']);
+        for strategy in [
+            MaskingStrategy::Hmac {
+                key: secret.clone(),
+            },
+            MaskingStrategy::FormatPreserving {
+                key: secret.clone(),
+            },
+            MaskingStrategy::SaltedHash {
+                salt: secret.clone(),
+                hex_length: 8,
+            },
+            MaskingStrategy::Synthesize {
+                seed: secret.clone(),
+            },
+        ] {
+            let config = DataCloakConfig::builder()
+                .masking_strategy(strategy)
+                .build()
+                .unwrap();
+            let debug = format!("{:?}", config);
+            assert!(!debug.contains(bytes), "{}", debug);
+            assert!(debug.contains("\"<redacted>\""));
+
+            let engine = crate::DataCloakEngine::new(config).unwrap();
+            assert!(!format!("{:?}", engine).contains(bytes));
+        }
+        assert_eq!(
+            format!(
+                "{:?}",
+                MaskingStrategy::SaltedHash {
+                    salt: secret,
+                    hex_length: 8
+                }
+            ),
+            "SaltedHash { salt: \"<redacted>\", hex_length: 8 }"
+        );
+    }
+}
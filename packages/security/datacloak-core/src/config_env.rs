@@ -0,0 +1,162 @@
+use crate::config::{DataCloakConfig, MaskingStrategy};
+use crate::error::DataCloakError;
+use crate::hex::decode_hex;
+
+// Variables understood by `DataCloakConfig::from_env`. Names match the ones
+// the workbench backend already documents; other `DATACLOAK_*` variables
+// belong to the hosts and are ignored.
+const ENV_REDOS_PROTECTION: &str = "DATACLOAK_REDOS_PROTECTION";
+const ENV_EMAIL_VALIDATION: &str = "DATACLOAK_EMAIL_VALIDATION";
+const ENV_CC_VALIDATION: &str = "DATACLOAK_CC_VALIDATION";
+const ENV_MAX_TEXT_LENGTH: &str = "DATACLOAK_MAX_TEXT_LENGTH";
+const ENV_REGEX_TIMEOUT: &str = "DATACLOAK_REGEX_TIMEOUT";
+const ENV_ENABLED_TYPES: &str = "DATACLOAK_ENABLED_TYPES";
+const ENV_MASKING_STRATEGY: &str = "DATACLOAK_MASKING_STRATEGY";
+const ENV_MASKING_KEY: &str = "DATACLOAK_MASKING_KEY";
+const ENV_HASH_LENGTH: &str = "DATACLOAK_HASH_LENGTH";
+
+const DEFAULT_HASH_LENGTH: usize = 16;
+
+impl DataCloakConfig {
+    /// Builds a config from the defaults overlaid with `DATACLOAK_*`
+    /// environment variables.
+    pub fn from_env() -> Result<Self, DataCloakError> {
+        DataCloakConfig::default().with_env_overrides()
+    }
+
+    /// Overlays `DATACLOAK_*` environment variables onto this config.
+    pub fn with_env_overrides(self) -> Result<Self, DataCloakError> {
+        self.with_overrides(std::env::vars())
+    }
+
+    fn with_overrides<I>(mut self, vars: I) -> Result<Self, DataCloakError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut strategy = None;
+        let mut key = None;
+        let mut hash_length = None;
+
+        for (name, value) in vars {
+            let value = value.trim().to_string();
+            match name.as_str() {
+                ENV_REDOS_PROTECTION => self.enable_redos_protection = parse_bool(&name, &value)?,
+                ENV_EMAIL_VALIDATION => self.email_validation = value.parse()?,
+                ENV_CC_VALIDATION => self.credit_card_validation = value.parse()?,
+                ENV_MAX_TEXT_LENGTH => self.max_text_length = parse_number(&name, &value)?,
+                ENV_REGEX_TIMEOUT => self.regex_timeout_ms = parse_number(&name, &value)?,
+                ENV_ENABLED_TYPES => {
+                    self.enabled_types = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+                ENV_MASKING_STRATEGY => strategy = Some(value.to_ascii_lowercase()),
+                ENV_MASKING_KEY => key = Some(decode_hex(&value)?),
+                ENV_HASH_LENGTH => hash_length = Some(parse_number(&name, &value)?),
+                _ => {}
+            }
+        }
+
+        if let Some(strategy) = strategy {
+            let mut require_key = || {
+                key.take().ok_or_else(|| {
+                    DataCloakError::InvalidConfig(format!(
+                        "{} '{}' requires {}",
+                        ENV_MASKING_STRATEGY, strategy, ENV_MASKING_KEY
+                    ))
+                })
+            };
+
+            self.masking_strategy = match strategy.as_str() {
+                "partial" => MaskingStrategy::Partial,
+                "placeholder" => MaskingStrategy::Placeholder,
+                "hmac" => MaskingStrategy::Hmac {
+                    key: require_key()?,
+                },
+                "format_preserving" => MaskingStrategy::FormatPreserving {
+                    key: require_key()?,
+                },
+                "salted_hash" => MaskingStrategy::SaltedHash {
+                    salt: require_key()?,
+                    hex_length: hash_length.unwrap_or(DEFAULT_HASH_LENGTH),
+                },
+                "synthesize" => MaskingStrategy::Synthesize {
+                    seed: require_key()?,
+                },
+                _ => {
+                    return Err(DataCloakError::InvalidConfig(format!(
+                        "Unknown masking strategy '{}' in {}",
+                        strategy, ENV_MASKING_STRATEGY
+                    )))
+                }
+            };
+        }
+
+        self.validate()?;
+        Ok(self)
+    }
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool, DataCloakError> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(DataCloakError::InvalidConfig(format!(
+            "{} must be a boolean, got '{}'",
+            name, value
+        ))),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, DataCloakError> {
+    value.parse().map_err(|_| {
+        DataCloakError::InvalidConfig(format!("{} must be a number, got '{}'", name, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_overlay() {
+        let config = DataCloakConfig::default()
+            .with_overrides(vars(&[
+                ("DATACLOAK_MAX_TEXT_LENGTH", "5000"),
+                ("DATACLOAK_CC_VALIDATION", "basic"),
+                ("DATACLOAK_ENABLED_TYPES", "email, ssn"),
+                ("DATACLOAK_MASKING_STRATEGY", "hmac"),
+                ("DATACLOAK_MASKING_KEY", "deadbeef"),
+                ("DATACLOAK_API_KEY", "belongs-to-the-host"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.max_text_length, 5000);
+        assert_eq!(config.enabled_types.len(), 2);
+        assert!(matches!(
+            config.masking_strategy,
+            MaskingStrategy::Hmac { .. }
+        ));
+    }
+
+    #[test]
+    fn test_env_overlay_errors() {
+        let base = DataCloakConfig::default;
+        assert!(base()
+            .with_overrides(vars(&[("DATACLOAK_REGEX_TIMEOUT", "soon")]))
+            .is_err());
+        assert!(base()
+            .with_overrides(vars(&[("DATACLOAK_MASKING_STRATEGY", "hmac")]))
+            .is_err());
+    }
+}
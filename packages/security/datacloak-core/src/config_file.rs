@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use crate::config::DataCloakConfig;
+use crate::error::DataCloakError;
+
+/// Serialization formats accepted by `DataCloakConfig::from_file`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Picks the format from a file extension (`.json`, `.yaml`/`.yml`, `.toml`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "toml" => Some(ConfigFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl DataCloakConfig {
+    /// Loads a config from a JSON, YAML or TOML file, chosen by extension.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, DataCloakError> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path).ok_or_else(|| {
+            DataCloakError::InvalidConfig(format!(
+                "Unsupported config file extension: {}",
+                path.display()
+            ))
+        })?;
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| DataCloakError::Io(format!("{}: {}", path.display(), e)))?;
+
+        Self::parse(&contents, format)
+    }
+
+    /// Parses a config document. Fields the document leaves out keep their
+    /// default values.
+    pub fn parse(contents: &str, format: ConfigFormat) -> Result<Self, DataCloakError> {
+        let config: DataCloakConfig = match format {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+        }
+        .map_err(DataCloakError::InvalidConfig)?;
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MaskingStrategy;
+
+    #[test]
+    fn test_parse_formats() {
+        let json = r#"{
+            "max_text_length": 2048,
+            "email_validation": "regex",
+            "masking_strategy": { "strategy": "hmac", "key": "0a0b0c" },
+            "mask_templates": { "ssn": "XXX-XX-{last4}" },
+            "custom_patterns": { "employee_id": "EMP-\\d{6}" }
+        }"#;
+        let config = DataCloakConfig::parse(json, ConfigFormat::Json).unwrap();
+        assert_eq!(config.max_text_length, 2048);
+        assert!(matches!(
+            config.masking_strategy,
+            MaskingStrategy::Hmac { ref key } if key == &[10, 11, 12]
+        ));
+        assert_eq!(config.custom_patterns["employee_id"], r"EMP-\d{6}");
+
+        let yaml = "regex_timeout_ms: 250\nenabled_types: [email, ssn]\n";
+        let config = DataCloakConfig::parse(yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config.regex_timeout_ms, 250);
+        assert_eq!(config.enabled_types.len(), 2);
+
+        let toml = "credit_card_validation = \"basic\"\n[reveal_lengths]\ncredit_card = 0\n";
+        let config = DataCloakConfig::parse(toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(config.reveal_lengths["credit_card"], 0);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_fields() {
+        assert!(DataCloakConfig::parse("{\"max_len\": 5}", ConfigFormat::Json).is_err());
+        assert!(
+            DataCloakConfig::parse("{\"email_validation\": \"strict\"}", ConfigFormat::Json)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Letters from other scripts that render like Latin ones, and dashes that
+/// render like `-`, with the ASCII character each stands in for. NFKC
+/// already folds fullwidth and other compatibility forms.
+const CONFUSABLES: &[(char, char)] = &[
+    // Cyrillic
+    ('а', 'a'),
+    ('в', 'b'),
+    ('с', 'c'),
+    ('ԁ', 'd'),
+    ('е', 'e'),
+    ('һ', 'h'),
+    ('і', 'i'),
+    ('ј', 'j'),
+    ('к', 'k'),
+    ('ӏ', 'l'),
+    ('м', 'm'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('ԛ', 'q'),
+    ('ѕ', 's'),
+    ('т', 't'),
+    ('у', 'y'),
+    ('ԝ', 'w'),
+    ('х', 'x'),
+    ('А', 'A'),
+    ('В', 'B'),
+    ('С', 'C'),
+    ('Е', 'E'),
+    ('Н', 'H'),
+    ('І', 'I'),
+    ('Ј', 'J'),
+    ('К', 'K'),
+    ('М', 'M'),
+    ('О', 'O'),
+    ('Р', 'P'),
+    ('Ѕ', 'S'),
+    ('Т', 'T'),
+    ('Х', 'X'),
+    ('У', 'Y'),
+    // Greek
+    ('ο', 'o'),
+    ('ν', 'v'),
+    ('ρ', 'p'),
+    ('Α', 'A'),
+    ('Β', 'B'),
+    ('Ε', 'E'),
+    ('Ζ', 'Z'),
+    ('Η', 'H'),
+    ('Ι', 'I'),
+    ('Κ', 'K'),
+    ('Μ', 'M'),
+    ('Ν', 'N'),
+    ('Ο', 'O'),
+    ('Ρ', 'P'),
+    ('Τ', 'T'),
+    ('Υ', 'Y'),
+    ('Χ', 'X'),
+    // Dashes and minus signs
+    ('\u{2010}', '-'),
+    ('\u{2011}', '-'),
+    ('\u{2012}', '-'),
+    ('\u{2013}', '-'),
+    ('\u{2014}', '-'),
+    ('\u{2212}', '-'),
+];
+
+/// Text rewritten for detection, like NFKC with confusables replaced,
+/// traced back to the text it came from.
+pub(crate) struct NormalizedText {
+    text: String,
+    /// For each byte of `text`, the offset of the original character it
+    /// came from, followed by the original length.
+    origins: Vec<usize>,
+}
+
+impl NormalizedText {
+    /// Normalizes `original`, or `None` if that changes nothing.
+    pub(crate) fn new(original: &str) -> Option<Self> {
+        let mut text = String::with_capacity(original.len());
+        let mut origins = Vec::with_capacity(original.len() + 1);
+        let mut changed = false;
+        for (offset, c) in original.char_indices() {
+            let before = text.len();
+            for folded in std::iter::once(c).nfkc() {
+                text.push(unconfuse(folded));
+            }
+            changed |= text[before..] != original[offset..offset + c.len_utf8()];
+            origins.resize(text.len(), offset);
+        }
+        origins.push(original.len());
+        changed.then_some(Self { text, origins })
+    }
+
+    /// `text` traced back through `origins`, which must hold one offset
+    /// into the original per byte of `text` plus the original length.
+    pub(crate) fn from_parts(text: String, origins: Vec<usize>) -> Self {
+        debug_assert_eq!(origins.len(), text.len() + 1);
+        Self { text, origins }
+    }
+
+    /// `next`, a rewrite of this text, traced back to the original.
+    pub(crate) fn then(self, next: NormalizedText) -> Self {
+        let origins = next
+            .origins
+            .iter()
+            .map(|&origin| self.origins[origin])
+            .collect();
+        Self {
+            text: next.text,
+            origins,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The span of the original text that `start..end` of the normalized
+    /// text came from, widened to whole original characters.
+    pub(crate) fn original_span(&self, start: usize, end: usize) -> (usize, usize) {
+        let original_start = self.origins[start];
+        if end <= start {
+            return (original_start, original_start);
+        }
+        let last = self.origins[end - 1];
+        let original_end = self.origins[end..]
+            .iter()
+            .copied()
+            .find(|&origin| origin > last)
+            .unwrap_or(last);
+        (original_start, original_end)
+    }
+}
+
+fn unconfuse(c: char) -> char {
+    CONFUSABLES
+        .iter()
+        .find(|&&(confusable, _)| confusable == c)
+        .map_or(c, |&(_, ascii)| ascii)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, DataCloakEngine};
+
+    #[test]
+    fn test_normalized_spans_map_to_original_offsets() {
+        let original = "SSN: １２３‐４５‐６７８９!";
+        let normalized = NormalizedText::new(original).unwrap();
+        assert_eq!(normalized.as_str(), "SSN: 123-45-6789!");
+
+        let (start, end) = normalized.original_span(5, 16);
+        assert_eq!(&original[start..end], "１２３‐４５‐６７８９");
+
+        assert!(NormalizedText::new("plain ascii").is_none());
+    }
+
+    #[test]
+    fn test_lookalikes_are_detected_when_enabled() {
+        // Cyrillic `о` and `а` in the local part and domain
+        let text = "Mail jоhn@exаmple.com or call ５５５－１２３－４５６７";
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let found = engine.detect_pii(text).unwrap();
+        assert!(found.iter().all(|pii| pii.pii_type != "phone"));
+
+        let config = DataCloakConfig::builder()
+            .normalize_unicode(true)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine.mask_text(text).unwrap();
+        let found: Vec<_> = result
+            .detected_pii
+            .iter()
+            .map(|pii| {
+                (
+                    pii.pii_type.as_str(),
+                    pii.sample.as_str(),
+                    pii.normalized.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("email", "jоhn@exаmple.com", Some("john@example.com")),
+                ("phone", "５５５－１２３－４５６７", Some("555-123-4567")),
+            ]
+        );
+        assert_eq!(
+            &text[result.detected_pii[1].start..],
+            "５５５－１２３－４５６７"
+        );
+        assert!(!result.masked_text.contains("５５５"));
+    }
+}
@@ -0,0 +1,304 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ConfidenceExplanation, ConfidenceSignal, DataCloakEngine, DataCloakError, MaskingResult,
+    MaskingStrategy, PIIDetectionResult,
+};
+
+/// Confidence given to a finding whose type matches its field.
+pub(crate) const HINTED_CONFIDENCE: f64 = 0.99;
+
+/// Confidence of a value reported because its field expects the type, when
+/// the type's pattern did not match on its own.
+const FORCED_CONFIDENCE: f64 = 0.9;
+
+/// Applied to a finding that fills a whole identifier field, such as a
+/// 10-digit `order_id` read as a phone number.
+const IDENTIFIER_PENALTY: f64 = 0.5;
+
+/// Words in a field name that point at a PII type.
+const FIELD_HINTS: &[(&str, &str)] = &[
+    ("email", "email"),
+    ("mail", "email"),
+    ("phone", "phone"),
+    ("tel", "phone"),
+    ("telephone", "phone"),
+    ("mobile", "phone"),
+    ("cell", "phone"),
+    ("fax", "phone"),
+    ("ssn", "ssn"),
+    ("social", "ssn"),
+    ("card", "credit_card"),
+    ("cc", "credit_card"),
+    ("pan", "credit_card"),
+    ("creditcard", "credit_card"),
+];
+
+/// Words in a field name that mark it as holding identifiers rather than
+/// personal data.
+const IDENTIFIER_WORDS: &[&str] = &["id", "ref", "reference", "order", "invoice", "sku", "uuid"];
+
+/// What is known about the field a value came from, such as a column
+/// header or a record key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldContext {
+    /// Field or column name, e.g. `email_address` or `order_id`. Findings
+    /// are reported under it.
+    pub name: String,
+    /// PII type the field holds, overriding what its name suggests.
+    #[serde(default)]
+    pub pii_type: Option<String>,
+}
+
+impl FieldContext {
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pii_type: None,
+        }
+    }
+
+    pub fn with_pii_type(mut self, pii_type: &str) -> Self {
+        self.pii_type = Some(pii_type.to_string());
+        self
+    }
+
+    /// The type set with `with_pii_type`, else the one the name suggests.
+    pub fn expected_type(&self) -> Option<&str> {
+        self.pii_type.as_deref().or_else(|| field_hint(&self.name))
+    }
+
+    fn holds_identifiers(&self) -> bool {
+        field_words(&self.name)
+            .iter()
+            .any(|word| IDENTIFIER_WORDS.contains(&word.as_str()))
+    }
+}
+
+impl DataCloakEngine {
+    /// Detects PII in a value of the field described by `context`.
+    ///
+    /// When the field expects a type (`ssn`, `billing_phone`, or an explicit
+    /// `pii_type`), findings of that type get raised confidence, and a value
+    /// shaped like the type is reported whole even where the pattern needs
+    /// more, so a 9-digit SSN column without dashes is still found. In an
+    /// identifier field (`order_id`, `invoice_ref`) a finding that is the
+    /// entire value gets lower confidence and is dropped if that leaves it
+    /// at or below the type's threshold. Findings are reported under
+    /// `context.name`.
+    pub fn detect_pii_with_context(
+        &self,
+        text: &str,
+        context: &FieldContext,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut results = self.detect_pii(text)?;
+        self.apply_context(text, context, &mut results);
+        Ok(results)
+    }
+
+    /// Masks `text` using the findings of `detect_pii_with_context`.
+    pub fn mask_text_with_context(
+        &self,
+        text: &str,
+        context: &FieldContext,
+    ) -> Result<MaskingResult, DataCloakError> {
+        let start_time = std::time::Instant::now();
+        let mut detected = self.detect_for_masking(text)?;
+        self.apply_context(text, context, &mut detected.results);
+
+        let placeholders = matches!(self.config.masking_strategy, MaskingStrategy::Placeholder);
+        if placeholders {
+            self.assign_placeholders(text, &mut detected.results)?;
+        }
+        Ok(self.apply_masks(text, detected, start_time, placeholders))
+    }
+
+    fn apply_context(
+        &self,
+        text: &str,
+        context: &FieldContext,
+        results: &mut Vec<PIIDetectionResult>,
+    ) {
+        if !context.name.is_empty() {
+            for pii in results.iter_mut() {
+                pii.field_name = context.name.clone();
+            }
+        }
+
+        if let Some(expected) = context.expected_type() {
+            for pii in results.iter_mut().filter(|pii| pii.pii_type == expected) {
+                pii.confidence = pii.confidence.max(HINTED_CONFIDENCE);
+                pii.low_confidence =
+                    pii.confidence <= self.config.confidence_threshold_for(expected);
+                pii.explain(|confidence| ConfidenceSignal::FieldHint {
+                    field: context.name.clone(),
+                    confidence,
+                });
+            }
+            if !results.iter().any(|pii| pii.pii_type == expected) {
+                results.extend(self.shaped_value(text, expected, context));
+            }
+        } else if context.holds_identifiers() {
+            let value = text.trim();
+            results.retain_mut(|pii| {
+                if pii.sample.trim() == value {
+                    pii.confidence *= IDENTIFIER_PENALTY;
+                    pii.explain(|confidence| ConfidenceSignal::IdentifierField {
+                        field: context.name.clone(),
+                        confidence,
+                    });
+                }
+                pii.low_confidence =
+                    pii.confidence <= self.config.confidence_threshold_for(&pii.pii_type);
+                !pii.low_confidence || self.config.report_low_confidence
+            });
+        }
+    }
+
+    /// The whole of `text` as a `pii_type` finding, if the type is enabled
+    /// and the value has its shape once separators are ignored.
+    fn shaped_value(
+        &self,
+        text: &str,
+        pii_type: &str,
+        context: &FieldContext,
+    ) -> Option<PIIDetectionResult> {
+        if !self.config.enabled_types.contains(pii_type) {
+            return None;
+        }
+        let value = text.trim();
+        let digits = value.chars().filter(char::is_ascii_digit).count();
+        let only = |separators: &str| {
+            value
+                .chars()
+                .all(|c| c.is_ascii_digit() || separators.contains(c))
+        };
+        let shaped = match pii_type {
+            "ssn" => digits == 9 && only("- "),
+            "phone" => (10..=15).contains(&digits) && only("+-(). "),
+            "credit_card" => only("- ") && self.validate_luhn(value),
+            _ => false,
+        };
+        let low_confidence = FORCED_CONFIDENCE <= self.config.confidence_threshold_for(pii_type);
+        if !shaped || (low_confidence && !self.config.report_low_confidence) {
+            return None;
+        }
+
+        let start = text.len() - text.trim_start().len();
+        let normalized = self.normalized_value(pii_type, value);
+        Some(PIIDetectionResult {
+            field_name: context.name.clone(),
+            pii_type: pii_type.to_string(),
+            confidence: FORCED_CONFIDENCE,
+            sample: value.to_string(),
+            masked: self.mask_value(normalized.as_deref().unwrap_or(value), pii_type),
+            start,
+            end: start + value.len(),
+            snippet: None,
+            explanation: self.config.explain_confidence.then(|| {
+                ConfidenceExplanation::new(ConfidenceSignal::FieldHint {
+                    field: context.name.clone(),
+                    confidence: FORCED_CONFIDENCE,
+                })
+            }),
+            low_confidence,
+            normalized,
+        })
+    }
+}
+
+/// The PII type a field name suggests, from the words in it: `ssn`,
+/// `billing_phone`, `contactEmail` and `CELL-2` all count.
+fn field_hint(name: &str) -> Option<&'static str> {
+    field_words(name).iter().find_map(|word| {
+        FIELD_HINTS
+            .iter()
+            .find(|(hint, _)| hint == word)
+            .map(|&(_, pii_type)| pii_type)
+    })
+}
+
+/// Lowercase words of a snake, kebab, camel or Pascal case name, with
+/// digits split off (`phone2` is `phone`, `2`).
+fn field_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            words.push(std::mem::take(&mut word));
+            previous = None;
+            continue;
+        }
+        let boundary = previous.is_some_and(|p| {
+            (p.is_lowercase() && c.is_uppercase()) || p.is_numeric() != c.is_numeric()
+        });
+        if boundary {
+            words.push(std::mem::take(&mut word));
+        }
+        previous = Some(c);
+        word.extend(c.to_lowercase());
+    }
+    words.push(word);
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_column_name_tells_ssn_from_order_id() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+
+        let ssn = engine
+            .detect_pii_with_context(" 123456789", &FieldContext::named("SSN"))
+            .unwrap();
+        assert_eq!(ssn.len(), 1);
+        assert_eq!(ssn[0].pii_type, "ssn");
+        assert_eq!(ssn[0].field_name, "SSN");
+        assert_eq!((ssn[0].start, ssn[0].end), (1, 10));
+
+        let order = engine
+            .detect_pii_with_context("123456789", &FieldContext::named("order_id"))
+            .unwrap();
+        assert!(order.is_empty());
+        let order = engine
+            .detect_pii_with_context("5551234567", &FieldContext::named("order_id"))
+            .unwrap();
+        assert!(order.is_empty());
+        let phone = engine
+            .detect_pii_with_context("5551234567", &FieldContext::named("contact"))
+            .unwrap();
+        assert_eq!(phone[0].pii_type, "phone");
+    }
+
+    #[test]
+    fn test_explicit_type_boosts_and_masks() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let context = FieldContext::named("col_7").with_pii_type("ssn");
+
+        let result = engine
+            .mask_text_with_context("123 45 6789", &context)
+            .unwrap();
+        assert_eq!(result.masked_text, "***-**-6789");
+
+        let findings = engine
+            .detect_pii_with_context("123-45-6789", &context)
+            .unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].confidence, HINTED_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_field_hints_split_names() {
+        assert_eq!(field_hint("ssn"), Some("ssn"));
+        assert_eq!(field_hint("contactEmail"), Some("email"));
+        assert_eq!(field_hint("CELL-2"), Some("phone"));
+        assert_eq!(field_hint("HomePhone2"), Some("phone"));
+        assert_eq!(field_hint("order_id"), None);
+        assert_eq!(field_hint("cellar"), None);
+    }
+}
@@ -0,0 +1,144 @@
+use aho_corasick::AhoCorasick;
+
+use crate::{DataCloakConfig, DataCloakEngine, DataCloakError, PiiMatch, Visit};
+
+/// PII type of denylisted terms.
+const DENYLIST_TYPE: &str = "custom";
+
+/// Denylisted terms are certain by definition.
+const DENYLIST_CONFIDENCE: f64 = 1.0;
+
+/// Compiled `denylist`.
+#[derive(Debug, Default)]
+pub(crate) struct Denylist {
+    terms: Option<AhoCorasick>,
+}
+
+impl Denylist {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Result<Self, DataCloakError> {
+        if config.denylist.is_empty() {
+            return Ok(Self::default());
+        }
+        let terms = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .build(config.denylist.iter().map(|term| term.trim()))
+            .map_err(|e| DataCloakError::Internal(format!("Failed to build denylist: {}", e)))?;
+        Ok(Self { terms: Some(terms) })
+    }
+
+    /// Whole-word occurrences of the terms in `text` as `(start, end)`,
+    /// ordered by position. Of overlapping terms the longest wins.
+    fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        let Some(terms) = &self.terms else {
+            return Vec::new();
+        };
+        let mut candidates: Vec<_> = terms
+            .find_overlapping_iter(text)
+            .map(|m| (m.start(), m.end()))
+            .filter(|&(start, end)| is_whole_word(text, start, end))
+            .collect();
+        candidates.sort_by_key(|&(start, end)| (start, std::cmp::Reverse(end)));
+
+        let mut found: Vec<(usize, usize)> = Vec::new();
+        for (start, end) in candidates {
+            if found.last().is_none_or(|&(_, last_end)| start >= last_end) {
+                found.push((start, end));
+            }
+        }
+        found
+    }
+}
+
+impl DataCloakEngine {
+    /// Hands the denylisted terms in `text` to `visit`, as `for_each_match`
+    /// does for pattern matches. They are reported whatever the enabled
+    /// types and thresholds.
+    pub(crate) fn for_each_denied_term<'a>(
+        &'a self,
+        text: &'a str,
+        visit: &mut impl FnMut(PiiMatch<'a>) -> Visit,
+    ) {
+        for (start, end) in self.denylist.find(text) {
+            let found = PiiMatch {
+                pii_type: DENYLIST_TYPE,
+                sample: &text[start..end],
+                confidence: DENYLIST_CONFIDENCE,
+                start,
+                end,
+                low_confidence: false,
+            };
+            match visit(found) {
+                Visit::Continue => {}
+                Visit::NextType | Visit::Stop => break,
+            }
+        }
+    }
+
+    /// Whether `found` came from the denylist rather than a pattern.
+    pub(crate) fn is_denied_term(&self, found: &PiiMatch) -> bool {
+        found.pii_type == DENYLIST_TYPE
+            && !self.config.custom_patterns.contains_key(DENYLIST_TYPE)
+            && self.denylist.find(found.sample) == [(0, found.sample.len())]
+    }
+}
+
+/// Parses a wordlist with one term per line. Blank lines and lines
+/// starting with `#` are skipped.
+pub fn parse_term_list(list: &str) -> Vec<String> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether `text[start..end]` is not joined to letters or digits on either
+/// side. Edges that aren't alphanumeric themselves, like the `+` of `C++`,
+/// need no boundary.
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let term = &text[start..end];
+    let joined = |edge: Option<char>, outside: Option<char>| {
+        edge.is_some_and(char::is_alphanumeric) && outside.is_some_and(char::is_alphanumeric)
+    };
+    !joined(term.chars().next(), text[..start].chars().next_back())
+        && !joined(term.chars().next_back(), text[end..].chars().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denylisted_terms_are_always_masked() {
+        let config = DataCloakConfig::builder()
+            .denylist(["Project Falcon", "Falcon", "J. Doe"])
+            .enabled_types(["email"])
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine
+            .mask_text("Project Falcon ships; falcons fly; ask j. doe or FALCON")
+            .unwrap();
+
+        let found: Vec<_> = result
+            .detected_pii
+            .iter()
+            .map(|pii| (pii.pii_type.as_str(), pii.sample.as_str(), pii.confidence))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("custom", "Project Falcon", 1.0),
+                ("custom", "j. doe", 1.0),
+                ("custom", "FALCON", 1.0),
+            ]
+        );
+        assert_eq!(result.masked_text, "*** ships; falcons fly; ask *** or ***");
+    }
+
+    #[test]
+    fn test_term_lists_skip_comments() {
+        let terms = parse_term_list("# codenames\nFalcon\n\n  Osprey  \n");
+        assert_eq!(terms, ["Falcon", "Osprey"]);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DataCloakError, MaskingMetadata, MaskingResult, PIIDetectionResult};
+
+/// A structured text document with its values masked and the markup around
+/// them re-serialized unchanged.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DocumentMaskingResult {
+    pub masked: String,
+    /// Findings with `field_name` set to the location of their value and
+    /// offsets relative to that value.
+    pub detected_pii: Vec<PIIDetectionResult>,
+    /// Totals over all values; `fields_processed` counts the values masked.
+    pub metadata: MaskingMetadata,
+    pub token_map: HashMap<String, String>,
+}
+
+/// Called by markup rewriters with the location and unescaped content of
+/// each value; returns the replacement content.
+pub(crate) type ValueVisitor<'v> = dyn FnMut(&str, &str) -> Result<String, DataCloakError> + 'v;
+
+/// Folds the `MaskingResult` of each value in a document into document-wide
+/// findings, metadata and token map.
+pub(crate) struct FieldTotals {
+    started: Instant,
+    pub(crate) detected_pii: Vec<PIIDetectionResult>,
+    pub(crate) metadata: MaskingMetadata,
+    pub(crate) token_map: HashMap<String, String>,
+}
+
+impl FieldTotals {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            detected_pii: Vec::new(),
+            metadata: MaskingMetadata::default(),
+            token_map: HashMap::new(),
+        }
+    }
+
+    /// Records `result` as the value at `field` and returns its masked text.
+    pub(crate) fn add(&mut self, field: &str, result: MaskingResult) -> String {
+        let value = result.metadata;
+        let metadata = &mut self.metadata;
+        metadata.fields_processed += 1;
+        metadata.pii_items_found += value.pii_items_found;
+        for (pii_type, count) in value.counts_by_type {
+            *metadata.counts_by_type.entry(pii_type).or_insert(0) += count;
+        }
+        metadata.bytes_processed += value.bytes_processed;
+        metadata.failed_validation += value.failed_validation;
+        metadata.suppressed_by_validation += value.suppressed_by_validation;
+        metadata.allowlisted += value.allowlisted;
+        metadata.already_masked += value.already_masked;
+        metadata.detection_time_us += value.detection_time_us;
+        metadata.masking_time_us += value.masking_time_us;
+        metadata.limits_exceeded |= value.limits_exceeded;
+        metadata.warnings.extend(
+            value
+                .warnings
+                .into_iter()
+                .map(|w| format!("{}: {}", field, w)),
+        );
+
+        self.token_map.extend(result.token_map);
+        self.detected_pii
+            .extend(result.detected_pii.into_iter().map(|mut pii| {
+                pii.field_name = field.to_string();
+                pii
+            }));
+        result.masked_text
+    }
+
+    pub(crate) fn finish(mut self) -> Self {
+        self.metadata.processing_time = self.started.elapsed().as_millis() as u64;
+        self
+    }
+
+    pub(crate) fn into_document(self, masked: String) -> DocumentMaskingResult {
+        let totals = self.finish();
+        DocumentMaskingResult {
+            masked,
+            detected_pii: totals.detected_pii,
+            metadata: totals.metadata,
+            token_map: totals.token_map,
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::base64::{decode_base64, encode_base64};
+use crate::document::FieldTotals;
+use crate::html::rewrite_html;
+use crate::{hex, DataCloakEngine, DataCloakError, DocumentMaskingResult};
+
+/// What `mask_eml` does with attachments: parts inside a multipart message
+/// that are not plain text or HTML, or that are marked as attachments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentPolicy {
+    /// Copy attachments through untouched.
+    #[default]
+    Keep,
+    /// Remove attachment parts from the message.
+    Strip,
+    /// Replace each attachment with a text part holding the SHA-256 of its
+    /// content, so identical files can still be matched up.
+    Hash,
+}
+
+/// Top-level headers whose values are masked.
+const MASKED_HEADERS: &[&str] = &[
+    "from",
+    "to",
+    "cc",
+    "bcc",
+    "reply-to",
+    "sender",
+    "return-path",
+    "delivered-to",
+    "subject",
+];
+
+/// Base64 line length required by RFC 2045.
+const BASE64_LINE: usize = 76;
+
+impl DataCloakEngine {
+    /// Masks an RFC 5322 message such as a `.eml` file: the address and
+    /// subject headers, and every text and HTML part, decoded from base64 or
+    /// quoted-printable and re-encoded the same way. Multipart structure,
+    /// boundaries and all other headers are kept, so the result is still a
+    /// valid message. Findings are labelled with the header name, or with
+    /// the part's section such as `body/2/1`. Encoded-word headers and
+    /// parts in charsets other than UTF-8 are left as they are, with a
+    /// warning in the metadata.
+    pub fn mask_eml(
+        &self,
+        message: &str,
+        attachments: AttachmentPolicy,
+    ) -> Result<DocumentMaskingResult, DataCloakError> {
+        let mut rewriter = EmlRewriter {
+            engine: self,
+            attachments,
+            totals: FieldTotals::new(),
+        };
+        let masked = rewriter
+            .rewrite_entity(message, "body", Some(""))?
+            .unwrap_or_default();
+        Ok(rewriter.totals.into_document(masked))
+    }
+}
+
+struct EmlRewriter<'e> {
+    engine: &'e DataCloakEngine,
+    attachments: AttachmentPolicy,
+    totals: FieldTotals,
+}
+
+/// One header, with its folded continuation lines and line ending.
+struct Header<'a> {
+    raw: &'a str,
+    name: &'a str,
+}
+
+impl Header<'_> {
+    fn value(&self) -> String {
+        let value = &self.raw[self.name.len() + 1..];
+        value
+            .split(['\r', '\n'])
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl EmlRewriter<'_> {
+    /// Rewrites one MIME entity. `header_prefix` is set for a message, whose
+    /// address headers are masked with findings labelled by that prefix.
+    /// Returns `None` when the entity is an attachment being stripped.
+    fn rewrite_entity(
+        &mut self,
+        entity: &str,
+        section: &str,
+        header_prefix: Option<&str>,
+    ) -> Result<Option<String>, DataCloakError> {
+        let (header_block, separator, body) = split_entity(entity);
+        let headers = parse_headers(header_block);
+        let find = |name: &str| {
+            headers
+                .iter()
+                .find(|h| h.name.trim().eq_ignore_ascii_case(name))
+                .map(Header::value)
+        };
+        let content_type = find("content-type").unwrap_or_default();
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let mime = if mime.is_empty() {
+            "text/plain".to_string()
+        } else {
+            mime
+        };
+        let encoding = find("content-transfer-encoding")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let attachment = find("content-disposition")
+            .is_some_and(|d| d.to_ascii_lowercase().starts_with("attachment"));
+        let newline = if entity.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+
+        let is_text = !attachment && (mime == "text/plain" || mime == "text/html");
+        let is_root = section == "body";
+        if !is_text && !mime.starts_with("multipart/") && mime != "message/rfc822" && !is_root {
+            return Ok(match self.attachments {
+                AttachmentPolicy::Keep => Some(entity.to_string()),
+                AttachmentPolicy::Strip => None,
+                AttachmentPolicy::Hash => Some(hashed_attachment(body, &encoding, newline)),
+            });
+        }
+
+        let mut out = String::with_capacity(entity.len());
+        for header in &headers {
+            match header_prefix {
+                Some(prefix)
+                    if MASKED_HEADERS
+                        .contains(&header.name.trim().to_ascii_lowercase().as_str()) =>
+                {
+                    let value = &header.raw[header.name.len() + 1..];
+                    let ending = value.len() - value.trim_end_matches(['\r', '\n']).len();
+                    let (value, ending) = value.split_at(value.len() - ending);
+                    if value.contains("=?") {
+                        self.totals.metadata.warnings.push(format!(
+                            "{}{}: encoded words left unmasked",
+                            prefix,
+                            header.name.trim()
+                        ));
+                    }
+                    let label = format!("{}{}", prefix, header.name.trim());
+                    let masked = self.totals.add(&label, self.engine.mask_text(value)?);
+                    out.push_str(&header.raw[..header.name.len() + 1]);
+                    out.push_str(&masked);
+                    out.push_str(ending);
+                }
+                _ => out.push_str(header.raw),
+            }
+        }
+        out.push_str(separator);
+
+        if let Some(boundary) = mime
+            .starts_with("multipart/")
+            .then(|| parameter(&content_type, "boundary"))
+            .flatten()
+        {
+            out.push_str(&self.rewrite_multipart(body, &boundary, section)?);
+        } else if mime == "message/rfc822"
+            && !matches!(encoding.as_str(), "base64" | "quoted-printable")
+        {
+            let prefix = format!("{}/", section);
+            let message = self.rewrite_entity(body, section, Some(&prefix))?;
+            out.push_str(&message.unwrap_or_default());
+        } else if is_text {
+            out.push_str(&self.rewrite_text(body, section, &mime, &encoding, newline)?);
+        } else {
+            out.push_str(body);
+        }
+        Ok(Some(out))
+    }
+
+    fn rewrite_multipart(
+        &mut self,
+        body: &str,
+        boundary: &str,
+        section: &str,
+    ) -> Result<String, DataCloakError> {
+        let delimiter = format!("--{}", boundary);
+        let mut out = String::with_capacity(body.len());
+        let mut part_start: Option<usize> = None;
+        // Where the delimiter opening the current part was written.
+        let mut opened = 0;
+        let mut part = 0;
+        let mut offset = 0;
+
+        for line in body.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let Some(rest) = line.trim_end().strip_prefix(&delimiter) else {
+                if part_start.is_none() {
+                    out.push_str(line);
+                }
+                continue;
+            };
+            let closing = rest == "--";
+            if !closing && !rest.is_empty() {
+                if part_start.is_none() {
+                    out.push_str(line);
+                }
+                continue;
+            }
+
+            if let Some(start) = part_start.take() {
+                part += 1;
+                let entity = &body[start..line_start];
+                let child = format!("{}/{}", section, part);
+                match self.rewrite_entity(entity, &child, None)? {
+                    Some(masked) => out.push_str(&masked),
+                    // Drop a stripped part with the delimiter that opened it
+                    None => out.truncate(opened),
+                }
+            }
+            opened = out.len();
+            out.push_str(line);
+            if closing {
+                out.push_str(&body[offset..]);
+                return Ok(out);
+            }
+            part_start = Some(offset);
+        }
+
+        if let Some(start) = part_start {
+            let child = format!("{}/{}", section, part + 1);
+            if let Some(masked) = self.rewrite_entity(&body[start..], &child, None)? {
+                out.push_str(&masked);
+            }
+        }
+        Ok(out)
+    }
+
+    fn rewrite_text(
+        &mut self,
+        body: &str,
+        section: &str,
+        mime: &str,
+        encoding: &str,
+        newline: &str,
+    ) -> Result<String, DataCloakError> {
+        // The line break before a boundary belongs to the boundary, so keep
+        // it out of the re-encoded content.
+        let content = body.trim_end_matches(['\r', '\n']);
+        let ending = &body[content.len()..];
+        let decoded = match encoding {
+            "base64" => decode_base64(content).ok(),
+            "quoted-printable" => Some(decode_quoted_printable(content)),
+            _ => Some(content.as_bytes().to_vec()),
+        };
+        let Some(text) = decoded.and_then(|bytes| String::from_utf8(bytes).ok()) else {
+            self.totals
+                .metadata
+                .warnings
+                .push(format!("{}: undecodable text part left unmasked", section));
+            return Ok(body.to_string());
+        };
+
+        let masked = if mime == "text/html" {
+            let (engine, totals) = (self.engine, &mut self.totals);
+            rewrite_html(&text, &mut |path, value| {
+                let label = format!("{}{}", section, path);
+                Ok(totals.add(&label, engine.mask_text(value)?))
+            })?
+        } else {
+            self.totals.add(section, self.engine.mask_text(&text)?)
+        };
+        if masked == text {
+            return Ok(body.to_string());
+        }
+
+        let encoded = match encoding {
+            "base64" => wrap(&encode_base64(masked.as_bytes()), newline),
+            "quoted-printable" => encode_quoted_printable(&masked, newline),
+            _ => masked,
+        };
+        Ok(encoded + ending)
+    }
+}
+
+/// Headers, the blank line ending them, and the body of a MIME entity.
+fn split_entity(entity: &str) -> (&str, &str, &str) {
+    let mut offset = 0;
+    for line in entity.split_inclusive('\n') {
+        if line == "\n" || line == "\r\n" {
+            let body = offset + line.len();
+            return (&entity[..offset], &entity[offset..body], &entity[body..]);
+        }
+        offset += line.len();
+    }
+    (entity, "", "")
+}
+
+fn parse_headers(block: &str) -> Vec<Header<'_>> {
+    let mut headers: Vec<Header<'_>> = Vec::new();
+    let mut start = 0;
+    for line in block.split_inclusive('\n') {
+        let end = start + line.len();
+        match headers.last_mut() {
+            Some(last) if line.starts_with([' ', '\t']) => {
+                last.raw = &block[start - last.raw.len()..end];
+            }
+            _ => {
+                if let Some(colon) = line.find(':') {
+                    headers.push(Header {
+                        raw: &block[start..end],
+                        name: &line[..colon],
+                    });
+                }
+            }
+        }
+        start = end;
+    }
+    headers
+}
+
+/// A `name=value` parameter of a structured header such as Content-Type.
+fn parameter(header: &str, name: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn hashed_attachment(body: &str, encoding: &str, newline: &str) -> String {
+    let content = match encoding {
+        "base64" => decode_base64(body).unwrap_or_else(|_| body.as_bytes().to_vec()),
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.as_bytes().to_vec(),
+    };
+    let digest = Sha256::digest(&content);
+    format!(
+        "Content-Type: text/plain; charset=us-ascii{nl}\
+         Content-Disposition: attachment; filename=\"attachment.sha256\"{nl}\
+         {nl}sha256:{}{nl}",
+        hex::encode_hex(&digest),
+        nl = newline
+    )
+}
+
+fn wrap(encoded: &str, newline: &str) -> String {
+    encoded
+        .as_bytes()
+        .chunks(BASE64_LINE)
+        .map(|line| std::str::from_utf8(line).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(newline)
+}
+
+fn decode_quoted_printable(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'=' {
+            out.push(bytes[i]);
+            i += 1;
+        } else if bytes[i + 1..].starts_with(b"\r\n") {
+            i += 3;
+        } else if bytes[i + 1..].starts_with(b"\n") {
+            i += 2;
+        } else if let Some(byte) = text
+            .get(i + 1..i + 3)
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(b'=');
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Quoted-printable with soft line breaks keeping lines within 76 bytes.
+fn encode_quoted_printable(text: &str, newline: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            out.push_str(newline);
+        }
+        let line = line.strip_suffix('\r').unwrap_or(line).as_bytes();
+        let mut width = 0;
+        for (i, &b) in line.iter().enumerate() {
+            let literal = (b'!'..=b'~').contains(&b) && b != b'='
+                || (b == b' ' || b == b'\t') && i + 1 < line.len();
+            let encoded = if literal {
+                (b as char).to_string()
+            } else {
+                format!("={:02X}", b)
+            };
+            if width + encoded.len() > 75 {
+                out.push('=');
+                out.push_str(newline);
+                width = 0;
+            }
+            out.push_str(&encoded);
+            width += encoded.len();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    const MESSAGE: &str = "From: Jo <jo@example.com>\r\n\
+        To: ann@example.com,\r\n bob@example.com\r\n\
+        Subject: hello\r\n\
+        Content-Type: multipart/mixed; boundary=\"b1\"\r\n\
+        \r\n\
+        preamble\r\n\
+        --b1\r\n\
+        Content-Type: text/plain; charset=utf-8\r\n\
+        Content-Transfer-Encoding: quoted-printable\r\n\
+        \r\n\
+        Call me, SSN 123-45-6789 =E2=80=94 jo@example.com\r\n\
+        --b1\r\n\
+        Content-Type: text/html\r\n\
+        Content-Transfer-Encoding: base64\r\n\
+        \r\n\
+        PHA+am9AZXhhbXBsZS5jb208L3A+\r\n\
+        --b1\r\n\
+        Content-Type: application/pdf\r\n\
+        Content-Disposition: attachment; filename=\"cv.pdf\"\r\n\
+        Content-Transfer-Encoding: base64\r\n\
+        \r\n\
+        JVBERi0=\r\n\
+        --b1--\r\n";
+
+    fn engine() -> DataCloakEngine {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        DataCloakEngine::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_mask_eml_keeps_mime_structure() {
+        let result = engine().mask_eml(MESSAGE, AttachmentPolicy::Keep).unwrap();
+        let masked = &result.masked;
+        assert!(masked.starts_with(
+            "From: Jo <[EMAIL_1]>\r\nTo: [EMAIL_2],\r\n [EMAIL_3]\r\nSubject: hello\r\n"
+        ));
+        assert!(masked.contains("\r\n\r\npreamble\r\n--b1\r\n"));
+        assert!(masked.contains("\r\n\r\nCall me, SSN [SSN_1] =E2=80=94 [EMAIL_1]\r\n--b1\r\n"));
+        assert!(masked.contains(&format!(
+            "\r\n\r\n{}\r\n--b1\r\n",
+            encode_base64(b"<p>[EMAIL_1]</p>")
+        )));
+        assert!(masked.contains("\r\n\r\nJVBERi0=\r\n--b1--\r\n"));
+
+        let fields: Vec<&str> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.field_name.as_str())
+            .collect();
+        assert_eq!(fields, ["From", "To", "To", "body/1", "body/1", "body/2/p"]);
+    }
+
+    #[test]
+    fn test_mask_eml_strips_or_hashes_attachments() {
+        let stripped = engine().mask_eml(MESSAGE, AttachmentPolicy::Strip).unwrap();
+        assert!(!stripped.masked.contains("cv.pdf"));
+        assert!(stripped.masked.ends_with("\r\n--b1--\r\n"));
+        assert_eq!(stripped.masked.matches("--b1\r\n").count(), 2);
+
+        let hashed = engine().mask_eml(MESSAGE, AttachmentPolicy::Hash).unwrap();
+        let digest = hex::encode_hex(&Sha256::digest(b"%PDF-"));
+        assert!(hashed
+            .masked
+            .contains(&format!("\r\n\r\nsha256:{}\r\n--b1--\r\n", digest)));
+        assert!(!hashed.masked.contains("JVBERi0="));
+    }
+}
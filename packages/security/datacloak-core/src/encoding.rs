@@ -0,0 +1,195 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+/// Character encodings `decode_text` recognizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+/// Decodes raw input in the encoding it appears to use: the one its byte
+/// order mark names, UTF-16 if every other byte is mostly zero, UTF-8 if
+/// valid and Latin-1 otherwise. Never fails; malformed sequences become
+/// U+FFFD.
+///
+/// Latin-1 maps every byte to a character, so spreadsheet exports in legacy
+/// Western encodings keep their ASCII PII intact instead of failing outright.
+pub fn decode_text(bytes: &[u8]) -> (Cow<'_, str>, TextEncoding) {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return (String::from_utf8_lossy(rest), TextEncoding::Utf8);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return (
+            decode_utf16(rest, u16::from_le_bytes),
+            TextEncoding::Utf16Le,
+        );
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return (
+            decode_utf16(rest, u16::from_be_bytes),
+            TextEncoding::Utf16Be,
+        );
+    }
+    if let Some(encoding) = sniff_utf16(bytes) {
+        let unit = match encoding {
+            TextEncoding::Utf16Be => u16::from_be_bytes,
+            _ => u16::from_le_bytes,
+        };
+        return (decode_utf16(bytes, unit), encoding);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (Cow::Borrowed(text), TextEncoding::Utf8),
+        Err(_) => (
+            Cow::Owned(bytes.iter().map(|&b| char::from(b)).collect()),
+            TextEncoding::Latin1,
+        ),
+    }
+}
+
+/// Decodes raw input as UTF-8 when valid and as Latin-1 otherwise, after
+/// checking for UTF-16 as `decode_text` does.
+pub(crate) fn decode_bytes(bytes: &[u8]) -> Cow<'_, str> {
+    decode_text(bytes).0
+}
+
+/// UTF-16 without a byte order mark, recognized by the zero high bytes of
+/// mostly-ASCII text: at least half the code units have one zero and one
+/// non-zero byte, on the same side.
+fn sniff_utf16(bytes: &[u8]) -> Option<TextEncoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = bytes.len() / 2;
+    let (mut little, mut big) = (0, 0);
+    for pair in bytes.chunks_exact(2) {
+        match (pair[0], pair[1]) {
+            (0, 0) => {}
+            (_, 0) => little += 1,
+            (0, _) => big += 1,
+            _ => {}
+        }
+    }
+    if little * 2 >= units && big == 0 {
+        Some(TextEncoding::Utf16Le)
+    } else if big * 2 >= units && little == 0 {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Cow<'static, str> {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    let mut text: String = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    // A trailing odd byte is half a code unit
+    if !bytes.len().is_multiple_of(2) {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    Cow::Owned(text)
+}
+
+/// Converts byte offsets into `text` to UTF-16 code unit offsets, for hosts
+/// such as .NET and JavaScript whose strings are indexed that way. Offsets
+/// must fall on char boundaries; they may be given in any order.
+pub fn utf16_offsets(text: &str, byte_offsets: &[usize]) -> Vec<usize> {
+    convert_offsets(text, byte_offsets, char::len_utf16)
+}
+
+/// Converts byte offsets into `text` to char (code point) offsets, for hosts
+/// such as Python whose strings are indexed that way. Offsets must fall on
+/// char boundaries; they may be given in any order.
+pub fn char_offsets(text: &str, byte_offsets: &[usize]) -> Vec<usize> {
+    convert_offsets(text, byte_offsets, |_| 1)
+}
+
+/// Byte offsets into `text` counted in the units `width` gives each char.
+fn convert_offsets(text: &str, byte_offsets: &[usize], width: fn(char) -> usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..byte_offsets.len()).collect();
+    order.sort_by_key(|&i| byte_offsets[i]);
+
+    let mut units = vec![0; byte_offsets.len()];
+    let mut chars = text.chars();
+    let (mut byte, mut unit) = (0, 0);
+    for i in order {
+        while byte < byte_offsets[i] {
+            let c = chars.next().expect("offset lies within the text");
+            byte += c.len_utf8();
+            unit += width(c);
+        }
+        units[i] = unit;
+    }
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_bytes_falls_back_to_latin1() {
+        assert!(matches!(
+            decode_bytes("caf\u{e9}".as_bytes()),
+            Cow::Borrowed("caf\u{e9}")
+        ));
+        assert_eq!(
+            decode_bytes(b"Jos\xe9 <jose@example.com>"),
+            "Jos\u{e9} <jose@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_decode_text_sniffs_utf16_and_boms() {
+        let utf16le: Vec<u8> = "Mail jo@example.com"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let utf16be: Vec<u8> = "\u{feff}Jos\u{e9}"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        assert_eq!(
+            decode_text(&utf16le),
+            (Cow::Borrowed("Mail jo@example.com"), TextEncoding::Utf16Le)
+        );
+        assert_eq!(
+            decode_text(&utf16be),
+            (Cow::Borrowed("Jos\u{e9}"), TextEncoding::Utf16Be)
+        );
+        assert_eq!(
+            decode_text(b"\xEF\xBB\xBFcaf\xC3\xA9 \xFF"),
+            (Cow::Borrowed("caf\u{e9} \u{fffd}"), TextEncoding::Utf8)
+        );
+        assert_eq!(decode_text(b"Jos\xe9").1, TextEncoding::Latin1);
+    }
+
+    #[test]
+    fn test_detect_pii_bytes_reads_legacy_exports() {
+        use crate::{DataCloakConfig, DataCloakEngine};
+
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let utf16: Vec<u8> = "\u{feff}Contact: jose@example.com"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        for bytes in [&utf16[..], b"Jos\xe9 <jose@example.com>"] {
+            let found = engine.detect_pii_bytes(bytes).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].sample, "jose@example.com");
+        }
+    }
+
+    #[test]
+    fn test_utf16_offsets() {
+        // "é" is 2 bytes / 1 unit, "😀" is 4 bytes / 2 units
+        let text = "é😀 a@b.co";
+        assert_eq!(utf16_offsets(text, &[13, 6, 0]), vec![10, 3, 0]);
+        assert_eq!(char_offsets(text, &[13, 6, 0]), vec![9, 2, 0]);
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+/// Errors returned by the DataCloak engine and its supporting subsystems.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataCloakError {
+    /// A detection pattern failed to compile.
+    PatternCompile { name: String, message: String },
+    /// The input exceeds `DataCloakConfig::max_text_length`.
+    TextTooLarge { length: usize, max: usize },
+    /// A scan ran longer than the configured timeout.
+    Timeout { elapsed_ms: u64 },
+    /// The configuration is inconsistent or incomplete.
+    InvalidConfig(String),
+    /// A token vault could not store or resolve a value.
+    Vault(String),
+    /// An encryption or decryption step failed.
+    Crypto(String),
+    /// An imported mapping conflicts with existing assignments.
+    MappingConflict { token: String },
+    /// Reading a file or stream failed.
+    Io(String),
+    /// A caller passed a null pointer or non-UTF-8 text across the FFI.
+    InvalidArgument(String),
+    /// The scan was stopped through its `CancellationToken`.
+    Cancelled,
+    /// A detection model failed to load or run.
+    Model(String),
+    /// Internal invariant violated, e.g. a poisoned lock.
+    Internal(String),
+}
+
+impl DataCloakError {
+    /// Stable numeric code for each variant, used by the C FFI. Existing
+    /// codes never change meaning; new variants get new numbers.
+    pub fn code(&self) -> i32 {
+        match self {
+            DataCloakError::PatternCompile { .. } => 1,
+            DataCloakError::TextTooLarge { .. } => 2,
+            DataCloakError::Timeout { .. } => 3,
+            DataCloakError::InvalidConfig(_) => 4,
+            DataCloakError::Vault(_) => 5,
+            DataCloakError::Crypto(_) => 6,
+            DataCloakError::MappingConflict { .. } => 7,
+            DataCloakError::Io(_) => 8,
+            DataCloakError::InvalidArgument(_) => 9,
+            DataCloakError::Cancelled => 10,
+            DataCloakError::Model(_) => 11,
+            DataCloakError::Internal(_) => 99,
+        }
+    }
+
+    pub(crate) fn poisoned(what: &str) -> Self {
+        DataCloakError::Internal(format!("{} lock poisoned", what))
+    }
+}
+
+impl fmt::Display for DataCloakError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataCloakError::PatternCompile { name, message } => {
+                write!(f, "Failed to compile {} regex: {}", name, message)
+            }
+            DataCloakError::TextTooLarge { length, max } => {
+                write!(f, "Text length ({}) exceeds maximum ({})", length, max)
+            }
+            DataCloakError::Timeout { elapsed_ms } => {
+                write!(f, "Scan timed out after {} ms", elapsed_ms)
+            }
+            DataCloakError::InvalidConfig(message) => {
+                write!(f, "Invalid configuration: {}", message)
+            }
+            DataCloakError::Vault(message) => write!(f, "Token vault error: {}", message),
+            DataCloakError::Crypto(message) => write!(f, "Encryption error: {}", message),
+            DataCloakError::MappingConflict { token } => {
+                write!(f, "Mapping conflict for token {}", token)
+            }
+            DataCloakError::Io(message) => write!(f, "I/O error: {}", message),
+            DataCloakError::InvalidArgument(message) => write!(f, "Invalid argument: {}", message),
+            DataCloakError::Cancelled => write!(f, "Scan cancelled"),
+            DataCloakError::Model(message) => write!(f, "Model error: {}", message),
+            DataCloakError::Internal(message) => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for DataCloakError {}
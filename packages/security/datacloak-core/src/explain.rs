@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::keywords::KeywordEffect;
+use crate::{
+    DataCloakEngine, PIIDetectionResult, PiiMatch, ScoreCandidate, FAILED_VALIDATION_FACTOR,
+    PATTERN_CONFIDENCE,
+};
+
+/// Why a finding has its confidence: the signals that set it, in the order
+/// they were applied.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceExplanation {
+    pub signals: Vec<ConfidenceSignal>,
+}
+
+/// One contribution to a finding's confidence. `confidence` is the score
+/// after the signal was applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "signal", rename_all = "snake_case")]
+pub enum ConfidenceSignal {
+    /// The pattern of the finding's type matched.
+    PatternMatch { confidence: f64 },
+    /// The value passed or failed email or Luhn validation.
+    Checksum { passed: bool, confidence: f64 },
+    /// A boost or penalty keyword was found near the match.
+    ContextKeyword {
+        keyword: String,
+        boost: bool,
+        confidence: f64,
+    },
+    /// The field's name or configured type expects the finding's type.
+    FieldHint { field: String, confidence: f64 },
+    /// The finding is the whole value of a field named for identifiers.
+    IdentifierField { field: String, confidence: f64 },
+    /// A NER model found the entity with this probability.
+    EntityModel { confidence: f64 },
+    /// The value is in the configured name lists.
+    NameDictionary { confidence: f64 },
+    /// The value is a listed organization or ends in a legal form.
+    OrganizationName { confidence: f64 },
+    /// The value is a `denylist` term.
+    Denylist { confidence: f64 },
+    /// The value is an email with `@` or dots spelled out.
+    ObfuscatedEmail { confidence: f64 },
+    /// A `ConfidenceScorer` registered on the engine set the score.
+    CustomScorer { confidence: f64 },
+    /// A `json_mask_paths` rule covers the value at `path`.
+    MaskPath { path: String, confidence: f64 },
+}
+
+impl ConfidenceExplanation {
+    pub(crate) fn new(signal: ConfidenceSignal) -> Self {
+        Self {
+            signals: vec![signal],
+        }
+    }
+}
+
+impl DataCloakEngine {
+    /// Replays the scoring of `found` in `text` as signals.
+    pub(crate) fn explain_match(&self, text: &str, found: &PiiMatch) -> ConfidenceExplanation {
+        if self.is_denied_term(found) {
+            return ConfidenceExplanation::new(ConfidenceSignal::Denylist {
+                confidence: found.confidence,
+            });
+        }
+        if self.is_obfuscated_email(found) {
+            return ConfidenceExplanation::new(ConfidenceSignal::ObfuscatedEmail {
+                confidence: found.confidence,
+            });
+        }
+        if self.is_dictionary_name(found) {
+            return ConfidenceExplanation::new(ConfidenceSignal::NameDictionary {
+                confidence: found.confidence,
+            });
+        }
+        if self.is_listed_or_suffixed(found) {
+            return ConfidenceExplanation::new(ConfidenceSignal::OrganizationName {
+                confidence: found.confidence,
+            });
+        }
+        #[cfg(feature = "ner")]
+        if self.is_entity(found) {
+            return ConfidenceExplanation::new(ConfidenceSignal::EntityModel {
+                confidence: found.confidence,
+            });
+        }
+
+        let mut confidence = PATTERN_CONFIDENCE;
+        let mut explanation =
+            ConfidenceExplanation::new(ConfidenceSignal::PatternMatch { confidence });
+
+        let validated = self.validate_match(found.pii_type, found.sample);
+        if let Some(passed) = validated {
+            if !passed {
+                confidence *= FAILED_VALIDATION_FACTOR;
+            }
+            explanation
+                .signals
+                .push(ConfidenceSignal::Checksum { passed, confidence });
+        }
+
+        let language = self.keywords.language(text);
+        let keyword = self
+            .keywords
+            .nearby(text, language, found.pii_type, found.start, found.end);
+        if let Some((keyword, effect)) = keyword {
+            confidence = effect.apply(confidence);
+            explanation.signals.push(ConfidenceSignal::ContextKeyword {
+                keyword: keyword.to_string(),
+                boost: effect == KeywordEffect::Boost,
+                confidence,
+            });
+        }
+
+        let candidate = ScoreCandidate {
+            pii_type: found.pii_type,
+            sample: found.sample,
+            text,
+            start: found.start,
+            end: found.end,
+            validated,
+            confidence,
+        };
+        if let Some(confidence) = self.custom_score(&candidate) {
+            explanation
+                .signals
+                .push(ConfidenceSignal::CustomScorer { confidence });
+        }
+        explanation
+    }
+}
+
+impl PIIDetectionResult {
+    /// Records the signal that just set `confidence`, if this finding is
+    /// being explained.
+    pub(crate) fn explain(&mut self, signal: impl FnOnce(f64) -> ConfidenceSignal) {
+        if let Some(explanation) = &mut self.explanation {
+            explanation.signals.push(signal(self.confidence));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, FieldContext};
+
+    #[test]
+    fn test_explanation_lists_each_signal() {
+        let config = DataCloakConfig::builder()
+            .explain_confidence(true)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let findings = engine
+            .detect_pii_with_context(
+                "card 4111 1111 1111 1111",
+                &FieldContext::named("payment_card"),
+            )
+            .unwrap();
+        let card = findings
+            .iter()
+            .find(|pii| pii.pii_type == "credit_card")
+            .unwrap();
+        let signals = &card.explanation.as_ref().unwrap().signals;
+        assert_eq!(
+            signals[..2],
+            [
+                ConfidenceSignal::PatternMatch { confidence: 0.95 },
+                ConfidenceSignal::Checksum {
+                    passed: true,
+                    confidence: 0.95
+                },
+            ]
+        );
+        assert!(matches!(
+            &signals[2],
+            ConfidenceSignal::ContextKeyword { keyword, boost: true, .. } if keyword == "card"
+        ));
+        assert_eq!(
+            signals[3],
+            ConfidenceSignal::FieldHint {
+                field: "payment_card".to_string(),
+                confidence: card.confidence
+            }
+        );
+
+        let json = serde_json::to_string(&signals[0]).unwrap();
+        assert_eq!(json, r#"{"signal":"pattern_match","confidence":0.95}"#);
+    }
+
+    #[test]
+    fn test_explanations_are_off_by_default() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let findings = engine.detect_pii("jane@example.com").unwrap();
+        assert_eq!(findings[0].explanation, None);
+    }
+}
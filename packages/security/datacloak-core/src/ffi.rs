@@ -0,0 +1,1006 @@
+//! C interface used by the Electron workbench and other non-Rust hosts.
+//!
+//! The primary entry points return a status code (`DATACLOAK_OK` or one of
+//! the `DATACLOAK_ERR_*` values, matching `DataCloakError::code`) and write
+//! their result through an out-parameter. The original pointer-returning
+//! functions remain as wrappers that return null on failure.
+//!
+//! Panics never unwind into the host: every entry point catches them and
+//! reports `DATACLOAK_ERR_INTERNAL` instead.
+//!
+//! Failures are also recorded in a thread-local slot, readable through
+//! `datacloak_last_error` and `datacloak_last_error_code` until the next call
+//! on the same thread.
+//!
+//! `include/datacloak.h` is generated from this module by cbindgen during
+//! the build. `DATACLOAK_ABI_VERSION` is bumped whenever a signature or
+//! struct layout changes, so bindings can refuse a library they don't match.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+use crate::encoding::{decode_bytes, utf16_offsets};
+use crate::{
+    CancellationToken, ConfigFormat, DataCloakConfig, DataCloakEngine, DataCloakError, EnginePool,
+    PIIDetectionResult, StreamScanner,
+};
+
+/// Version of the C ABI described by `include/datacloak.h`.
+pub const DATACLOAK_ABI_VERSION: u32 = 1;
+
+pub const DATACLOAK_OK: c_int = 0;
+pub const DATACLOAK_ERR_PATTERN_COMPILE: c_int = 1;
+pub const DATACLOAK_ERR_TEXT_TOO_LARGE: c_int = 2;
+pub const DATACLOAK_ERR_TIMEOUT: c_int = 3;
+pub const DATACLOAK_ERR_INVALID_CONFIG: c_int = 4;
+pub const DATACLOAK_ERR_VAULT: c_int = 5;
+pub const DATACLOAK_ERR_CRYPTO: c_int = 6;
+pub const DATACLOAK_ERR_MAPPING_CONFLICT: c_int = 7;
+pub const DATACLOAK_ERR_IO: c_int = 8;
+pub const DATACLOAK_ERR_INVALID_ARGUMENT: c_int = 9;
+pub const DATACLOAK_ERR_CANCELLED: c_int = 10;
+pub const DATACLOAK_ERR_INTERNAL: c_int = 99;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<DataCloakError>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: DataCloakError) {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(error));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+}
+
+/// Runs `body`, converting a panic into `DataCloakError::Internal`.
+fn catch_panic<R>(body: impl FnOnce() -> Result<R, DataCloakError>) -> Result<R, DataCloakError> {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(DataCloakError::Internal(format!("panic: {}", message)))
+    })
+}
+
+/// Runs an FFI body, writing its result to `out` and returning a status
+/// code. On failure `out` is set to null and the error is recorded.
+fn ffi_status<T>(out: *mut *mut T, body: impl FnOnce() -> Result<*mut T, DataCloakError>) -> c_int {
+    clear_last_error();
+    if out.is_null() {
+        set_last_error(DataCloakError::InvalidArgument(
+            "output pointer is null".to_string(),
+        ));
+        return DATACLOAK_ERR_INVALID_ARGUMENT;
+    }
+
+    unsafe { *out = std::ptr::null_mut() };
+    ffi_try(|| {
+        let ptr = body()?;
+        unsafe { *out = ptr };
+        Ok(())
+    })
+}
+
+/// Runs an FFI body that produces no output and returns its status code.
+fn ffi_try(body: impl FnOnce() -> Result<(), DataCloakError>) -> c_int {
+    clear_last_error();
+    match catch_panic(body) {
+        Ok(()) => DATACLOAK_OK,
+        Err(error) => {
+            let code = error.code();
+            set_last_error(error);
+            code
+        }
+    }
+}
+
+/// Adapts a status-returning entry point to the legacy null-on-failure form.
+fn legacy_call<T>(call: impl FnOnce(*mut *mut T) -> c_int) -> *mut T {
+    let mut out = std::ptr::null_mut();
+    call(&mut out);
+    out
+}
+
+unsafe fn read_str<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, DataCloakError> {
+    if ptr.is_null() {
+        return Err(DataCloakError::InvalidArgument(format!("{} is null", what)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|e| DataCloakError::InvalidArgument(format!("{} is not valid UTF-8: {}", what, e)))
+}
+
+/// Parses an optional JSON config, falling back to the defaults on null.
+unsafe fn read_config(json_config: *const c_char) -> Result<DataCloakConfig, DataCloakError> {
+    if json_config.is_null() {
+        return Ok(DataCloakConfig::default());
+    }
+    let json = read_str(json_config, "json_config")?;
+    DataCloakConfig::parse(json, ConfigFormat::Json)
+}
+
+unsafe fn engine_ref<'a>(engine: *mut c_void) -> Result<&'a DataCloakEngine, DataCloakError> {
+    (engine as *const DataCloakEngine)
+        .as_ref()
+        .ok_or_else(|| DataCloakError::InvalidArgument("engine is null".to_string()))
+}
+
+unsafe fn read_bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], DataCloakError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(DataCloakError::InvalidArgument("data is null".to_string()));
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+fn c_string(s: String) -> Result<CString, DataCloakError> {
+    CString::new(s)
+        .map_err(|e| DataCloakError::Internal(format!("Output contains a NUL byte: {}", e)))
+}
+
+fn into_c_string(s: String) -> Result<*mut c_char, DataCloakError> {
+    c_string(s).map(CString::into_raw)
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<*mut c_char, DataCloakError> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| DataCloakError::Internal(format!("Failed to serialize result: {}", e)))?;
+    into_c_string(json)
+}
+
+/// Creates an engine from a JSON-encoded `DataCloakConfig`, or from the
+/// defaults when `json_config` is null. Fields missing from the document keep
+/// their defaults. Release the engine with `datacloak_destroy`.
+#[no_mangle]
+pub extern "C" fn datacloak_engine_create(
+    json_config: *const c_char,
+    out_engine: *mut *mut c_void,
+) -> c_int {
+    ffi_status(out_engine, || {
+        let config = unsafe { read_config(json_config)? };
+        let engine = DataCloakEngine::new(config)?;
+        Ok(Box::into_raw(Box::new(engine)) as *mut c_void)
+    })
+}
+
+/// Detects PII in `text`, writing a JSON array of detections to `out_json`.
+/// Free the result with `datacloak_free_string`.
+#[no_mangle]
+pub extern "C" fn datacloak_detect(
+    engine: *mut c_void,
+    text: *const c_char,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    ffi_status(out_json, || {
+        let engine = unsafe { engine_ref(engine)? };
+        let text = unsafe { read_str(text, "text")? };
+        to_json(&engine.detect_pii(text)?)
+    })
+}
+
+/// Detects PII in a byte buffer of `len` bytes, which need not be UTF-8 or
+/// NUL-terminated. UTF-16 is recognized by its byte order mark or zero
+/// bytes, and other invalid UTF-8 is decoded as Latin-1, so samples in the
+/// JSON written to `out_json` are UTF-8 either way.
+#[no_mangle]
+pub extern "C" fn datacloak_detect_bytes(
+    engine: *mut c_void,
+    data: *const u8,
+    len: usize,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    ffi_status(out_json, || {
+        let engine = unsafe { engine_ref(engine)? };
+        let bytes = unsafe { read_bytes(data, len)? };
+        to_json(&engine.detect_pii(&decode_bytes(bytes))?)
+    })
+}
+
+/// A single detection. Strings are owned by the enclosing
+/// `DataCloakFindingList`; offsets are bytes into the scanned text.
+#[repr(C)]
+pub struct DataCloakFinding {
+    pub pii_type: *mut c_char,
+    pub sample: *mut c_char,
+    pub masked: *mut c_char,
+    pub start: usize,
+    pub end: usize,
+    pub confidence: f64,
+}
+
+/// Detections returned by `datacloak_detect_list`. Release with
+/// `datacloak_free_findings`.
+#[repr(C)]
+pub struct DataCloakFindingList {
+    pub items: *mut DataCloakFinding,
+    pub len: usize,
+}
+
+impl DataCloakFindingList {
+    fn from_results(results: Vec<PIIDetectionResult>) -> Result<Self, DataCloakError> {
+        // Convert every string before releasing any to C, so a failure part
+        // way through drops the earlier ones instead of leaking them
+        let converted = results
+            .into_iter()
+            .map(|pii| {
+                let strings = [
+                    c_string(pii.pii_type)?,
+                    c_string(pii.sample)?,
+                    c_string(pii.masked)?,
+                ];
+                Ok((strings, pii.start, pii.end, pii.confidence))
+            })
+            .collect::<Result<Vec<_>, DataCloakError>>()?;
+
+        let items = converted
+            .into_iter()
+            .map(
+                |([pii_type, sample, masked], start, end, confidence)| DataCloakFinding {
+                    pii_type: pii_type.into_raw(),
+                    sample: sample.into_raw(),
+                    masked: masked.into_raw(),
+                    start,
+                    end,
+                    confidence,
+                },
+            )
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+
+        let len = items.len();
+        Ok(Self {
+            items: Box::into_raw(items) as *mut DataCloakFinding,
+            len,
+        })
+    }
+}
+
+/// Detects PII in `text`, writing the detections as a `DataCloakFindingList`
+/// to `out_list`.
+#[no_mangle]
+pub extern "C" fn datacloak_detect_list(
+    engine: *mut c_void,
+    text: *const c_char,
+    out_list: *mut *mut DataCloakFindingList,
+) -> c_int {
+    ffi_status(out_list, || {
+        let engine = unsafe { engine_ref(engine)? };
+        let text = unsafe { read_str(text, "text")? };
+        let list = DataCloakFindingList::from_results(engine.detect_pii(text)?)?;
+        Ok(Box::into_raw(Box::new(list)))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_free_findings(list: *mut DataCloakFindingList) {
+    if !list.is_null() {
+        let _ = catch_panic(|| {
+            let list = unsafe { Box::from_raw(list) };
+            let items =
+                unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(list.items, list.len)) };
+            for item in items.iter() {
+                datacloak_free_string(item.pii_type);
+                datacloak_free_string(item.sample);
+                datacloak_free_string(item.masked);
+            }
+            Ok(())
+        });
+    }
+}
+
+/// A length-prefixed UTF-16 string allocated by the library. Release with
+/// `datacloak_free_utf16`.
+#[repr(C)]
+pub struct DataCloakUtf16Buffer {
+    pub data: *mut u16,
+    pub len: usize,
+}
+
+impl DataCloakUtf16Buffer {
+    fn from_text(s: &str) -> Self {
+        let units = s.encode_utf16().collect::<Vec<u16>>().into_boxed_slice();
+        let len = units.len();
+        Self {
+            data: Box::into_raw(units) as *mut u16,
+            len,
+        }
+    }
+}
+
+unsafe fn read_utf16(data: *const u16, len: usize) -> Result<String, DataCloakError> {
+    if len == 0 {
+        return Ok(String::new());
+    }
+    if data.is_null() {
+        return Err(DataCloakError::InvalidArgument("data is null".to_string()));
+    }
+    // Lone surrogates become U+FFFD, which is also one code unit, so offsets
+    // still line up with the caller's string
+    Ok(String::from_utf16_lossy(std::slice::from_raw_parts(
+        data, len,
+    )))
+}
+
+/// Rewrites detection offsets from UTF-8 bytes to UTF-16 code units.
+fn offsets_to_utf16(text: &str, results: &mut [PIIDetectionResult]) {
+    let offsets: Vec<usize> = results.iter().flat_map(|r| [r.start, r.end]).collect();
+    let units = utf16_offsets(text, &offsets);
+    for (pii, range) in results.iter_mut().zip(units.chunks(2)) {
+        pii.start = range[0];
+        pii.end = range[1];
+    }
+}
+
+/// Runs a UTF-16 entry point, writing its JSON output to `out`.
+fn utf16_status(
+    out: *mut DataCloakUtf16Buffer,
+    body: impl FnOnce() -> Result<String, DataCloakError>,
+) -> c_int {
+    if out.is_null() {
+        clear_last_error();
+        set_last_error(DataCloakError::InvalidArgument(
+            "output pointer is null".to_string(),
+        ));
+        return DATACLOAK_ERR_INVALID_ARGUMENT;
+    }
+
+    unsafe {
+        *out = DataCloakUtf16Buffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        }
+    };
+    ffi_try(|| {
+        let json = body()?;
+        unsafe { *out = DataCloakUtf16Buffer::from_text(&json) };
+        Ok(())
+    })
+}
+
+/// Detects PII in `len` UTF-16 code units, e.g. a .NET `string`, writing a
+/// JSON array of detections to `out_json`. Offsets in the JSON count UTF-16
+/// code units, so they index the caller's string directly.
+#[no_mangle]
+pub extern "C" fn datacloak_detect_pii_utf16(
+    engine: *mut c_void,
+    data: *const u16,
+    len: usize,
+    out_json: *mut DataCloakUtf16Buffer,
+) -> c_int {
+    utf16_status(out_json, || {
+        let engine = unsafe { engine_ref(engine)? };
+        let text = unsafe { read_utf16(data, len)? };
+        let mut results = engine.detect_pii(&text)?;
+        offsets_to_utf16(&text, &mut results);
+        serde_json::to_string(&results)
+            .map_err(|e| DataCloakError::Internal(format!("Failed to serialize result: {}", e)))
+    })
+}
+
+/// UTF-16 counterpart of `datacloak_mask`; offsets count UTF-16 code units.
+#[no_mangle]
+pub extern "C" fn datacloak_mask_text_utf16(
+    engine: *mut c_void,
+    data: *const u16,
+    len: usize,
+    out_json: *mut DataCloakUtf16Buffer,
+) -> c_int {
+    utf16_status(out_json, || {
+        let engine = unsafe { engine_ref(engine)? };
+        let text = unsafe { read_utf16(data, len)? };
+        let mut result = engine.mask_text(&text)?;
+        offsets_to_utf16(&text, &mut result.detected_pii);
+        serde_json::to_string(&result)
+            .map_err(|e| DataCloakError::Internal(format!("Failed to serialize result: {}", e)))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_free_utf16(buffer: DataCloakUtf16Buffer) {
+    if !buffer.data.is_null() {
+        let _ = catch_panic(|| {
+            drop(unsafe {
+                Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len))
+            });
+            Ok(())
+        });
+    }
+}
+
+/// Masks `text`, writing the JSON-encoded `MaskingResult` to `out_json`.
+/// Free the result with `datacloak_free_string`.
+#[no_mangle]
+pub extern "C" fn datacloak_mask(
+    engine: *mut c_void,
+    text: *const c_char,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    ffi_status(out_json, || {
+        let engine = unsafe { engine_ref(engine)? };
+        let text = unsafe { read_str(text, "text")? };
+        to_json(&engine.mask_text(text)?)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_create() -> *mut c_void {
+    legacy_call(|out| datacloak_engine_create(std::ptr::null(), out))
+}
+
+/// Creates an engine from a JSON-encoded `DataCloakConfig`. Returns null if
+/// `json_config` is null, the JSON is invalid or the config fails validation.
+#[no_mangle]
+pub extern "C" fn datacloak_create_with_config(json_config: *const c_char) -> *mut c_void {
+    if json_config.is_null() {
+        clear_last_error();
+        set_last_error(DataCloakError::InvalidArgument(
+            "json_config is null".to_string(),
+        ));
+        return std::ptr::null_mut();
+    }
+    legacy_call(|out| datacloak_engine_create(json_config, out))
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_destroy(engine: *mut c_void) {
+    if !engine.is_null() {
+        let _ = catch_panic(|| {
+            drop(unsafe { Box::from_raw(engine as *mut DataCloakEngine) });
+            Ok(())
+        });
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_detect_pii(engine: *mut c_void, text: *const c_char) -> *mut c_char {
+    legacy_call(|out| datacloak_detect(engine, text, out))
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_detect_pii_bytes(
+    engine: *mut c_void,
+    data: *const u8,
+    len: usize,
+) -> *mut c_char {
+    legacy_call(|out| datacloak_detect_bytes(engine, data, len, out))
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_mask_text(engine: *mut c_void, text: *const c_char) -> *mut c_char {
+    legacy_call(|out| datacloak_mask(engine, text, out))
+}
+
+unsafe fn pool_ref<'a>(pool: *mut c_void) -> Result<&'a EnginePool, DataCloakError> {
+    (pool as *const EnginePool)
+        .as_ref()
+        .ok_or_else(|| DataCloakError::InvalidArgument("pool is null".to_string()))
+}
+
+/// Creates a pool of `size` engines sharing one config (defaults when
+/// `json_config` is null). Pool calls are safe from any number of threads.
+/// Release the pool with `datacloak_pool_destroy`.
+#[no_mangle]
+pub extern "C" fn datacloak_pool_create(
+    size: usize,
+    json_config: *const c_char,
+    out_pool: *mut *mut c_void,
+) -> c_int {
+    ffi_status(out_pool, || {
+        let config = unsafe { read_config(json_config)? };
+        let pool = EnginePool::new(config, size)?;
+        Ok(Box::into_raw(Box::new(pool)) as *mut c_void)
+    })
+}
+
+/// Same as `datacloak_detect`, on the next engine of the pool.
+#[no_mangle]
+pub extern "C" fn datacloak_pool_detect(
+    pool: *mut c_void,
+    text: *const c_char,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    ffi_status(out_json, || {
+        let pool = unsafe { pool_ref(pool)? };
+        let text = unsafe { read_str(text, "text")? };
+        to_json(&pool.engine().detect_pii(text)?)
+    })
+}
+
+/// Same as `datacloak_mask`, on the next engine of the pool.
+#[no_mangle]
+pub extern "C" fn datacloak_pool_mask(
+    pool: *mut c_void,
+    text: *const c_char,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    ffi_status(out_json, || {
+        let pool = unsafe { pool_ref(pool)? };
+        let text = unsafe { read_str(text, "text")? };
+        to_json(&pool.engine().mask_text(text)?)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_pool_destroy(pool: *mut c_void) {
+    if !pool.is_null() {
+        let _ = catch_panic(|| {
+            drop(unsafe { Box::from_raw(pool as *mut EnginePool) });
+            Ok(())
+        });
+    }
+}
+
+/// Receives each streaming finding as a NUL-terminated JSON object whose
+/// `start`/`end` are byte offsets from the start of the stream. The string is
+/// only valid for the duration of the call. Nullable on the C side, but
+/// rejected by `datacloak_stream_begin` when null.
+pub type DataCloakFindingCallback =
+    Option<extern "C" fn(finding_json: *const c_char, user_data: *mut c_void)>;
+
+struct StreamSession {
+    // Locked so `datacloak_cancel` can reach the session while a feed runs
+    scanner: Mutex<StreamScanner<'static>>,
+    cancel: CancellationToken,
+    callback: extern "C" fn(*const c_char, *mut c_void),
+    user_data: *mut c_void,
+}
+
+fn report_findings(
+    callback: extern "C" fn(*const c_char, *mut c_void),
+    user_data: *mut c_void,
+    findings: Vec<PIIDetectionResult>,
+) -> Result<(), DataCloakError> {
+    for finding in findings {
+        let json = to_json(&finding)?;
+        callback(json, user_data);
+        // Reclaim the string we just handed out
+        datacloak_free_string(json);
+    }
+    Ok(())
+}
+
+unsafe fn stream_ref<'a>(stream: *mut c_void) -> Result<&'a StreamSession, DataCloakError> {
+    (stream as *const StreamSession)
+        .as_ref()
+        .ok_or_else(|| DataCloakError::InvalidArgument("stream is null".to_string()))
+}
+
+/// Starts a streaming scan on `engine`, which must outlive the stream.
+/// `callback` is invoked with `user_data` for every finding, from within
+/// `datacloak_stream_feed` and `datacloak_stream_finish`.
+#[no_mangle]
+pub extern "C" fn datacloak_stream_begin(
+    engine: *mut c_void,
+    callback: DataCloakFindingCallback,
+    user_data: *mut c_void,
+    out_stream: *mut *mut c_void,
+) -> c_int {
+    ffi_status(out_stream, || {
+        let engine = unsafe { engine_ref(engine)? };
+        let callback = callback
+            .ok_or_else(|| DataCloakError::InvalidArgument("callback is null".to_string()))?;
+        let cancel = CancellationToken::new();
+        let session = StreamSession {
+            scanner: Mutex::new(StreamScanner::new(engine).with_cancellation(cancel.clone())),
+            cancel,
+            callback,
+            user_data,
+        };
+        Ok(Box::into_raw(Box::new(session)) as *mut c_void)
+    })
+}
+
+/// Feeds the next chunk of UTF-8 text. Chunks may split a value, or a
+/// multi-byte character, anywhere; a partial character is held back until
+/// the next feed completes it.
+#[no_mangle]
+pub extern "C" fn datacloak_stream_feed(stream: *mut c_void, chunk: *const c_char) -> c_int {
+    ffi_try(|| {
+        let session = unsafe { stream_ref(stream)? };
+        if chunk.is_null() {
+            return Err(DataCloakError::InvalidArgument("chunk is null".to_string()));
+        }
+        let chunk = unsafe { CStr::from_ptr(chunk) }.to_bytes();
+        let findings = session
+            .scanner
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Stream"))?
+            .feed_bytes(chunk)?;
+        report_findings(session.callback, session.user_data, findings)
+    })
+}
+
+/// Reports the findings left in the final chunk and releases the stream,
+/// whatever the returned status.
+#[no_mangle]
+pub extern "C" fn datacloak_stream_finish(stream: *mut c_void) -> c_int {
+    ffi_try(|| {
+        if stream.is_null() {
+            return Err(DataCloakError::InvalidArgument(
+                "stream is null".to_string(),
+            ));
+        }
+        let session = unsafe { Box::from_raw(stream as *mut StreamSession) };
+        let scanner = session
+            .scanner
+            .into_inner()
+            .map_err(|_| DataCloakError::poisoned("Stream"))?;
+        report_findings(session.callback, session.user_data, scanner.finish()?)
+    })
+}
+
+/// Cancels a stream from any thread. A feed in progress stops at its next
+/// pattern pass, and it and every later feed return `DATACLOAK_ERR_CANCELLED`.
+/// The stream must still be released with `datacloak_stream_finish`, which
+/// must not race with this call.
+#[no_mangle]
+pub extern "C" fn datacloak_cancel(stream: *mut c_void) -> c_int {
+    ffi_try(|| {
+        unsafe { stream_ref(stream)? }.cancel.cancel();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        let _ = catch_panic(|| {
+            drop(unsafe { CString::from_raw(s) });
+            Ok(())
+        });
+    }
+}
+
+/// Returns `DATACLOAK_ABI_VERSION` as compiled into this library.
+#[no_mangle]
+pub extern "C" fn datacloak_abi_version() -> u32 {
+    DATACLOAK_ABI_VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn datacloak_version() -> *mut c_char {
+    legacy_call(|out| ffi_status(out, || into_c_string("1.0.0".to_string())))
+}
+
+/// Returns the message of the last error raised on this thread, or null if
+/// the previous call succeeded. Free the result with `datacloak_free_string`.
+#[no_mangle]
+pub extern "C" fn datacloak_last_error() -> *mut c_char {
+    LAST_ERROR.with(|slot| match slot.borrow().as_ref() {
+        Some(error) => CString::new(error.to_string())
+            .map(CString::into_raw)
+            .unwrap_or(std::ptr::null_mut()),
+        None => std::ptr::null_mut(),
+    })
+}
+
+/// Returns the `DataCloakError::code` of the last error raised on this
+/// thread, or 0 if the previous call succeeded.
+#[no_mangle]
+pub extern "C" fn datacloak_last_error_code() -> c_int {
+    LAST_ERROR.with(|slot| slot.borrow().as_ref().map_or(0, DataCloakError::code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error_message() -> String {
+        let ptr = datacloak_last_error();
+        assert!(!ptr.is_null());
+        let message = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        datacloak_free_string(ptr);
+        message
+    }
+
+    #[test]
+    fn test_ffi_create_with_config() {
+        let json = CString::new(r#"{"enabled_types": ["ssn"], "max_text_length": 64}"#).unwrap();
+        let engine = datacloak_create_with_config(json.as_ptr());
+        assert!(!engine.is_null());
+        {
+            let engine = unsafe { &*(engine as *const DataCloakEngine) };
+            assert_eq!(engine.config.max_text_length, 64);
+            assert!(engine.detect_pii("mail a@b.com").unwrap().is_empty());
+        }
+        datacloak_destroy(engine);
+
+        let bad = CString::new(r#"{"max_text_length": 0}"#).unwrap();
+        assert!(datacloak_create_with_config(bad.as_ptr()).is_null());
+        assert!(datacloak_create_with_config(std::ptr::null()).is_null());
+    }
+
+    #[test]
+    fn test_ffi_last_error_distinguishes_failures() {
+        let engine = datacloak_create_with_config(
+            CString::new(r#"{"max_text_length": 8}"#).unwrap().as_ptr(),
+        );
+
+        let invalid_utf8 = [0xff_u8, 0xfe, 0];
+        assert!(datacloak_detect_pii(engine, invalid_utf8.as_ptr() as *const c_char).is_null());
+        assert_eq!(datacloak_last_error_code(), 9);
+        assert!(last_error_message().contains("UTF-8"));
+
+        let long = CString::new("far more than eight bytes").unwrap();
+        assert!(datacloak_mask_text(engine, long.as_ptr()).is_null());
+        assert_eq!(datacloak_last_error_code(), 2);
+
+        let short = CString::new("hi").unwrap();
+        let json = datacloak_detect_pii(engine, short.as_ptr());
+        assert!(!json.is_null());
+        assert_eq!(datacloak_last_error_code(), 0);
+        assert!(datacloak_last_error().is_null());
+
+        datacloak_free_string(json);
+        datacloak_destroy(engine);
+    }
+
+    #[test]
+    fn test_ffi_status_codes_and_out_params() {
+        let mut engine = std::ptr::null_mut();
+        assert_eq!(
+            datacloak_engine_create(std::ptr::null(), &mut engine),
+            DATACLOAK_OK
+        );
+        assert!(!engine.is_null());
+
+        let text = CString::new("Email jane@example.com").unwrap();
+        let mut json = std::ptr::null_mut();
+        assert_eq!(
+            datacloak_mask(engine, text.as_ptr(), &mut json),
+            DATACLOAK_OK
+        );
+        let masked = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(masked.contains("j***@example.com"));
+        datacloak_free_string(json);
+
+        assert_eq!(
+            datacloak_detect(std::ptr::null_mut(), text.as_ptr(), &mut json),
+            DATACLOAK_ERR_INVALID_ARGUMENT
+        );
+        assert!(json.is_null());
+        assert_eq!(
+            datacloak_detect(engine, text.as_ptr(), std::ptr::null_mut()),
+            DATACLOAK_ERR_INVALID_ARGUMENT
+        );
+
+        let bad = CString::new(r#"{"masking_strategy": {"strategy": "hmac", "key": ""}}"#).unwrap();
+        let mut other = std::ptr::null_mut();
+        assert_eq!(
+            datacloak_engine_create(bad.as_ptr(), &mut other),
+            DATACLOAK_ERR_INVALID_CONFIG
+        );
+        assert!(other.is_null());
+
+        datacloak_destroy(engine);
+    }
+
+    #[test]
+    fn test_ffi_panic_becomes_error_code() {
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let status = ffi_status(&mut out, || panic!("slice index out of range"));
+        assert_eq!(status, DATACLOAK_ERR_INTERNAL);
+        assert!(out.is_null());
+        assert!(last_error_message().contains("slice index out of range"));
+    }
+
+    #[test]
+    fn test_ffi_detect_pii_bytes_accepts_latin1() {
+        let engine = datacloak_create();
+        let data = b"Jos\xe9: jose@example.com";
+        let json = datacloak_detect_pii_bytes(engine, data.as_ptr(), data.len());
+        assert!(!json.is_null());
+        let detections = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(detections.contains("jose@example.com"));
+
+        datacloak_free_string(json);
+        datacloak_destroy(engine);
+    }
+
+    extern "C" fn collect_finding(finding_json: *const c_char, user_data: *mut c_void) {
+        let findings = unsafe { &mut *(user_data as *mut Vec<String>) };
+        let json = unsafe { CStr::from_ptr(finding_json) };
+        findings.push(json.to_str().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_ffi_stream_joins_characters_split_across_feeds() {
+        let engine = datacloak_create();
+        let mut findings: Vec<String> = Vec::new();
+        let mut stream = std::ptr::null_mut();
+        assert_eq!(
+            datacloak_stream_begin(
+                engine,
+                Some(collect_finding),
+                &mut findings as *mut Vec<String> as *mut c_void,
+                &mut stream,
+            ),
+            DATACLOAK_OK
+        );
+
+        // "é" is C3 A9; the first feed ends between the two bytes
+        let first = CString::new(&b"caf\xC3"[..]).unwrap();
+        let second = CString::new(&b"\xA9 jane@example.com"[..]).unwrap();
+        assert_eq!(datacloak_stream_feed(stream, first.as_ptr()), DATACLOAK_OK);
+        assert_eq!(datacloak_stream_feed(stream, second.as_ptr()), DATACLOAK_OK);
+        assert_eq!(datacloak_stream_finish(stream), DATACLOAK_OK);
+
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].contains("jane@example.com"));
+        assert!(findings[0].contains("\"start\":6"));
+
+        // A character never completed fails at finish
+        let mut stream = std::ptr::null_mut();
+        datacloak_stream_begin(
+            engine,
+            Some(collect_finding),
+            std::ptr::null_mut(),
+            &mut stream,
+        );
+        assert_eq!(datacloak_stream_feed(stream, first.as_ptr()), DATACLOAK_OK);
+        assert_ne!(datacloak_stream_finish(stream), DATACLOAK_OK);
+        datacloak_destroy(engine);
+    }
+
+    #[test]
+    fn test_ffi_stream_invokes_callback_per_finding() {
+        let engine = datacloak_create();
+        let mut findings: Vec<String> = Vec::new();
+        let mut stream = std::ptr::null_mut();
+        assert_eq!(
+            datacloak_stream_begin(
+                engine,
+                Some(collect_finding),
+                &mut findings as *mut Vec<String> as *mut c_void,
+                &mut stream,
+            ),
+            DATACLOAK_OK
+        );
+
+        let text = format!(
+            "{}write to jane@example.com {}",
+            "x ".repeat(300),
+            "y ".repeat(300)
+        );
+        for chunk in text.as_bytes().chunks(64) {
+            let chunk = CString::new(chunk).unwrap();
+            assert_eq!(datacloak_stream_feed(stream, chunk.as_ptr()), DATACLOAK_OK);
+        }
+        assert_eq!(datacloak_stream_finish(stream), DATACLOAK_OK);
+
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].contains("jane@example.com"));
+        datacloak_destroy(engine);
+    }
+
+    #[test]
+    fn test_ffi_detect_list_returns_repr_c_findings() {
+        assert_eq!(datacloak_abi_version(), DATACLOAK_ABI_VERSION);
+
+        let engine = datacloak_create();
+        let text = CString::new("SSN 123-45-6789").unwrap();
+        let mut list = std::ptr::null_mut();
+        assert_eq!(
+            datacloak_detect_list(engine, text.as_ptr(), &mut list),
+            DATACLOAK_OK
+        );
+
+        let findings = unsafe { std::slice::from_raw_parts((*list).items, (*list).len) };
+        let ssn = findings
+            .iter()
+            .find(|f| unsafe { CStr::from_ptr(f.pii_type) }.to_str() == Ok("ssn"))
+            .unwrap();
+        assert_eq!((ssn.start, ssn.end), (4, 15));
+
+        datacloak_free_findings(list);
+        datacloak_destroy(engine);
+    }
+
+    #[test]
+    fn test_finding_list_rejects_nul_bytes_after_converting_earlier_findings() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let mut results = engine
+            .detect_pii("SSN 123-45-6789, mail jane@example.com")
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        results[1].sample.push('\0');
+
+        assert!(matches!(
+            DataCloakFindingList::from_results(results),
+            Err(DataCloakError::Internal(_))
+        ));
+    }
+
+    #[test]
+    fn test_ffi_pool_detect_from_threads() {
+        let mut pool = std::ptr::null_mut();
+        assert_eq!(
+            datacloak_pool_create(0, std::ptr::null(), &mut pool),
+            DATACLOAK_ERR_INVALID_CONFIG
+        );
+        assert_eq!(
+            datacloak_pool_create(2, std::ptr::null(), &mut pool),
+            DATACLOAK_OK
+        );
+
+        // Raw pointers aren't Send; the address is, and the pool is Sync
+        let address = pool as usize;
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(move || {
+                    let text = CString::new("SSN 123-45-6789").unwrap();
+                    let mut json = std::ptr::null_mut();
+                    let status =
+                        datacloak_pool_detect(address as *mut c_void, text.as_ptr(), &mut json);
+                    datacloak_free_string(json);
+                    status
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), DATACLOAK_OK);
+        }
+
+        datacloak_pool_destroy(pool);
+    }
+
+    #[test]
+    fn test_ffi_utf16_offsets_index_the_callers_string() {
+        let engine = datacloak_create();
+        let text: Vec<u16> = "Zoë\0 zoe@example.com".encode_utf16().collect();
+        let mut out = DataCloakUtf16Buffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(
+            datacloak_detect_pii_utf16(engine, text.as_ptr(), text.len(), &mut out),
+            DATACLOAK_OK
+        );
+
+        let json =
+            String::from_utf16(unsafe { std::slice::from_raw_parts(out.data, out.len) }).unwrap();
+        // The embedded NUL doesn't truncate, and offsets count UTF-16 units
+        assert!(json.contains(r#""start":5"#));
+        assert!(json.contains(r#""end":20"#));
+
+        datacloak_free_utf16(out);
+        datacloak_destroy(engine);
+    }
+
+    #[test]
+    fn test_ffi_cancel_stream() {
+        let engine = datacloak_create();
+        let mut findings: Vec<String> = Vec::new();
+        let mut stream = std::ptr::null_mut();
+        datacloak_stream_begin(
+            engine,
+            Some(collect_finding),
+            &mut findings as *mut Vec<String> as *mut c_void,
+            &mut stream,
+        );
+
+        assert_eq!(datacloak_cancel(stream), DATACLOAK_OK);
+        let chunk = CString::new("jane@example.com ".repeat(100)).unwrap();
+        assert_eq!(
+            datacloak_stream_feed(stream, chunk.as_ptr()),
+            DATACLOAK_ERR_CANCELLED
+        );
+        assert_eq!(datacloak_stream_finish(stream), DATACLOAK_ERR_CANCELLED);
+        assert!(findings.is_empty());
+        datacloak_destroy(engine);
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+
+use aes::Aes256;
+use fpe::ff1::{FlexibleNumeralString, FF1};
+
+use crate::error::DataCloakError;
+
+/// FF1 format-preserving encryption over the digits of a value.
+///
+/// Separators are kept in place and only the digits are encrypted. Cycle
+/// walking keeps the output in the same validity class as the input, so a
+/// Luhn-valid card number encrypts to another Luhn-valid card number and a
+/// well-formed SSN to another well-formed SSN. The `fpe` crate only
+/// implements FF1, so FF3-1 is not offered.
+pub(crate) struct FormatPreservingCipher {
+    ff1: FF1<Aes256>,
+}
+
+impl fmt::Debug for FormatPreservingCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FormatPreservingCipher")
+    }
+}
+
+impl FormatPreservingCipher {
+    pub(crate) const KEY_LEN: usize = 32;
+
+    pub(crate) fn new(key: &[u8]) -> Result<Self, DataCloakError> {
+        if key.len() != Self::KEY_LEN {
+            return Err(DataCloakError::InvalidConfig(format!(
+                "Format-preserving encryption requires a {}-byte key, got {}",
+                Self::KEY_LEN,
+                key.len()
+            )));
+        }
+
+        let ff1 = FF1::<Aes256>::new(key, 10)
+            .map_err(|e| DataCloakError::Crypto(format!("Failed to initialize FF1: {}", e)))?;
+        Ok(Self { ff1 })
+    }
+
+    /// Returns true if values of `pii_type` are handled by this cipher.
+    pub(crate) fn supports(pii_type: &str) -> bool {
+        matches!(pii_type, "credit_card" | "ssn")
+    }
+
+    pub(crate) fn encrypt(&self, value: &str, pii_type: &str) -> Result<String, DataCloakError> {
+        self.transform(value, pii_type, true)
+    }
+
+    pub(crate) fn decrypt(&self, value: &str, pii_type: &str) -> Result<String, DataCloakError> {
+        self.transform(value, pii_type, false)
+    }
+
+    fn transform(
+        &self,
+        value: &str,
+        pii_type: &str,
+        encrypt: bool,
+    ) -> Result<String, DataCloakError> {
+        let digits: Vec<u16> = value
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as u16)
+            .collect();
+
+        let is_valid = |d: &[u16]| match pii_type {
+            "credit_card" => luhn_valid(d),
+            "ssn" => ssn_valid(d),
+            _ => true,
+        };
+        let target = is_valid(&digits);
+        let tweak = pii_type.as_bytes();
+
+        let mut current = digits;
+        loop {
+            let input = FlexibleNumeralString::from(current);
+            let output = if encrypt {
+                self.ff1.encrypt(tweak, &input)
+            } else {
+                self.ff1.decrypt(tweak, &input)
+            }
+            .map_err(|e| {
+                DataCloakError::Crypto(format!("Format-preserving encryption failed: {}", e))
+            })?;
+            current = output.into();
+
+            if is_valid(&current) == target {
+                break;
+            }
+        }
+
+        let mut out_digits = current.into_iter();
+        Ok(value
+            .chars()
+            .map(|c| {
+                if c.is_ascii_digit() {
+                    out_digits
+                        .next()
+                        .and_then(|d| char::from_digit(d as u32, 10))
+                        .unwrap_or(c)
+                } else {
+                    c
+                }
+            })
+            .collect())
+    }
+}
+
+fn luhn_valid(digits: &[u16]) -> bool {
+    let sum: u16 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn ssn_valid(digits: &[u16]) -> bool {
+    if digits.len() != 9 {
+        return false;
+    }
+    let number = |d: &[u16]| d.iter().fold(0u32, |acc, &x| acc * 10 + x as u32);
+    let area = number(&digits[..3]);
+    let group = number(&digits[3..5]);
+    let serial = number(&digits[5..]);
+
+    area != 0 && area != 666 && area < 900 && group != 0 && serial != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_preserves_format() {
+        let cipher = FormatPreservingCipher::new(&[7u8; 32]).unwrap();
+
+        let card = "4532 0151 1283 0366";
+        let masked = cipher.encrypt(card, "credit_card").unwrap();
+        assert_eq!(masked.len(), card.len());
+        assert_eq!(masked.as_bytes()[4], b' ');
+        let masked_digits: Vec<u16> = masked
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as u16)
+            .collect();
+        assert!(luhn_valid(&masked_digits));
+        assert_eq!(cipher.decrypt(&masked, "credit_card").unwrap(), card);
+
+        let ssn = "123-45-6789";
+        let masked = cipher.encrypt(ssn, "ssn").unwrap();
+        assert_eq!(masked.len(), ssn.len());
+        assert_eq!(cipher.decrypt(&masked, "ssn").unwrap(), ssn);
+    }
+
+    #[test]
+    fn test_rejects_wrong_key_length() {
+        assert!(FormatPreservingCipher::new(b"short").is_err());
+    }
+}
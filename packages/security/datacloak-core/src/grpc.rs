@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::{DataCloakEngine, DataCloakError, MaskingResult, PIIDetectionResult};
+
+/// Messages and service traits generated from `proto/datacloak.proto`.
+pub mod proto {
+    tonic::include_proto!("datacloak.v1");
+}
+
+use proto::data_cloak_server::{DataCloak, DataCloakServer};
+use proto::{DetectPiiRequest, DetectPiiResponse, Detection, MaskTextRequest, MaskTextResponse};
+
+/// Responses buffered per `MaskStream` call before the server stops reading
+/// requests, so a slow client cannot make it queue unbounded work.
+const STREAM_BUFFER: usize = 16;
+
+/// The `datacloak.v1.DataCloak` gRPC service. Add it to a tonic server with
+/// `into_server`; scans run on tokio's blocking pool.
+#[derive(Debug, Clone)]
+pub struct DataCloakGrpc {
+    engine: Arc<DataCloakEngine>,
+}
+
+impl DataCloakGrpc {
+    pub fn new(engine: Arc<DataCloakEngine>) -> Self {
+        Self { engine }
+    }
+
+    /// Wraps the service for `tonic::transport::Server::add_service`.
+    pub fn into_server(self) -> DataCloakServer<Self> {
+        DataCloakServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl DataCloak for DataCloakGrpc {
+    async fn detect_pii(
+        &self,
+        request: Request<DetectPiiRequest>,
+    ) -> Result<Response<DetectPiiResponse>, Status> {
+        let detections = Arc::clone(&self.engine)
+            .detect_pii_async(request.into_inner().text)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(DetectPiiResponse {
+            detections: detections.into_iter().map(Detection::from).collect(),
+        }))
+    }
+
+    async fn mask_text(
+        &self,
+        request: Request<MaskTextRequest>,
+    ) -> Result<Response<MaskTextResponse>, Status> {
+        let response = mask(&self.engine, request.into_inner()).await?;
+        Ok(Response::new(response))
+    }
+
+    type MaskStreamStream = ReceiverStream<Result<MaskTextResponse, Status>>;
+
+    /// Masks requests one at a time as they arrive. A failing message ends
+    /// the stream with its status; earlier responses are still delivered.
+    async fn mask_stream(
+        &self,
+        request: Request<Streaming<MaskTextRequest>>,
+    ) -> Result<Response<Self::MaskStreamStream>, Status> {
+        let mut requests = request.into_inner();
+        let engine = Arc::clone(&self.engine);
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            loop {
+                let response = match requests.message().await {
+                    Ok(Some(request)) => mask(&engine, request).await,
+                    Ok(None) => break,
+                    Err(e) => Err(e),
+                };
+                let failed = response.is_err();
+                // The client hung up; stop masking for it
+                if sender.send(response).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+async fn mask(
+    engine: &Arc<DataCloakEngine>,
+    request: MaskTextRequest,
+) -> Result<MaskTextResponse, Status> {
+    let result: MaskingResult = Arc::clone(engine)
+        .mask_text_async(request.text)
+        .await
+        .map_err(status)?;
+    Ok(MaskTextResponse {
+        masked_text: result.masked_text,
+        detections: result
+            .detected_pii
+            .into_iter()
+            .map(Detection::from)
+            .collect(),
+        token_map: result.token_map,
+        id: request.id,
+    })
+}
+
+impl From<PIIDetectionResult> for Detection {
+    fn from(pii: PIIDetectionResult) -> Self {
+        Self {
+            field_name: pii.field_name,
+            pii_type: pii.pii_type,
+            confidence: pii.confidence,
+            sample: pii.sample,
+            masked: pii.masked,
+            start: pii.start as u64,
+            end: pii.end as u64,
+        }
+    }
+}
+
+fn status(error: DataCloakError) -> Status {
+    let message = error.to_string();
+    match error {
+        DataCloakError::TextTooLarge { .. } | DataCloakError::InvalidArgument(_) => {
+            Status::invalid_argument(message)
+        }
+        DataCloakError::Timeout { .. } => Status::deadline_exceeded(message),
+        DataCloakError::Cancelled => Status::cancelled(message),
+        _ => Status::internal(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_mask_text_echoes_id() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let engine = Arc::new(DataCloakEngine::new(DataCloakConfig::default()).unwrap());
+        let service = DataCloakGrpc::new(engine);
+
+        let response = runtime
+            .block_on(service.mask_text(Request::new(MaskTextRequest {
+                text: "SSN 123-45-6789".to_string(),
+                id: "row-7".to_string(),
+            })))
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.masked_text, "SSN ***-**-6789");
+        assert_eq!(response.id, "row-7");
+        assert_eq!(response.detections[0].pii_type, "ssn");
+        assert_eq!(response.detections[0].start, 4);
+    }
+
+    #[test]
+    fn test_engine_errors_map_to_grpc_codes() {
+        let error = DataCloakError::TextTooLarge { length: 10, max: 5 };
+        assert_eq!(status(error).code(), tonic::Code::InvalidArgument);
+        assert_eq!(
+            status(DataCloakError::Timeout { elapsed_ms: 5 }).code(),
+            tonic::Code::DeadlineExceeded
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use crate::document::{FieldTotals, ValueVisitor};
+use crate::{DataCloakEngine, DataCloakError, DocumentMaskingResult, PIIDetectionResult};
+
+/// Elements that never have content or an end tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is code rather than text and is never scanned.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// URL schemes whose `href` target is personal data rather than a location.
+const CONTACT_SCHEMES: &[&str] = &["mailto:", "tel:"];
+
+/// Entities decoded before scanning. Any other named entity is left as
+/// written so it still renders after the text around it is masked.
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+];
+
+impl DataCloakEngine {
+    /// Detects PII in the text content of an HTML document and in the
+    /// attributes `mask_html` rewrites. Each finding's `field_name` is the
+    /// element path of its value, such as `/html/body/p` or
+    /// `/html/body/a/@href`.
+    pub fn detect_pii_html(&self, html: &str) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut findings = Vec::new();
+        rewrite_html(html, &mut |path, text| {
+            findings.extend(self.detect_pii(text)?.into_iter().map(|mut pii| {
+                pii.field_name = path.to_string();
+                pii
+            }));
+            Ok(text.to_string())
+        })?;
+        Ok(findings)
+    }
+
+    /// Masks the text content of an HTML document, `mailto:` and `tel:`
+    /// link targets and `data-*` attribute values, leaving tags, other
+    /// attributes, comments, scripts and styles exactly as written so the
+    /// page still renders. Unlike `mask_xml` this accepts real-world markup:
+    /// void elements, unquoted attributes and unclosed tags.
+    pub fn mask_html(&self, html: &str) -> Result<DocumentMaskingResult, DataCloakError> {
+        let mut totals = FieldTotals::new();
+        let masked = rewrite_html(html, &mut |path, text| {
+            Ok(totals.add(path, self.mask_text(text)?))
+        })?;
+        Ok(totals.into_document(masked))
+    }
+}
+
+pub(crate) fn rewrite_html(
+    html: &str,
+    visit: &mut ValueVisitor<'_>,
+) -> Result<String, DataCloakError> {
+    let mut out = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < html.len() {
+        let text_end = html[i..].find('<').map_or(html.len(), |p| i + p);
+        if text_end > i {
+            out.push_str(&rewrite_text(
+                &html[i..text_end],
+                &element_path(&open),
+                visit,
+            )?);
+        }
+        if text_end == html.len() {
+            break;
+        }
+        i = text_end;
+        let rest = &html[i..];
+
+        let len = if rest.starts_with("<!--") {
+            rest.find("-->").map_or(rest.len(), |p| p + 3)
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest.find('>').map_or(rest.len(), |p| p + 1)
+        } else if let Some(close) = rest.strip_prefix("</") {
+            let name = tag_name(close);
+            if let Some(depth) = open.iter().rposition(|open| *open == name) {
+                open.truncate(depth);
+            }
+            rest.find('>').map_or(rest.len(), |p| p + 1)
+        } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let len = tag_end(rest);
+            let tag = &rest[..len];
+            let name = tag_name(&tag[1..]);
+            let path = format!("{}/{}", element_path(&open), name);
+            out.push_str(&rewrite_tag(tag, &path, visit)?);
+            i += len;
+
+            let self_closing = tag.ends_with("/>");
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) && !self_closing {
+                let end = find_end_tag(&html[i..], &name).map_or(html.len(), |p| i + p);
+                out.push_str(&html[i..end]);
+                i = end;
+            } else if !VOID_ELEMENTS.contains(&name.as_str()) && !self_closing {
+                open.push(name);
+            }
+            continue;
+        } else {
+            // A `<` that starts no tag is text.
+            1
+        };
+        out.push_str(&rest[..len]);
+        i += len;
+    }
+
+    Ok(out)
+}
+
+fn element_path(open: &[String]) -> String {
+    open.iter().map(|name| format!("/{}", name)).collect()
+}
+
+/// Lower-cased element name at the start of `s`.
+fn tag_name(s: &str) -> String {
+    s.chars()
+        .take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Length of the tag at the start of `s`, up to and including the `>` that
+/// is not inside a quoted attribute value. As in browsers, only a quote
+/// right after `=` opens a value, so `title=O'Brien` ends at its `>`.
+fn tag_end(s: &str) -> usize {
+    let mut quote = None;
+    let mut after_equals = false;
+    for (index, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if after_equals => quote = Some(c),
+            (None, '>') => return index + 1,
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            after_equals = quote.is_none() && c == '=';
+        }
+    }
+    s.len()
+}
+
+fn find_end_tag(s: &str, name: &str) -> Option<usize> {
+    let lower = s.to_ascii_lowercase();
+    lower.find(&format!("</{}", name))
+}
+
+fn rewrite_text(
+    raw: &str,
+    path: &str,
+    visit: &mut ValueVisitor<'_>,
+) -> Result<String, DataCloakError> {
+    if raw.trim().is_empty() {
+        return Ok(raw.to_string());
+    }
+    let text = decode_entities(raw);
+    let masked = visit(path, &text)?;
+    Ok(if masked == text {
+        raw.to_string()
+    } else {
+        escape(&masked, false)
+    })
+}
+
+/// `tag` with the values of `data-*` attributes and the targets of contact
+/// links passed through `visit`. Everything else in the tag, including
+/// attribute order, spacing and quoting, is copied unchanged.
+fn rewrite_tag(
+    tag: &str,
+    path: &str,
+    visit: &mut ValueVisitor<'_>,
+) -> Result<String, DataCloakError> {
+    let bytes = tag.as_bytes();
+    let mut out = String::with_capacity(tag.len());
+    let mut copied = 0;
+    let mut i = 1 + tag[1..]
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(tag.len() - 1);
+
+    while i < tag.len() {
+        while i < tag.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        let name_start = i;
+        while i < tag.len() && !b" \t\r\n=>/".contains(&bytes[i]) {
+            i += 1;
+        }
+        if i == name_start {
+            break;
+        }
+        let name = tag[name_start..i].to_ascii_lowercase();
+        let mut j = i;
+        while j < tag.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        if j >= tag.len() || bytes[j] != b'=' {
+            continue;
+        }
+        j += 1;
+        while j < tag.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+
+        let value_start = j;
+        let (value, value_end) = match bytes.get(j) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let close = tag[j + 1..]
+                    .find(quote as char)
+                    .map_or(tag.len(), |p| j + 1 + p);
+                (&tag[j + 1..close], (close + 1).min(tag.len()))
+            }
+            _ => {
+                while j < tag.len() && !bytes[j].is_ascii_whitespace() && bytes[j] != b'>' {
+                    j += 1;
+                }
+                (&tag[value_start..j], j)
+            }
+        };
+        i = value_end;
+
+        let field = format!("{}/@{}", path, name);
+        let value = decode_entities(value);
+        let masked = if name.starts_with("data-") {
+            visit(&field, &value)?
+        } else if name == "href" {
+            let scheme = CONTACT_SCHEMES.iter().find(|scheme| {
+                value
+                    .trim_start()
+                    .to_ascii_lowercase()
+                    .starts_with(**scheme)
+            });
+            match scheme {
+                Some(scheme) => {
+                    let split = value.len() - value.trim_start().len() + scheme.len();
+                    let target = visit(&field, &value[split..])?;
+                    format!("{}{}", &value[..split], target)
+                }
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+
+        if masked != value {
+            out.push_str(&tag[copied..value_start]);
+            out.push('"');
+            out.push_str(&escape(&masked, true));
+            out.push('"');
+            copied = value_end;
+        }
+    }
+
+    out.push_str(&tag[copied..]);
+    Ok(out)
+}
+
+/// Decodes `NAMED_ENTITIES` and numeric character references.
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        match known_entity(rest) {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The character and length of a decodable entity at the start of `s`.
+fn known_entity(s: &str) -> Option<(char, usize)> {
+    let end = s.find(';')?;
+    let body = &s[1..end];
+    let c = if let Some(hex) = body.strip_prefix("#x").or_else(|| body.strip_prefix("#X")) {
+        char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+    } else if let Some(decimal) = body.strip_prefix('#') {
+        char::from_u32(decimal.parse().ok()?)?
+    } else {
+        NAMED_ENTITIES
+            .iter()
+            .find(|(name, _)| *name == body)
+            .map(|&(_, c)| c)?
+    };
+    Some((c, end + 1))
+}
+
+/// Escapes `s` for HTML text, or for a double-quoted attribute value when
+/// `attribute` is set. An `&` starting a named entity `decode_entities`
+/// left alone is kept, so such entities survive a decode/escape round trip.
+fn escape(s: &str, attribute: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for (index, c) in s.char_indices() {
+        match c {
+            '&' if !is_unknown_entity(&s[index..]) => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn is_unknown_entity(s: &str) -> bool {
+    let Some(end) = s.find(';') else {
+        return false;
+    };
+    let body = &s[1..end];
+    body.starts_with(|c: char| c.is_ascii_alphabetic())
+        && body.chars().all(|c| c.is_ascii_alphanumeric())
+        && known_entity(s).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataCloakConfig, DataCloakEngine, MaskingStrategy};
+
+    #[test]
+    fn test_mask_html_keeps_markup() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let html = "<!DOCTYPE html><html><body class=x>\n\
+                    <p>Write to jo&#64;example.com &copy; Jo &amp; Co<br></p>\
+                    <a href=\"mailto:jo@example.com?subject=hi\" title=\"jo@example.com\">mail</a>\
+                    <div data-owner=jo@example.com data-id='7'></div>\
+                    <script>var s = \"123-45-6789\";</script></body></html>";
+
+        let result = engine.mask_html(html).unwrap();
+        assert_eq!(
+            result.masked,
+            "<!DOCTYPE html><html><body class=x>\n\
+             <p>Write to [EMAIL_1] &copy; Jo &amp; Co<br></p>\
+             <a href=\"mailto:[EMAIL_1]?subject=hi\" title=\"jo@example.com\">mail</a>\
+             <div data-owner=\"[EMAIL_1]\" data-id='7'></div>\
+             <script>var s = \"123-45-6789\";</script></body></html>"
+        );
+        let fields: Vec<&str> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.field_name.as_str())
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "/html/body/p",
+                "/html/body/a/@href",
+                "/html/body/div/@data-owner"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_pii_html_skips_tags() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let findings = engine
+            .detect_pii_html("<img alt=O'Brien src=\"a@example.com.png\"><p>ssn 123-45-6789 < 5")
+            .unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].field_name, "/p");
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::Instant;
+
+use tracing::span::EnteredSpan;
+
+use crate::DataCloakEngine;
+
+/// Bytes of input included, scrubbed, in slow-pattern events.
+const PREVIEW_BYTES: usize = 80;
+
+/// Span covering one pattern pass. On drop it records the pass duration and,
+/// when the pass took longer than `regex_timeout_ms`, emits a warning with a
+/// scrubbed preview of the input so the offending content can be identified.
+pub(crate) struct PatternTrace<'a> {
+    span: EnteredSpan,
+    started: Instant,
+    engine: &'a DataCloakEngine,
+    pii_type: &'a str,
+    text: &'a str,
+}
+
+impl<'a> PatternTrace<'a> {
+    pub(crate) fn start(engine: &'a DataCloakEngine, pii_type: &'a str, text: &'a str) -> Self {
+        let span = tracing::trace_span!(
+            "datacloak.pattern",
+            pii_type,
+            elapsed_us = tracing::field::Empty
+        )
+        .entered();
+        Self {
+            span,
+            started: Instant::now(),
+            engine,
+            pii_type,
+            text,
+        }
+    }
+}
+
+impl Drop for PatternTrace<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        self.span.record("elapsed_us", elapsed.as_micros() as u64);
+        if elapsed.as_millis() as u64 > self.engine.config.regex_timeout_ms {
+            tracing::warn!(
+                pii_type = self.pii_type,
+                elapsed_ms = elapsed.as_millis() as u64,
+                bytes = self.text.len(),
+                preview = %self.engine.scrubbed_preview(self.text),
+                "slow pattern pass"
+            );
+        }
+    }
+}
+
+impl DataCloakEngine {
+    /// The start of `text` with everything any pattern matches replaced by
+    /// `[type]`. Validation is skipped on purpose: over-scrubbing a log line
+    /// is harmless, leaking a value into it is not.
+    pub(crate) fn scrubbed_preview(&self, text: &str) -> String {
+        let mut end = text.len().min(PREVIEW_BYTES);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut preview = text[..end].to_string();
+        for (pii_type, pattern) in self.patterns.matching(&text[..end]) {
+            preview = pattern
+                .replace_all(&preview, format!("[{}]", pii_type).as_str())
+                .into_owned();
+        }
+        preview
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataCloakConfig, DataCloakEngine};
+
+    #[test]
+    fn test_preview_scrubs_every_match() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let preview = engine.scrubbed_preview(&format!(
+            "ssn 123-45-6789 mail x@example.com {}",
+            "tail ".repeat(40)
+        ));
+        assert!(preview.starts_with("ssn [ssn] mail [email] tail"));
+        assert!(preview.len() <= 80);
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::document::FieldTotals;
+use crate::jsonpath::{JsonPath, PathToken};
+use crate::{
+    ConfidenceExplanation, ConfidenceSignal, DataCloakConfig, DataCloakEngine, DataCloakError,
+    Detections, MaskingMetadata, MaskingResult, MaskingStrategy, PIIDetectionResult,
+    ValidationCounts,
+};
+
+/// A JSON document with its string values masked.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonMaskingResult {
+    /// The input document with every string leaf masked. Keys, numbers and
+    /// booleans are left untouched.
+    pub masked: Value,
+    /// Findings with `field_name` set to the JSON pointer of their string and
+    /// offsets relative to that string.
+    pub detected_pii: Vec<PIIDetectionResult>,
+    /// Totals over all strings; `fields_processed` counts the string leaves.
+    pub metadata: MaskingMetadata,
+    pub token_map: HashMap<String, String>,
+}
+
+/// Compiled `json_mask_paths` and `json_skip_paths`.
+#[derive(Debug, Default)]
+pub(crate) struct JsonRules {
+    /// Most specific expression first, so nested rules win over broad ones.
+    mask: Vec<(JsonPath, String)>,
+    skip: Vec<JsonPath>,
+}
+
+/// How a JSON value is handled under the configured rules.
+enum JsonAction<'r> {
+    Scan,
+    Skip,
+    Mask(&'r str),
+}
+
+impl JsonRules {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Result<Self, DataCloakError> {
+        let mut mask: Vec<(&String, &String)> = config.json_mask_paths.iter().collect();
+        mask.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(b.0)));
+        Ok(Self {
+            mask: mask
+                .into_iter()
+                .map(|(path, pii_type)| Ok((JsonPath::parse(path)?, pii_type.clone())))
+                .collect::<Result<_, DataCloakError>>()?,
+            skip: config
+                .json_skip_paths
+                .iter()
+                .map(|path| JsonPath::parse(path))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn action(&self, path: &[PathToken]) -> JsonAction<'_> {
+        if self.skip.iter().any(|rule| rule.covers(path)) {
+            return JsonAction::Skip;
+        }
+        match self.mask.iter().find(|(rule, _)| rule.covers(path)) {
+            Some((_, pii_type)) => JsonAction::Mask(pii_type),
+            None => JsonAction::Scan,
+        }
+    }
+}
+
+impl DataCloakEngine {
+    /// Detects PII in every string value of `document`, however deeply
+    /// nested. Each finding's `field_name` is the JSON pointer of the string
+    /// it was found in, e.g. `/customers/0/email`. Values under
+    /// `json_skip_paths` are ignored and values under `json_mask_paths` are
+    /// reported whole.
+    pub fn detect_pii_json(
+        &self,
+        document: &Value,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut findings = Vec::new();
+        visit_leaves(document, &mut JsonCursor::default(), &mut |cursor, leaf| {
+            let text = match (self.json_rules.action(&cursor.tokens), leaf) {
+                (JsonAction::Skip, _) => return Ok(()),
+                (JsonAction::Mask(pii_type), leaf) => {
+                    if let Some(text) = scalar_text(leaf) {
+                        findings.push(self.whole_value_finding(&text, pii_type, &cursor.pointer));
+                    }
+                    return Ok(());
+                }
+                (JsonAction::Scan, Value::String(text)) => text,
+                _ => return Ok(()),
+            };
+            for mut pii in self.detect_pii(text)? {
+                pii.field_name = cursor.pointer.clone();
+                findings.push(pii);
+            }
+            Ok(())
+        })?;
+        Ok(findings)
+    }
+
+    /// Masks every string value of `document` in place of flattening it to
+    /// text, so the result has the same structure as the input. Values under
+    /// `json_mask_paths` are masked whole; numbers there become strings.
+    pub fn mask_json(&self, document: &Value) -> Result<JsonMaskingResult, DataCloakError> {
+        let mut masked = document.clone();
+        let mut totals = FieldTotals::new();
+
+        visit_leaves_mut(
+            &mut masked,
+            &mut JsonCursor::default(),
+            &mut |cursor, leaf| {
+                let result = match self.json_rules.action(&cursor.tokens) {
+                    JsonAction::Skip => return Ok(()),
+                    JsonAction::Mask(pii_type) => match scalar_text(leaf) {
+                        Some(text) => self.mask_whole_value(&text, pii_type, &cursor.pointer)?,
+                        None => return Ok(()),
+                    },
+                    JsonAction::Scan => match leaf {
+                        Value::String(text) => self.mask_text(text)?,
+                        _ => return Ok(()),
+                    },
+                };
+                *leaf = Value::String(totals.add(&cursor.pointer, result));
+                Ok(())
+            },
+        )?;
+
+        let totals = totals.finish();
+        Ok(JsonMaskingResult {
+            masked,
+            detected_pii: totals.detected_pii,
+            metadata: totals.metadata,
+            token_map: totals.token_map,
+        })
+    }
+
+    fn whole_value_finding(&self, text: &str, pii_type: &str, field: &str) -> PIIDetectionResult {
+        let normalized = self.normalized_value(pii_type, text);
+        PIIDetectionResult {
+            field_name: field.to_string(),
+            pii_type: pii_type.to_string(),
+            confidence: 1.0,
+            sample: text.to_string(),
+            masked: self.mask_value(normalized.as_deref().unwrap_or(text), pii_type),
+            start: 0,
+            end: text.len(),
+            snippet: None,
+            explanation: self.config.explain_confidence.then(|| {
+                ConfidenceExplanation::new(ConfidenceSignal::MaskPath {
+                    path: field.to_string(),
+                    confidence: 1.0,
+                })
+            }),
+            low_confidence: false,
+            normalized,
+        }
+    }
+
+    /// Masks all of `text` as one `pii_type` value, the way `mask_text`
+    /// masks a detected value.
+    pub(crate) fn mask_whole_value(
+        &self,
+        text: &str,
+        pii_type: &str,
+        field: &str,
+    ) -> Result<MaskingResult, DataCloakError> {
+        let start_time = std::time::Instant::now();
+        let mut detected = Detections {
+            results: vec![self.whole_value_finding(text, pii_type, field)],
+            limits_exceeded: false,
+            warnings: Vec::new(),
+            validation: ValidationCounts::default(),
+            elapsed: std::time::Duration::ZERO,
+        };
+
+        let placeholders = matches!(self.config.masking_strategy, MaskingStrategy::Placeholder);
+        if placeholders {
+            self.assign_placeholders(text, &mut detected.results)?;
+        }
+        Ok(self.apply_masks(text, detected, start_time, placeholders))
+    }
+}
+
+/// Location of the value being visited, as JSONPath tokens for rule matching
+/// and as an RFC 6901 pointer for reporting.
+#[derive(Debug, Default)]
+struct JsonCursor {
+    tokens: Vec<PathToken>,
+    pointer: String,
+}
+
+impl JsonCursor {
+    fn push(&mut self, token: PathToken) -> usize {
+        let len = self.pointer.len();
+        self.pointer.push('/');
+        match &token {
+            PathToken::Key(key) => self
+                .pointer
+                .push_str(&key.replace('~', "~0").replace('/', "~1")),
+            PathToken::Index(index) => self.pointer.push_str(&index.to_string()),
+        }
+        self.tokens.push(token);
+        len
+    }
+
+    fn pop(&mut self, len: usize) {
+        self.tokens.pop();
+        self.pointer.truncate(len);
+    }
+}
+
+/// Text of a string or number value; other values are never masked.
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// Calls `visit` with the location of every non-container value.
+fn visit_leaves(
+    value: &Value,
+    cursor: &mut JsonCursor,
+    visit: &mut dyn FnMut(&JsonCursor, &Value) -> Result<(), DataCloakError>,
+) -> Result<(), DataCloakError> {
+    match value {
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let len = cursor.push(PathToken::Index(index));
+                visit_leaves(item, cursor, visit)?;
+                cursor.pop(len);
+            }
+            Ok(())
+        }
+        Value::Object(fields) => {
+            for (key, item) in fields {
+                let len = cursor.push(PathToken::Key(key.clone()));
+                visit_leaves(item, cursor, visit)?;
+                cursor.pop(len);
+            }
+            Ok(())
+        }
+        leaf => visit(cursor, leaf),
+    }
+}
+
+/// `visit_leaves` over a mutable document, letting `visit` rewrite leaves.
+fn visit_leaves_mut(
+    value: &mut Value,
+    cursor: &mut JsonCursor,
+    visit: &mut dyn FnMut(&JsonCursor, &mut Value) -> Result<(), DataCloakError>,
+) -> Result<(), DataCloakError> {
+    match value {
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                let len = cursor.push(PathToken::Index(index));
+                visit_leaves_mut(item, cursor, visit)?;
+                cursor.pop(len);
+            }
+            Ok(())
+        }
+        Value::Object(fields) => {
+            for (key, item) in fields.iter_mut() {
+                let len = cursor.push(PathToken::Key(key.clone()));
+                visit_leaves_mut(item, cursor, visit)?;
+                cursor.pop(len);
+            }
+            Ok(())
+        }
+        leaf => visit(cursor, leaf),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_findings_carry_pointers() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let document = json!({
+            "customers": [{ "contact": "jane@example.com", "age": 41 }],
+            "notes/private": "call 555-123-4567",
+        });
+
+        let mut pointers: Vec<String> = engine
+            .detect_pii_json(&document)
+            .unwrap()
+            .into_iter()
+            .map(|pii| pii.field_name)
+            .collect();
+        pointers.sort();
+        assert_eq!(pointers, vec!["/customers/0/contact", "/notes~1private"]);
+    }
+
+    #[test]
+    fn test_mask_json_keeps_structure() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let document = json!({ "user": { "email": "jane@example.com", "id": 7 }, "tags": ["ok"] });
+
+        let result = engine.mask_json(&document).unwrap();
+        assert_eq!(result.masked["user"]["email"], "j***@example.com");
+        assert_eq!(result.masked["user"]["id"], 7);
+        assert_eq!(result.masked["tags"][0], "ok");
+        assert_eq!(result.metadata.fields_processed, 2);
+        assert_eq!(result.detected_pii[0].field_name, "/user/email");
+    }
+
+    #[test]
+    fn test_json_path_rules_mask_and_skip() {
+        let config = DataCloakConfig::builder()
+            .json_mask_path("$.customer.*", "customer")
+            .json_skip_path("$.metadata")
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let document = json!({
+            "customer": { "name": "Jane Roe", "id": 1234 },
+            "metadata": { "owner": "ops@example.com" },
+            "note": "cc ops@example.com",
+        });
+
+        let result = engine.mask_json(&document).unwrap();
+        assert_eq!(result.masked["customer"]["name"], "***");
+        assert_eq!(result.masked["customer"]["id"], "***");
+        assert_eq!(result.masked["metadata"]["owner"], "ops@example.com");
+        assert_eq!(result.masked["note"], "cc o***@example.com");
+
+        let findings = engine.detect_pii_json(&document).unwrap();
+        assert!(findings
+            .iter()
+            .all(|pii| !pii.field_name.starts_with("/metadata")));
+        assert_eq!(
+            findings
+                .iter()
+                .filter(|pii| pii.pii_type == "customer")
+                .count(),
+            2
+        );
+
+        assert!(DataCloakConfig::builder()
+            .json_skip_path("metadata")
+            .build()
+            .is_err());
+    }
+}
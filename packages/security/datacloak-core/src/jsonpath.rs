@@ -0,0 +1,151 @@
+use crate::error::DataCloakError;
+
+/// One step from a JSON value to a child, as walked by the JSON scanner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathToken {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    Key(String),
+    Index(usize),
+    /// `*` or `[*]`: any one child.
+    Wildcard,
+    /// `..`: zero or more levels of any children.
+    Descendants,
+}
+
+/// A parsed JSONPath expression, limited to the subset useful for scoping
+/// masking rules: `$`, `.key`, `['key']`, `[0]`, `*`, `[*]` and `..key`.
+/// Filters and slices are not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JsonPath {
+    selectors: Vec<Selector>,
+}
+
+impl JsonPath {
+    pub(crate) fn parse(expression: &str) -> Result<Self, DataCloakError> {
+        let invalid = |reason: &str| {
+            DataCloakError::InvalidConfig(format!("Invalid JSONPath '{}': {}", expression, reason))
+        };
+
+        let rest = expression
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with '$'"))?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut selectors = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    i += 1;
+                    if chars.get(i) == Some(&'.') {
+                        selectors.push(Selector::Descendants);
+                        i += 1;
+                    }
+                    let start = i;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    let name: String = chars[start..i].iter().collect();
+                    match name.as_str() {
+                        "" if selectors.last() == Some(&Selector::Descendants) => {}
+                        "" => return Err(invalid("empty member name")),
+                        "*" => selectors.push(Selector::Wildcard),
+                        _ => selectors.push(Selector::Key(name)),
+                    }
+                }
+                '[' => {
+                    let close = chars[i..]
+                        .iter()
+                        .position(|&c| c == ']')
+                        .ok_or_else(|| invalid("unclosed '['"))?;
+                    let inner: String = chars[i + 1..i + close].iter().collect();
+                    let inner = inner.trim();
+                    let quoted = (inner.starts_with('\'') && inner.ends_with('\''))
+                        || (inner.starts_with('"') && inner.ends_with('"'));
+                    if inner == "*" {
+                        selectors.push(Selector::Wildcard);
+                    } else if quoted && inner.len() >= 2 {
+                        selectors.push(Selector::Key(inner[1..inner.len() - 1].to_string()));
+                    } else {
+                        let index = inner.parse().map_err(|_| {
+                            invalid("brackets must hold an index, '*' or a quoted key")
+                        })?;
+                        selectors.push(Selector::Index(index));
+                    }
+                    i += close + 1;
+                }
+                _ => return Err(invalid("expected '.' or '['")),
+            }
+        }
+
+        Ok(Self { selectors })
+    }
+
+    /// Whether the value at `path` or one of its ancestors is selected, so a
+    /// rule on `$.customer` covers everything inside the customer object.
+    pub(crate) fn covers(&self, path: &[PathToken]) -> bool {
+        (0..=path.len()).any(|len| matches_exactly(&self.selectors, &path[..len]))
+    }
+}
+
+fn matches_exactly(selectors: &[Selector], path: &[PathToken]) -> bool {
+    let Some((selector, rest)) = selectors.split_first() else {
+        return path.is_empty();
+    };
+    match selector {
+        Selector::Descendants => (0..=path.len()).any(|skip| matches_exactly(rest, &path[skip..])),
+        _ => match path.split_first() {
+            Some((token, remaining)) => {
+                let selected = match (selector, token) {
+                    (Selector::Wildcard, _) => true,
+                    (Selector::Key(key), PathToken::Key(name)) => key == name,
+                    (Selector::Index(index), PathToken::Index(position)) => index == position,
+                    _ => false,
+                };
+                selected && matches_exactly(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(tokens: &[&str]) -> Vec<PathToken> {
+        tokens
+            .iter()
+            .map(|t| match t.parse() {
+                Ok(index) => PathToken::Index(index),
+                Err(_) => PathToken::Key(t.to_string()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_jsonpath_selection() {
+        let customer = JsonPath::parse("$.customer.*").unwrap();
+        assert!(customer.covers(&path(&["customer", "name"])));
+        assert!(customer.covers(&path(&["customer", "address", "city"])));
+        assert!(!customer.covers(&path(&["customer"])));
+        assert!(!customer.covers(&path(&["vendor", "name"])));
+
+        let emails = JsonPath::parse("$..email").unwrap();
+        assert!(emails.covers(&path(&["users", "3", "email"])));
+        assert!(emails.covers(&path(&["email"])));
+
+        let first = JsonPath::parse("$['orders'][0]").unwrap();
+        assert!(first.covers(&path(&["orders", "0", "id"])));
+        assert!(!first.covers(&path(&["orders", "1", "id"])));
+
+        assert!(JsonPath::parse("customer").is_err());
+        assert!(JsonPath::parse("$.orders[?(@.id)]").is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, CommitMode, Consumer};
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::Message;
+use rdkafka::producer::{BaseProducer, BaseRecord, DeliveryResult, Producer, ProducerContext};
+use rdkafka::ClientContext;
+use serde::{Deserialize, Serialize};
+
+use crate::{CancellationToken, DataCloakEngine, DataCloakError};
+
+/// How long `scrub_kafka` waits for a message before checking for
+/// cancellation again.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a commit point waits for outstanding deliveries.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where `scrub_kafka` reads from and writes to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KafkaScrubConfig {
+    /// `bootstrap.servers` for both clients.
+    pub brokers: String,
+    /// Consumer group; offsets are committed under it.
+    pub group_id: String,
+    pub source_topic: String,
+    pub sink_topic: String,
+    /// Extra librdkafka properties set on both clients, such as
+    /// `security.protocol` or `sasl.username`.
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+    /// Messages republished between offset commits.
+    pub commit_every: usize,
+}
+
+impl KafkaScrubConfig {
+    pub fn new(brokers: &str, group_id: &str, source_topic: &str, sink_topic: &str) -> Self {
+        Self {
+            brokers: brokers.to_string(),
+            group_id: group_id.to_string(),
+            source_topic: source_topic.to_string(),
+            sink_topic: sink_topic.to_string(),
+            properties: HashMap::new(),
+            commit_every: 1000,
+        }
+    }
+}
+
+/// Totals from `scrub_kafka`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KafkaScrubReport {
+    /// Messages republished, including tombstones.
+    pub messages: u64,
+    /// Payloads masked with `mask_json`.
+    pub json_payloads: u64,
+    /// Payloads masked as plain text.
+    pub text_payloads: u64,
+    /// Payloads forwarded unchanged because they are not UTF-8.
+    pub binary_payloads: u64,
+    pub counts_by_type: HashMap<String, u64>,
+    /// Set when any payload hit a match limit or the memory budget.
+    pub limits_exceeded: bool,
+}
+
+/// How a payload was masked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PayloadKind {
+    Json,
+    Text,
+    Binary,
+}
+
+impl DataCloakEngine {
+    /// Consumes `source_topic`, masks each payload and republishes it to
+    /// `sink_topic` until `cancel` is cancelled. Payloads that parse as JSON
+    /// are masked with `mask_json`, other UTF-8 payloads as text, and
+    /// binary payloads are forwarded unchanged; keys, headers and
+    /// tombstones are kept.
+    ///
+    /// Delivery is at least once: auto-commit is disabled, and offsets are
+    /// committed only after every message consumed before them has been
+    /// acknowledged by the sink, every `commit_every` messages and on
+    /// shutdown. A failed delivery stops the run without committing, so a
+    /// restart re-reads the uncommitted messages and the sink may see some
+    /// of them twice.
+    pub fn scrub_kafka(
+        &self,
+        config: &KafkaScrubConfig,
+        cancel: &CancellationToken,
+    ) -> Result<KafkaScrubReport, DataCloakError> {
+        if config.commit_every == 0 {
+            return Err(DataCloakError::InvalidConfig(
+                "commit_every must be at least 1".to_string(),
+            ));
+        }
+        let consumer: BaseConsumer = client_config(config)
+            .set("group.id", &config.group_id)
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .map_err(kafka_error)?;
+        consumer
+            .subscribe(&[config.source_topic.as_str()])
+            .map_err(kafka_error)?;
+        let producer: BaseProducer<DeliveryTracker> = client_config(config)
+            .set("enable.idempotence", "true")
+            .create_with_context(DeliveryTracker::default())
+            .map_err(kafka_error)?;
+
+        let mut report = KafkaScrubReport::default();
+        let mut uncommitted = 0;
+        while !cancel.is_cancelled() {
+            producer.poll(Duration::ZERO);
+            let Some(message) = consumer.poll(POLL_INTERVAL) else {
+                continue;
+            };
+            let message = message.map_err(kafka_error)?;
+
+            let masked = match message.payload() {
+                Some(payload) => Some(self.scrub_payload(payload, &mut report)?),
+                None => None,
+            };
+            let mut record: BaseRecord<'_, [u8], Vec<u8>> = BaseRecord::to(&config.sink_topic);
+            if let Some(key) = message.key() {
+                record = record.key(key);
+            }
+            if let Some(masked) = &masked {
+                record = record.payload(masked);
+            }
+            if let Some(headers) = message.headers() {
+                record = record.headers(headers.detach());
+            }
+            send(&producer, record)?;
+            report.messages += 1;
+
+            uncommitted += 1;
+            if uncommitted >= config.commit_every {
+                commit(&consumer, &producer)?;
+                uncommitted = 0;
+            }
+        }
+        if uncommitted > 0 {
+            commit(&consumer, &producer)?;
+        }
+        Ok(report)
+    }
+
+    /// Masks one payload and adds its findings to `report`.
+    fn scrub_payload(
+        &self,
+        payload: &[u8],
+        report: &mut KafkaScrubReport,
+    ) -> Result<Vec<u8>, DataCloakError> {
+        let (masked, kind, findings, limited) = self.mask_payload(payload)?;
+        match kind {
+            PayloadKind::Json => report.json_payloads += 1,
+            PayloadKind::Text => report.text_payloads += 1,
+            PayloadKind::Binary => report.binary_payloads += 1,
+        }
+        for pii_type in findings {
+            *report.counts_by_type.entry(pii_type).or_insert(0) += 1;
+        }
+        report.limits_exceeded |= limited;
+        Ok(masked)
+    }
+
+    /// The masked payload, how it was read, the types found and whether a
+    /// limit was hit.
+    pub(crate) fn mask_payload(
+        &self,
+        payload: &[u8],
+    ) -> Result<(Vec<u8>, PayloadKind, Vec<String>, bool), DataCloakError> {
+        let Ok(text) = std::str::from_utf8(payload) else {
+            return Ok((payload.to_vec(), PayloadKind::Binary, Vec::new(), false));
+        };
+        let findings = |pii: Vec<crate::PIIDetectionResult>| {
+            pii.into_iter().map(|p| p.pii_type).collect::<Vec<_>>()
+        };
+
+        if let Ok(document) = serde_json::from_str::<serde_json::Value>(text) {
+            let result = self.mask_json(&document)?;
+            let masked = serde_json::to_vec(&result.masked)
+                .map_err(|e| DataCloakError::Internal(e.to_string()))?;
+            let limited = result.metadata.limits_exceeded;
+            return Ok((
+                masked,
+                PayloadKind::Json,
+                findings(result.detected_pii),
+                limited,
+            ));
+        }
+        let result = self.mask_text(text)?;
+        let limited = result.metadata.limits_exceeded;
+        Ok((
+            result.masked_text.into_bytes(),
+            PayloadKind::Text,
+            findings(result.detected_pii),
+            limited,
+        ))
+    }
+}
+
+/// Records the first failed delivery reported to the producer.
+#[derive(Default)]
+struct DeliveryTracker {
+    failure: Mutex<Option<String>>,
+}
+
+impl ClientContext for DeliveryTracker {}
+
+impl ProducerContext for DeliveryTracker {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _opaque: ()) {
+        if let Err((e, _)) = result {
+            if let Ok(mut failure) = self.failure.lock() {
+                failure.get_or_insert_with(|| e.to_string());
+            }
+        }
+    }
+}
+
+fn client_config(config: &KafkaScrubConfig) -> ClientConfig {
+    let mut client = ClientConfig::new();
+    client.set("bootstrap.servers", &config.brokers);
+    for (key, value) in &config.properties {
+        client.set(key, value);
+    }
+    client
+}
+
+/// Queues `record`, waiting for room while the producer queue is full.
+fn send(
+    producer: &BaseProducer<DeliveryTracker>,
+    mut record: BaseRecord<'_, [u8], Vec<u8>>,
+) -> Result<(), DataCloakError> {
+    loop {
+        match producer.send(record) {
+            Ok(()) => return Ok(()),
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+                producer.poll(Duration::from_millis(100));
+                record = returned;
+            }
+            Err((e, _)) => return Err(kafka_error(e)),
+        }
+    }
+}
+
+/// Waits until every queued message is acknowledged, then commits the
+/// consumer's position.
+fn commit(
+    consumer: &BaseConsumer,
+    producer: &BaseProducer<DeliveryTracker>,
+) -> Result<(), DataCloakError> {
+    producer.flush(FLUSH_TIMEOUT).map_err(kafka_error)?;
+    let failure = producer
+        .context()
+        .failure
+        .lock()
+        .map_err(|_| DataCloakError::Internal("Delivery tracker lock poisoned".to_string()))?
+        .clone();
+    if let Some(failure) = failure {
+        return Err(DataCloakError::Io(format!(
+            "Kafka delivery failed: {}",
+            failure
+        )));
+    }
+    consumer
+        .commit_consumer_state(CommitMode::Sync)
+        .map_err(kafka_error)
+}
+
+fn kafka_error(e: KafkaError) -> DataCloakError {
+    DataCloakError::Io(format!("Kafka: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    #[test]
+    fn test_mask_payload_is_json_aware() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let (masked, kind, findings, _) = engine
+            .mask_payload(br#"{"user":"a@example.com","n":1}"#)
+            .unwrap();
+        assert_eq!(kind, PayloadKind::Json);
+        let masked: serde_json::Value = serde_json::from_slice(&masked).unwrap();
+        assert_eq!(masked["user"], "[EMAIL_1]");
+        assert_eq!(masked["n"], 1);
+        assert_eq!(findings, ["email"]);
+
+        let (masked, kind, _, _) = engine.mask_payload(b"mail a@example.com").unwrap();
+        assert_eq!(kind, PayloadKind::Text);
+        assert_eq!(masked, b"mail [EMAIL_1]");
+
+        let binary = b"\xff\xfe a@example.com";
+        let (masked, kind, _, _) = engine.mask_payload(binary).unwrap();
+        assert_eq!(kind, PayloadKind::Binary);
+        assert_eq!(masked, binary);
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use crate::language::{detect_language, Language};
+use crate::DataCloakConfig;
+
+/// Added to the confidence of a match with a boost keyword nearby.
+const KEYWORD_BOOST: f64 = 0.04;
+
+/// Ceiling for boosted confidence, so context alone never makes a match
+/// certain.
+const MAX_BOOSTED_CONFIDENCE: f64 = 0.99;
+
+/// Factor applied to a match with only a penalty keyword nearby. A valid
+/// match stays above the default threshold; one that also failed
+/// validation drops below it.
+const KEYWORD_PENALTY: f64 = 0.7;
+
+type KeywordTable = HashMap<String, Vec<String>>;
+
+/// Compiled `boost_keywords` and `penalty_keywords`, with their localized
+/// counterparts.
+#[derive(Debug, Default)]
+pub(crate) struct KeywordScorer {
+    window: usize,
+    boost: KeywordTable,
+    penalty: KeywordTable,
+    localized_boost: HashMap<Language, KeywordTable>,
+    localized_penalty: HashMap<Language, KeywordTable>,
+    detect_language: bool,
+}
+
+impl KeywordScorer {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Self {
+        let lowercase = |table: &KeywordTable| -> KeywordTable {
+            table
+                .iter()
+                .map(|(pii_type, words)| {
+                    let words = words.iter().map(|w| w.trim().to_lowercase()).collect();
+                    (pii_type.clone(), words)
+                })
+                .collect()
+        };
+        let localized = |tables: &HashMap<Language, KeywordTable>| {
+            tables
+                .iter()
+                .map(|(&language, table)| (language, lowercase(table)))
+                .collect()
+        };
+        Self {
+            window: config.keyword_window,
+            boost: lowercase(&config.boost_keywords),
+            penalty: lowercase(&config.penalty_keywords),
+            localized_boost: localized(&config.localized_boost_keywords),
+            localized_penalty: localized(&config.localized_penalty_keywords),
+            detect_language: config.language_detection,
+        }
+    }
+
+    /// The language whose localized keywords apply to `text`, `None` for
+    /// all of them. Texts are only examined when there is a choice to make.
+    pub(crate) fn language(&self, text: &str) -> Option<Language> {
+        let localized = !self.localized_boost.is_empty() || !self.localized_penalty.is_empty();
+        if self.window == 0 || !self.detect_language || !localized {
+            return None;
+        }
+        detect_language(text)
+    }
+
+    /// Adjusts the confidence of a `pii_type` match at `start..end` of
+    /// `text` for the keywords around it. `language` is as returned by
+    /// `language`.
+    pub(crate) fn score(
+        &self,
+        text: &str,
+        language: Option<Language>,
+        pii_type: &str,
+        start: usize,
+        end: usize,
+        confidence: f64,
+    ) -> f64 {
+        match self.nearby(text, language, pii_type, start, end) {
+            Some((_, effect)) => effect.apply(confidence),
+            None => confidence,
+        }
+    }
+
+    /// The keyword deciding the score of a `pii_type` match at `start..end`
+    /// of `text`: the first boost keyword in the window, else the first
+    /// penalty keyword. Localized keywords count after the built-in ones.
+    pub(crate) fn nearby<'s>(
+        &'s self,
+        text: &str,
+        language: Option<Language>,
+        pii_type: &str,
+        start: usize,
+        end: usize,
+    ) -> Option<(&'s str, KeywordEffect)> {
+        if self.window == 0 {
+            return None;
+        }
+        let lists = |base: &'s KeywordTable, localized: &'s HashMap<Language, KeywordTable>| {
+            let localized = localized
+                .iter()
+                .filter(move |&(l, _)| language.is_none_or(|language| *l == language))
+                .filter_map(move |(_, table)| table.get(pii_type));
+            base.get(pii_type).into_iter().chain(localized)
+        };
+        let boost: Vec<&'s Vec<String>> = lists(&self.boost, &self.localized_boost).collect();
+        let penalty: Vec<&'s Vec<String>> = lists(&self.penalty, &self.localized_penalty).collect();
+        if boost.is_empty() && penalty.is_empty() {
+            return None;
+        }
+
+        let before = &text[..start];
+        let before = match before.char_indices().rev().nth(self.window - 1) {
+            Some((i, _)) => &before[i..],
+            None => before,
+        };
+        let after = &text[end..];
+        let after = match after.char_indices().nth(self.window) {
+            Some((i, _)) => &after[..i],
+            None => after,
+        };
+        let nearby = [before.to_lowercase(), after.to_lowercase()];
+        let near = |lists: &[&'s Vec<String>]| -> Option<&'s String> {
+            lists
+                .iter()
+                .flat_map(|words| words.iter())
+                .find(|word| nearby.iter().any(|side| contains_word(side, word)))
+        };
+
+        if let Some(word) = near(&boost) {
+            Some((word.as_str(), KeywordEffect::Boost))
+        } else {
+            near(&penalty).map(|word| (word.as_str(), KeywordEffect::Penalty))
+        }
+    }
+}
+
+/// How a context keyword changes the confidence of a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeywordEffect {
+    Boost,
+    Penalty,
+}
+
+impl KeywordEffect {
+    pub(crate) fn apply(self, confidence: f64) -> f64 {
+        match self {
+            KeywordEffect::Boost => (confidence + KEYWORD_BOOST).min(MAX_BOOSTED_CONFIDENCE),
+            KeywordEffect::Penalty => confidence * KEYWORD_PENALTY,
+        }
+    }
+}
+
+/// Whether `word` occurs in `haystack` not directly joined to other letters
+/// or digits, so `tel` is found in `tel:` but not in `hotel`.
+fn contains_word(haystack: &str, word: &str) -> bool {
+    haystack.match_indices(word).any(|(i, _)| {
+        let joined_before = haystack[..i]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let joined_after = haystack[i + word.len()..]
+            .chars()
+            .next()
+            .is_some_and(char::is_alphanumeric);
+        !joined_before && !joined_after
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakEngine;
+
+    fn confidence(engine: &DataCloakEngine, text: &str) -> Option<f64> {
+        let found = engine.detect_pii(text).unwrap();
+        found
+            .iter()
+            .find(|pii| pii.pii_type == "phone")
+            .map(|pii| pii.confidence)
+    }
+
+    #[test]
+    fn test_nearby_keywords_move_confidence() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+
+        let plain = confidence(&engine, "reach 555-123-4567 today").unwrap();
+        let fax = confidence(&engine, "Fax: 555-123-4567").unwrap();
+        let order = confidence(&engine, "order 555-123-4567 shipped").unwrap();
+        assert_eq!(plain, 0.95);
+        assert_eq!(fax, MAX_BOOSTED_CONFIDENCE);
+        assert!(order < plain && order > 0.6);
+
+        // Inside another word, or outside the window, a keyword doesn't count
+        let hotel = confidence(&engine, "hotel 555-123-4567").unwrap();
+        assert_eq!(hotel, plain);
+        let far = format!("call me{}555-123-4567", " ".repeat(40));
+        assert_eq!(confidence(&engine, &far).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_keywords_are_configurable() {
+        let config = DataCloakConfig::builder()
+            .keyword_window(0)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        assert_eq!(confidence(&engine, "Fax: 555-123-4567").unwrap(), 0.95);
+
+        let config = DataCloakConfig::builder()
+            .boost_keywords("phone", &["Hotline"])
+            .penalty_keywords("phone", &["fax"])
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        assert_eq!(
+            confidence(&engine, "HOTLINE 555-123-4567").unwrap(),
+            MAX_BOOSTED_CONFIDENCE
+        );
+        assert!(confidence(&engine, "fax 555-123-4567").unwrap() < 0.95);
+
+        assert!(DataCloakConfig::builder()
+            .boost_keywords("ssn", &[" "])
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+/// Words examined when identifying the language of a text.
+const SAMPLE_WORDS: usize = 200;
+
+/// Evidence required before a language is reported.
+const MIN_SCORE: usize = 2;
+
+/// Languages with their own context keywords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    English,
+    French,
+    German,
+    Spanish,
+    Italian,
+    Portuguese,
+    Dutch,
+}
+
+/// Frequent words distinctive enough to tell the languages apart. Words
+/// shared between languages, like `de` or `que`, are listed for one or
+/// none of them.
+const STOPWORDS: &[(Language, &[&str])] = &[
+    (
+        Language::English,
+        &[
+            "the", "and", "is", "are", "was", "with", "for", "this", "that", "you", "my", "your",
+            "please", "of", "to",
+        ],
+    ),
+    (
+        Language::French,
+        &[
+            "le", "la", "les", "des", "du", "et", "est", "une", "pour", "avec", "dans", "qui",
+            "sur", "pas", "mon", "votre", "vous", "je", "au", "aux", "ce",
+        ],
+    ),
+    (
+        Language::German,
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "mit", "ein", "eine", "ich", "sie", "auf",
+            "für", "bitte", "mein", "ihre", "zu", "den", "dem",
+        ],
+    ),
+    (
+        Language::Spanish,
+        &[
+            "el", "los", "las", "y", "es", "con", "por", "para", "mi", "del", "está", "usted",
+            "muy", "pero",
+        ],
+    ),
+    (
+        Language::Italian,
+        &[
+            "il", "gli", "è", "di", "che", "non", "sono", "della", "mio", "suo", "questo", "anche",
+            "alla",
+        ],
+    ),
+    (
+        Language::Portuguese,
+        &[
+            "os", "não", "um", "meu", "você", "do", "da", "dos", "em", "seu", "obrigado",
+        ],
+    ),
+    (
+        Language::Dutch,
+        &[
+            "de", "het", "een", "van", "niet", "ik", "mijn", "voor", "zijn", "wij", "uw", "en",
+        ],
+    ),
+];
+
+/// Letters used by only one of the languages, each occurrence counting like
+/// a stopword.
+const LETTERS: &[(Language, &[char])] = &[
+    (Language::French, &['ç', 'œ']),
+    (Language::German, &['ß', 'ä', 'ö', 'ü']),
+    (Language::Spanish, &['ñ', '¿', '¡']),
+    (Language::Portuguese, &['ã', 'õ']),
+];
+
+/// Identifies the language of `text` from its most frequent words. `None`
+/// if the text is too short or too mixed to tell, e.g. a lone field value.
+pub fn detect_language(text: &str) -> Option<Language> {
+    let mut scores = [0usize; STOPWORDS.len()];
+    let words = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .take(SAMPLE_WORDS);
+    for word in words {
+        let word = word.to_lowercase();
+        for (score, (_, stopwords)) in scores.iter_mut().zip(STOPWORDS) {
+            if stopwords.contains(&word.as_str()) {
+                *score += 1;
+            }
+        }
+        for c in word.chars() {
+            let letter = LETTERS.iter().find(|(_, letters)| letters.contains(&c));
+            if let Some((language, _)) = letter {
+                let index = STOPWORDS.iter().position(|(l, _)| l == language);
+                scores[index.expect("every language has stopwords")] += 1;
+            }
+        }
+    }
+
+    let mut ranked: Vec<_> = STOPWORDS.iter().zip(scores).collect();
+    ranked.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    match ranked.as_slice() {
+        [((language, _), best), (_, second), ..] if *best >= MIN_SCORE && best > second => {
+            Some(*language)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, DataCloakEngine};
+
+    #[test]
+    fn test_detects_common_languages() {
+        assert_eq!(
+            detect_language("Please call me back, this is my mobile number."),
+            Some(Language::English)
+        );
+        assert_eq!(
+            detect_language("Voici le numéro de sécurité sociale pour le dossier."),
+            Some(Language::French)
+        );
+        assert_eq!(
+            detect_language("Bitte rufen Sie mich unter der Nummer an, die ich gesendet habe."),
+            Some(Language::German)
+        );
+        assert_eq!(detect_language("555-123-4567"), None);
+        assert_eq!(detect_language("Sécurité sociale"), None);
+    }
+
+    #[test]
+    fn test_keywords_follow_detected_language() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let confidence = |text: &str| engine.detect_pii(text).unwrap()[0].confidence;
+
+        // `portable` is a French phone keyword, but not an English one
+        let french = confidence("Vous pouvez appeler mon portable au 555-123-4567.");
+        let english = confidence("The portable 555-123-4567 was on the shelf.");
+        assert!(french > english);
+        assert_eq!(english, 0.95);
+
+        // Without enough text to tell, every language's keywords apply
+        assert_eq!(confidence("portable 555-123-4567"), french);
+
+        let config = DataCloakConfig::builder()
+            .language_detection(false)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        assert_eq!(
+            engine
+                .detect_pii("The portable 555-123-4567 was on the shelf.")
+                .unwrap()[0]
+                .confidence,
+            french
+        );
+    }
+}
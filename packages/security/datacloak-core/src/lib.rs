@@ -1,8 +1,174 @@
-use regex::Regex;
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
 use std::collections::HashMap;
-use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_void};
+use std::sync::{Arc, Mutex};
+use unicode_segmentation::UnicodeSegmentation;
+
+mod allowlist;
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "arrow")]
+mod arrow_batch;
+#[cfg(feature = "tokio")]
+mod async_api;
+mod base64;
+mod base64_payloads;
+#[cfg(feature = "parallel")]
+mod batch;
+mod cancellation;
+mod compression;
+mod config;
+mod config_env;
+mod config_file;
+mod confusables;
+mod context;
+mod denylist;
+mod document;
+mod eml;
+mod encoding;
+mod error;
+mod explain;
+mod ffi;
+mod format_preserving;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+mod hex;
+mod html;
+#[cfg(feature = "tracing")]
+mod instrument;
+mod json;
+mod jsonpath;
+#[cfg(feature = "kafka")]
+mod kafka;
+mod keywords;
+mod language;
+mod locales;
+mod logs;
+mod mapping;
+mod names;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod ndjson;
+#[cfg(feature = "ner")]
+mod ner;
+mod obfuscated;
+mod organizations;
+mod own_masks;
+#[cfg(feature = "parquet")]
+mod parquet_file;
+mod pattern_set;
+mod percent;
+#[cfg(feature = "phonenumber")]
+mod phone;
+mod policy;
+#[cfg(feature = "polars")]
+mod polars_frame;
+mod pool;
+mod presets;
+mod record;
+mod reidentification;
+mod sampling;
+mod scoring;
+mod serde_mask;
+#[cfg(feature = "server")]
+mod server;
+mod snippet;
+mod sql;
+mod stats;
+#[cfg(feature = "sqlite-vault")]
+mod sqlite_vault;
+mod streaming;
+mod synthetic;
+#[cfg(feature = "csv")]
+mod tabular;
+mod templates;
+mod tokenization;
+#[cfg(feature = "tracing-layer")]
+mod tracing_layer;
+mod views;
+#[cfg(feature = "xml")]
+mod xml;
+
+use format_preserving::FormatPreservingCipher;
+use mapping::PlaceholderRegistry;
+use pattern_set::{PatternSet, PatternSpec};
+use reidentification::{token_pattern, AuditHook};
+use templates::MaskTemplate;
+
+#[cfg(feature = "archive")]
+pub use archive::{ArchiveEntryReport, ArchiveFormat, ArchiveReport};
+#[cfg(feature = "arrow")]
+pub use arrow_batch::{BatchMaskingResult, ColumnFindings, ColumnSelection};
+pub use cancellation::CancellationToken;
+pub use compression::Compression;
+pub use config::{
+    CreditCardValidation, DataCloakConfig, DataCloakConfigBuilder, EmailValidation,
+    MaskingStrategy, PhoneValidation,
+};
+pub use config_file::ConfigFormat;
+pub use context::FieldContext;
+pub use denylist::parse_term_list;
+#[cfg(feature = "derive")]
+pub use datacloak_derive::DataCloak;
+pub use document::DocumentMaskingResult;
+pub use eml::AttachmentPolicy;
+pub use encoding::{char_offsets, decode_text, utf16_offsets, TextEncoding};
+pub use explain::{ConfidenceExplanation, ConfidenceSignal};
+pub use error::DataCloakError;
+pub use ffi::{
+    DataCloakFinding, DataCloakFindingList, DataCloakUtf16Buffer, DATACLOAK_ABI_VERSION,
+    DATACLOAK_ERR_CANCELLED, DATACLOAK_ERR_CRYPTO, DATACLOAK_ERR_INTERNAL,
+    DATACLOAK_ERR_INVALID_ARGUMENT, DATACLOAK_ERR_INVALID_CONFIG, DATACLOAK_ERR_IO,
+    DATACLOAK_ERR_MAPPING_CONFLICT, DATACLOAK_ERR_PATTERN_COMPILE, DATACLOAK_ERR_TEXT_TOO_LARGE,
+    DATACLOAK_ERR_TIMEOUT, DATACLOAK_ERR_VAULT, DATACLOAK_OK,
+};
+#[cfg(feature = "grpc")]
+pub use grpc::DataCloakGrpc;
+pub use json::JsonMaskingResult;
+#[cfg(feature = "kafka")]
+pub use kafka::{KafkaScrubConfig, KafkaScrubReport};
+pub use language::{detect_language, Language};
+pub use locales::{Locale, RegionProfile};
+pub use logs::LogFormat;
+pub use mapping::{MappingEntry, MergePolicy, MergeReport, TokenMapping};
+#[cfg(feature = "metrics")]
+pub use metrics::EngineMetrics;
+pub use names::parse_name_list;
+pub use ndjson::{NdjsonFinding, NdjsonLineReport, NdjsonReport};
+#[cfg(feature = "ner")]
+pub use ner::NerModel;
+#[cfg(feature = "parquet")]
+pub use parquet_file::ParquetReport;
+pub use policy::{Policy, PolicyAction, PolicyCondition, PolicyRule};
+pub use pool::EnginePool;
+pub use record::RecordMaskingResult;
+#[cfg(feature = "sqlite-vault")]
+pub use sqlite_vault::SqliteTokenVault;
+pub use reidentification::{ReidentificationAudit, ReidentificationEvent, Reidentifier};
+pub use sampling::{SampleReport, SampleStrategy, ScanMode};
+pub use scoring::{ConfidenceScorer, ScoreCandidate};
+pub use serde_mask::{FieldRule, FieldRules, MaskRules, Masked};
+#[cfg(feature = "server")]
+pub use server::{http_router, serve_http, ServerConfig};
+pub use stats::{ScanStats, SourceStats, StatsSnapshot};
+pub use streaming::{ScanProgress, StreamMaskingReport, StreamOptions, StreamScanner};
+#[cfg(feature = "csv")]
+pub use tabular::{ColumnProfile, CsvMaskingReport, CsvOptions};
+pub use tokenization::{InMemoryTokenVault, TokenVault};
+#[cfg(feature = "tracing-layer")]
+pub use tracing_layer::DataCloakLayer;
+pub use views::MaskingView;
+
+/// The built-in phone pattern, for NANP numbers like `201-555-0123`.
+const PHONE_PATTERN: &str = r"(?:\(?\d{3}\)?[-.\\s]?\d{3}[-.\\s]?\d{4}|\b\d{3}[-.\\s]?\d{3}[-.\\s]?\d{4})\b";
+
+/// Confidence of a plain pattern match.
+const PATTERN_CONFIDENCE: f64 = 0.95;
+
+/// Applied to a match that fails email or Luhn validation.
+const FAILED_VALIDATION_FACTOR: f64 = 0.7;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PIIDetectionResult {
@@ -11,6 +177,54 @@ pub struct PIIDetectionResult {
     pub confidence: f64,
     pub sample: String,
     pub masked: String,
+    /// Byte offsets of the match in the scanned text.
+    pub start: usize,
+    pub end: usize,
+    /// The text around the match with it and any other findings masked,
+    /// when `snippet_context_chars` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// The signals behind `confidence`, when `explain_confidence` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ConfidenceExplanation>,
+    /// Confidence is at or below the threshold for the type; only reported
+    /// when `report_low_confidence` is set.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub low_confidence: bool,
+    /// The value in canonical form: E.164 `+442079460958` for phones
+    /// validated with `PhoneValidation::PhoneNumber`, `john@example.com` for
+    /// `john [at] example [dot] com`, the folded form of a value found by
+    /// `normalize_unicode`, or the decoded value inside a `decode_base64`
+    /// payload or percent-encoded value. `masked` is derived from it, and
+    /// percent-encoded again where the value was; base64 payloads are masked
+    /// as a whole instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<String>,
+}
+
+/// A finding borrowed from the scanned text, returned by `find_pii`. Unlike
+/// `PIIDetectionResult` it holds no masked value and allocates nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PiiMatch<'a> {
+    pub pii_type: &'a str,
+    pub sample: &'a str,
+    pub confidence: f64,
+    /// Byte offsets of the match in the scanned text.
+    pub start: usize,
+    pub end: usize,
+    /// See `PIIDetectionResult::low_confidence`.
+    pub low_confidence: bool,
+}
+
+impl PIIDetectionResult {
+    /// Approximate heap and inline bytes held by this finding.
+    fn allocated_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.field_name.len()
+            + self.pii_type.len()
+            + self.sample.len()
+            + self.masked.len()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,165 +233,841 @@ pub struct MaskingResult {
     pub masked_text: String,
     pub detected_pii: Vec<PIIDetectionResult>,
     pub metadata: MaskingMetadata,
+    /// Token → original value for placeholder and tokenized masking. Each
+    /// distinct value maps to exactly one token within the document.
+    pub token_map: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl MaskingResult {
+    /// Replaces every token from `token_map` found in `text` (for example a
+    /// model reply mentioning `[EMAIL_1]`) with its original value.
+    pub fn rehydrate(&self, text: &str) -> String {
+        let mut tokens: Vec<(&String, &String)> = self.token_map.iter().collect();
+        // Longest first so `[EMAIL_1]` never clobbers part of `[EMAIL_12]`
+        tokens.sort_by_key(|token| Reverse(token.0.len()));
+
+        let mut rehydrated = text.to_string();
+        for (token, original) in tokens {
+            rehydrated = rehydrated.replace(token.as_str(), original);
+        }
+        rehydrated
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct MaskingMetadata {
+    /// Total milliseconds spent in the call.
     pub processing_time: u64,
+    /// Input fields masked; a plain text counts as one field.
     pub fields_processed: u32,
     pub pii_items_found: u32,
+    /// Findings per PII type.
+    #[serde(default)]
+    pub counts_by_type: HashMap<String, u32>,
+    /// Size of the input text in bytes.
+    #[serde(default)]
+    pub bytes_processed: usize,
+    /// Matches that failed validation, such as the Luhn check, and were
+    /// reported with reduced confidence.
+    #[serde(default)]
+    pub failed_validation: u32,
+    /// Matches dropped because validation left their confidence too low.
+    #[serde(default)]
+    pub suppressed_by_validation: u32,
+    /// Matches left alone because they are allowlisted, such as addresses
+    /// in `allowed_email_domains`.
+    #[serde(default)]
+    pub allowlisted: u32,
+    /// Matches left alone because they overlap an earlier mask, see
+    /// `skip_masked_values`.
+    #[serde(default)]
+    pub already_masked: u32,
+    /// Microseconds spent detecting PII.
+    #[serde(default)]
+    pub detection_time_us: u64,
+    /// Microseconds spent rewriting the text with the masked values.
+    #[serde(default)]
+    pub masking_time_us: u64,
+    /// Detection stopped at `max_matches_per_type`, `max_findings` or the
+    /// memory budget, so some values were neither reported nor masked.
+    #[serde(default)]
+    pub limits_exceeded: bool,
+    /// Why the scan was cut short, if it was.
+    #[serde(default)]
+    pub warnings: Vec<String>,
 }
 
-#[derive(Debug)]
-pub struct DataCloakEngine {
-    patterns: HashMap<String, Regex>,
-    config: DataCloakConfig,
+#[derive(Debug, Clone, Default)]
+pub struct DetectionOptions {
+    /// Collapse repeated occurrences of the same value into a single result.
+    pub deduplicate: bool,
+    /// Additionally return the results grouped by PII type.
+    pub group_by_type: bool,
+    /// Aborts the scan between pattern passes once cancelled.
+    pub cancel: Option<CancellationToken>,
 }
 
-#[derive(Debug, Clone)]
-pub struct DataCloakConfig {
-    pub enable_redos_protection: bool,
-    pub email_validation: EmailValidation,
-    pub credit_card_validation: CreditCardValidation,
-    pub max_text_length: usize,
-    pub regex_timeout_ms: u64,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetectionReport {
+    pub results: Vec<PIIDetectionResult>,
+    pub grouped: Option<HashMap<String, Vec<PIIDetectionResult>>>,
+    /// Number of matches per PII type, counted before deduplication.
+    pub counts: HashMap<String, u32>,
+    /// Detection stopped at `max_matches_per_type`, `max_findings` or the
+    /// memory budget.
+    pub limits_exceeded: bool,
+    /// Why the scan was cut short, if it was.
+    pub warnings: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
-pub enum EmailValidation {
-    Regex,
-    Validator,
-    Hybrid,
+/// What `for_each_match` should do after visiting a match.
+enum Visit {
+    Continue,
+    /// Skip the remaining matches of the current type.
+    NextType,
+    Stop,
+}
+
+/// Findings of one scan and whether a limit cut it short.
+pub(crate) struct Detections {
+    pub(crate) results: Vec<PIIDetectionResult>,
+    pub(crate) limits_exceeded: bool,
+    pub(crate) warnings: Vec<String>,
+    pub(crate) validation: ValidationCounts,
+    pub(crate) elapsed: std::time::Duration,
 }
 
+/// How many matches a scan's validators rejected.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ValidationCounts {
+    /// Matches that failed validation, whether reported or not.
+    pub(crate) failed: u32,
+    /// Failed matches dropped for low confidence.
+    pub(crate) suppressed: u32,
+    /// Matches skipped as allowlisted.
+    pub(crate) allowlisted: u32,
+    /// Matches skipped as overlapping an earlier mask.
+    pub(crate) already_masked: u32,
+}
+
+/// Detects and masks PII according to a `DataCloakConfig`.
+///
+/// The engine is `Send + Sync`: share one instance behind an `Arc` and call it
+/// from as many threads as needed. Scans only read shared state; placeholder
+/// masking briefly locks the placeholder registry to number new values.
+///
+/// Cloning is cheap: clones share the compiled patterns, the configuration
+/// and the placeholder registry, so placeholders stay consistent across all
+/// clones of an engine.
 #[derive(Debug, Clone)]
-pub enum CreditCardValidation {
-    Basic,
-    Luhn,
-    Full,
+pub struct DataCloakEngine {
+    patterns: Arc<PatternSet>,
+    config: Arc<DataCloakConfig>,
+    fpe: Option<Arc<FormatPreservingCipher>>,
+    templates: Arc<HashMap<String, MaskTemplate>>,
+    placeholders: Arc<Mutex<PlaceholderRegistry>>,
+    audit_hook: Option<AuditHook>,
+    scorer: Option<scoring::ScorerHook>,
+    json_rules: Arc<json::JsonRules>,
+    keywords: Arc<keywords::KeywordScorer>,
+    names: Arc<names::NameDictionary>,
+    organizations: Arc<organizations::OrganizationDictionary>,
+    allowlist: Arc<allowlist::Allowlist>,
+    denylist: Arc<denylist::Denylist>,
+    own_masks: Arc<own_masks::OwnMasks>,
+    #[cfg(feature = "phonenumber")]
+    phone_regions: Arc<phone::PhoneRegions>,
+    #[cfg(feature = "ner")]
+    ner: Option<Arc<ner::NerModel>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<metrics::EngineMetrics>,
 }
 
-impl Default for DataCloakConfig {
-    fn default() -> Self {
+impl DataCloakEngine {
+    pub fn new(config: DataCloakConfig) -> Result<Self, DataCloakError> {
+        config.validate()?;
+
+        let fpe = match &config.masking_strategy {
+            MaskingStrategy::FormatPreserving { key } => {
+                Some(Arc::new(FormatPreservingCipher::new(key)?))
+            }
+            _ => None,
+        };
+
+        let templates = config
+            .mask_templates
+            .iter()
+            .map(|(pii_type, template)| Ok((pii_type.clone(), MaskTemplate::parse(template)?)))
+            .collect::<Result<HashMap<_, _>, DataCloakError>>()?;
+
+        // Enhanced patterns for PII detection, each with the literals any
+        // match must contain so clean text skips them
+        const DIGITS: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let mut patterns: Vec<PatternSpec> = [
+            ("email", r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Z|a-z]{2,}\b", &["@"][..]),
+            ("phone", PHONE_PATTERN, DIGITS),
+            ("ssn", r"\b\d{3}-\d{2}-\d{4}\b", &["-"][..]),
+            ("credit_card", r"\b(?:\d[ -]*?){13,19}\b", DIGITS),
+        ]
+        .into_iter()
+        .map(|(name, pattern, literals)| PatternSpec {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            literals,
+        })
+        .collect();
+        locales::add_locale_patterns(&mut patterns, &config.all_locales());
+
+        // Custom patterns replace a built-in of the same name, otherwise
+        // follow the built-ins in name order. Their literals are unknown, so
+        // they are always evaluated.
+        let mut custom: Vec<_> = config.custom_patterns.iter().collect();
+        custom.sort();
+        for (pii_type, pattern) in custom {
+            let spec = PatternSpec {
+                name: pii_type.clone(),
+                pattern: pattern.clone(),
+                literals: &[],
+            };
+            match patterns.iter_mut().find(|existing| &existing.name == pii_type) {
+                Some(existing) => *existing = spec,
+                None => patterns.push(spec),
+            }
+        }
+        let patterns = PatternSet::cached(patterns)?;
+        let json_rules = json::JsonRules::compile(&config)?;
+        let keywords = keywords::KeywordScorer::compile(&config);
+        let names = names::NameDictionary::compile(&config);
+        let organizations = organizations::OrganizationDictionary::compile(&config)?;
+        let allowlist = allowlist::Allowlist::compile(&config)?;
+        let denylist = denylist::Denylist::compile(&config)?;
+        let own_masks = own_masks::OwnMasks::compile(&config)?;
+        #[cfg(feature = "phonenumber")]
+        let phone_regions = phone::PhoneRegions::compile(&config)?;
+
+        Ok(Self {
+            patterns,
+            config: Arc::new(config),
+            fpe,
+            templates: Arc::new(templates),
+            placeholders: Arc::new(Mutex::new(PlaceholderRegistry::default())),
+            audit_hook: None,
+            scorer: None,
+            json_rules: Arc::new(json_rules),
+            keywords: Arc::new(keywords),
+            names: Arc::new(names),
+            organizations: Arc::new(organizations),
+            allowlist: Arc::new(allowlist),
+            denylist: Arc::new(denylist),
+            own_masks: Arc::new(own_masks),
+            #[cfg(feature = "phonenumber")]
+            phone_regions: Arc::new(phone_regions),
+            #[cfg(feature = "ner")]
+            ner: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        })
+    }
+
+    /// Empties the process-wide cache of compiled patterns. Engines already
+    /// created keep theirs; later engines compile afresh.
+    pub fn clear_pattern_cache() -> Result<(), DataCloakError> {
+        PatternSet::clear_cache()
+    }
+
+    /// A clone sharing the compiled patterns but numbering placeholders on
+    /// its own, starting from an empty registry.
+    pub(crate) fn clone_with_own_placeholders(&self) -> Self {
         Self {
-            enable_redos_protection: true,
-            email_validation: EmailValidation::Validator,
-            credit_card_validation: CreditCardValidation::Luhn,
-            max_text_length: 100_000,
-            regex_timeout_ms: 1000,
+            placeholders: Arc::new(Mutex::new(PlaceholderRegistry::default())),
+            ..self.clone()
         }
     }
-}
 
-impl DataCloakEngine {
-    pub fn new(config: DataCloakConfig) -> Result<Self, String> {
-        let mut patterns = HashMap::new();
-        
-        // Enhanced patterns for PII detection
-        patterns.insert(
-            "email".to_string(),
-            Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Z|a-z]{2,}\b")
-                .map_err(|e| format!("Failed to compile email regex: {}", e))?,
-        );
-        
-        patterns.insert(
-            "phone".to_string(),
-            Regex::new(r"(?:\(?\d{3}\)?[-.\\s]?\d{3}[-.\\s]?\d{4}|\b\d{3}[-.\\s]?\d{3}[-.\\s]?\d{4})\b")
-                .map_err(|e| format!("Failed to compile phone regex: {}", e))?,
-        );
-        
-        patterns.insert(
-            "ssn".to_string(),
-            Regex::new(r"\b\d{3}-\d{2}-\d{4}\b")
-                .map_err(|e| format!("Failed to compile SSN regex: {}", e))?,
-        );
-        
-        patterns.insert(
-            "credit_card".to_string(),
-            Regex::new(r"\b(?:\d[ -]*?){13,19}\b")
-                .map_err(|e| format!("Failed to compile credit card regex: {}", e))?,
-        );
+    /// Registers a hook that is notified of every value restored by `unmask_text`.
+    pub fn with_audit_hook(mut self, hook: Arc<dyn ReidentificationAudit>) -> Self {
+        self.audit_hook = Some(AuditHook(hook));
+        self
+    }
+
+    pub fn detect_pii(&self, text: &str) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        Ok(self.detect_pii_cancellable(text, None)?.results)
+    }
+
+    /// Scans raw bytes in whatever encoding they use, as sniffed by
+    /// `decode_text`, instead of failing on input that isn't UTF-8. Offsets
+    /// refer to the decoded text.
+    pub fn detect_pii_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let (text, _) = encoding::decode_text(bytes);
+        self.detect_pii(&text)
+    }
+
+    pub(crate) fn detect_pii_cancellable(
+        &self,
+        text: &str,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Detections, DataCloakError> {
+        self.detect_within_budget(text, cancel, self.config.memory_budget_bytes)
+    }
+
+    /// Scans `text`, dropping findings once their estimated allocations would
+    /// exceed `budget` bytes.
+    fn detect_within_budget(
+        &self,
+        text: &str,
+        cancel: Option<&CancellationToken>,
+        budget: Option<usize>,
+    ) -> Result<Detections, DataCloakError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("datacloak.detect", bytes = text.len()).entered();
+        let started = std::time::Instant::now();
+        let mut results = Vec::new();
+        let mut limits_exceeded = false;
+        let mut warnings = Vec::new();
+        let mut allocated = 0;
+        let mut type_matches: HashMap<&str, usize> = HashMap::new();
+        let folded = self.fold_for_detection(text);
+        let scan_text = folded.as_ref().map_or(text, |folded| folded.as_str());
+
+        let scanned = self.for_each_match(scan_text, cancel, |found| {
+            if self.config.max_findings.is_some_and(|max| results.len() >= max) {
+                limits_exceeded = true;
+                warnings.push(format!(
+                    "Stopped after max_findings ({}) findings",
+                    results.len()
+                ));
+                return Visit::Stop;
+            }
+            let type_count = type_matches.entry(found.pii_type).or_insert(0);
+            if self.config.max_matches_per_type.is_some_and(|max| *type_count >= max) {
+                limits_exceeded = true;
+                warnings.push(format!(
+                    "Stopped reporting {} after max_matches_per_type ({}) matches",
+                    found.pii_type, type_count
+                ));
+                return Visit::NextType;
+            }
+
+            // Findings in folded text are reported against the original
+            let (start, end) = folded.as_ref().map_or((found.start, found.end), |folded| {
+                folded.original_span(found.start, found.end)
+            });
+            let sample = &text[start..end];
+            let normalized = self
+                .normalized_value(found.pii_type, found.sample)
+                .or_else(|| (found.sample != sample).then(|| found.sample.to_string()));
+            let mut masked =
+                self.mask_value(normalized.as_deref().unwrap_or(found.sample), found.pii_type);
+            if self.config.decode_percent_encoding && percent::is_percent_encoded(sample) {
+                masked = percent::percent_encode(&masked);
+            }
+            let pii = PIIDetectionResult {
+                field_name: "text".to_string(),
+                pii_type: found.pii_type.to_string(),
+                confidence: found.confidence,
+                sample: sample.to_string(),
+                masked,
+                start,
+                end,
+                snippet: None,
+                explanation: self
+                    .config
+                    .explain_confidence
+                    .then(|| self.explain_match(scan_text, &found)),
+                low_confidence: found.low_confidence,
+                normalized,
+            };
+            let size = pii.allocated_bytes();
+            if let Some(budget) = budget.filter(|&budget| allocated + size > budget) {
+                limits_exceeded = true;
+                warnings.push(format!(
+                    "Stopped after {} findings to stay within the {} byte memory budget",
+                    results.len(),
+                    budget
+                ));
+                return Visit::Stop;
+            }
+            allocated += size;
+            *type_count += 1;
+            results.push(pii);
+            Visit::Continue
+        });
+        let validation = match scanned {
+            Ok(validation) => validation,
+            Err(e) => {
+                #[cfg(feature = "metrics")]
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_error(&e);
+                }
+                return Err(e);
+            }
+        };
+
+        let mut detections = Detections {
+            results,
+            limits_exceeded,
+            warnings,
+            validation,
+            elapsed: std::time::Duration::ZERO,
+        };
+        if self.config.decode_base64 {
+            self.detect_in_base64(text, cancel, budget, &mut detections)?;
+        }
+        if self.config.snippet_context_chars > 0 {
+            snippet::attach_snippets(
+                text,
+                &mut detections.results,
+                self.config.snippet_context_chars,
+            );
+        }
+        detections.elapsed = started.elapsed();
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_detection(&detections);
+        }
+        Ok(detections)
+    }
 
-        Ok(Self { patterns, config })
+    /// Locates PII without copying anything out of `text`: each match borrows
+    /// its sample from the input and its type name from the engine. Use this
+    /// when only counts or positions are needed; match limits and the memory
+    /// budget don't apply.
+    pub fn find_pii<'a>(&'a self, text: &'a str) -> Result<Vec<PiiMatch<'a>>, DataCloakError> {
+        let mut matches = Vec::new();
+        self.for_each_match(text, None, |found| {
+            matches.push(found);
+            Visit::Continue
+        })?;
+        Ok(matches)
     }
 
-    pub fn detect_pii(&self, text: &str) -> Result<Vec<PIIDetectionResult>, String> {
+    /// Runs the enabled patterns over `text` and hands every match with
+    /// reasonable confidence to `visit`, pattern by pattern.
+    fn for_each_match<'a>(
+        &'a self,
+        text: &'a str,
+        cancel: Option<&CancellationToken>,
+        mut visit: impl FnMut(PiiMatch<'a>) -> Visit,
+    ) -> Result<ValidationCounts, DataCloakError> {
         if text.len() > self.config.max_text_length {
-            return Err(format!(
-                "Text length ({}) exceeds maximum ({})",
-                text.len(),
-                self.config.max_text_length
-            ));
+            return Err(DataCloakError::TextTooLarge {
+                length: text.len(),
+                max: self.config.max_text_length,
+            });
         }
 
-        let mut results = Vec::new();
+        let mut validation = ValidationCounts::default();
+        let language = self.keywords.language(text);
+        let masks = self.own_masks.find(text);
+        for (pii_type, pattern) in self.patterns.matching(text) {
+            if let Some(cancel) = cancel {
+                cancel.check()?;
+            }
+            #[cfg(feature = "tracing")]
+            let _trace = instrument::PatternTrace::start(self, pii_type, text);
+            if !self.config.enabled_types.contains(pii_type)
+                && !self.config.custom_patterns.contains_key(pii_type)
+            {
+                continue;
+            }
 
-        for (pii_type, pattern) in &self.patterns {
             for mat in pattern.find_iter(text) {
-                let sample = mat.as_str().to_string();
-                let mut confidence = 0.95;
-
-                // Enhanced validation
-                let is_valid = match pii_type.as_str() {
-                    "email" => match self.config.email_validation {
-                        EmailValidation::Regex => true,
-                        EmailValidation::Validator => self.validate_email(&sample),
-                        EmailValidation::Hybrid => self.validate_email(&sample),
-                    },
-                    "credit_card" => match self.config.credit_card_validation {
-                        CreditCardValidation::Basic => true,
-                        CreditCardValidation::Luhn => self.validate_luhn(&sample),
-                        CreditCardValidation::Full => self.validate_luhn(&sample),
-                    },
-                    _ => true,
-                };
+                let sample = mat.as_str();
+                if own_masks::overlaps_mask(&masks, mat.start(), mat.end()) {
+                    validation.already_masked += 1;
+                    continue;
+                }
+                if self.is_allowlisted(pii_type, sample) {
+                    validation.allowlisted += 1;
+                    continue;
+                }
+                let mut confidence = PATTERN_CONFIDENCE;
 
+                let validated = self.validate_match(pii_type, sample);
+                let is_valid = validated.unwrap_or(true);
                 if !is_valid {
-                    confidence *= 0.7; // Reduce confidence for invalid items
+                    confidence *= FAILED_VALIDATION_FACTOR;
+                    validation.failed += 1;
+                }
+                confidence = self
+                    .keywords
+                    .score(text, language, pii_type, mat.start(), mat.end(), confidence);
+                let candidate = ScoreCandidate {
+                    pii_type,
+                    sample,
+                    text,
+                    start: mat.start(),
+                    end: mat.end(),
+                    validated,
+                    confidence,
+                };
+                if let Some(custom) = self.custom_score(&candidate) {
+                    confidence = custom;
                 }
 
-                if confidence > 0.6 {
-                    // Only include items with reasonable confidence
-                    results.push(PIIDetectionResult {
-                        field_name: "text".to_string(),
-                        pii_type: pii_type.clone(),
+                let low_confidence = confidence <= self.config.confidence_threshold_for(pii_type);
+                if !low_confidence || self.config.report_low_confidence {
+                    let found = PiiMatch {
+                        pii_type,
+                        sample,
                         confidence,
-                        sample: sample.clone(),
-                        masked: self.mask_value(&sample, pii_type),
-                    });
+                        start: mat.start(),
+                        end: mat.end(),
+                        low_confidence,
+                    };
+                    match visit(found) {
+                        Visit::Continue => {}
+                        Visit::NextType => break,
+                        Visit::Stop => return Ok(validation),
+                    }
+                } else if !is_valid {
+                    validation.suppressed += 1;
                 }
             }
         }
 
-        Ok(results)
+        // Denylisted terms are reported even if also allowlisted
+        self.for_each_denied_term(text, &mut visit);
+
+        // The remaining detectors report names, which can be allowlisted too
+        let mut visit = |found: PiiMatch<'a>| {
+            if own_masks::overlaps_mask(&masks, found.start, found.end) {
+                validation.already_masked += 1;
+                return Visit::Continue;
+            }
+            if self.is_allowlisted(found.pii_type, found.sample) {
+                validation.allowlisted += 1;
+                return Visit::Continue;
+            }
+            visit(found)
+        };
+        #[cfg(feature = "ner")]
+        {
+            if let Some(cancel) = cancel {
+                cancel.check()?;
+            }
+            self.for_each_entity(text, &mut visit)?;
+        }
+        self.for_each_obfuscated_email(text, &mut visit);
+        self.for_each_name(text, &mut visit);
+        self.for_each_organization(text, &mut visit);
+
+        Ok(validation)
     }
 
-    pub fn mask_text(&self, text: &str) -> Result<MaskingResult, String> {
+    /// Whether `sample` passes the validation configured for `pii_type`, or
+    /// `None` if the type has none.
+    fn validate_match(&self, pii_type: &str, sample: &str) -> Option<bool> {
+        match pii_type {
+            "email" => match self.config.email_validation {
+                EmailValidation::Regex => None,
+                EmailValidation::Validator => Some(self.validate_email(sample)),
+                EmailValidation::Hybrid => Some(self.validate_email(sample)),
+            },
+            "credit_card" => match self.config.credit_card_validation {
+                CreditCardValidation::Basic => None,
+                CreditCardValidation::Luhn => Some(self.validate_luhn(sample)),
+                CreditCardValidation::Full => Some(self.validate_luhn(sample)),
+            },
+            #[cfg(feature = "phonenumber")]
+            "phone" => match self.config.phone_validation {
+                PhoneValidation::Pattern => None,
+                PhoneValidation::PhoneNumber => Some(self.validate_phone(sample)),
+            },
+            _ => None,
+        }
+    }
+
+    /// `text` rewritten by the configured decoding and normalization
+    /// stages, or `None` if there are none or they change nothing.
+    fn fold_for_detection(&self, text: &str) -> Option<confusables::NormalizedText> {
+        let decoded = self
+            .config
+            .decode_percent_encoding
+            .then(|| percent::percent_decode(text))
+            .flatten();
+        let source = decoded.as_ref().map_or(text, |decoded| decoded.as_str());
+        let normalized = self
+            .config
+            .normalize_unicode
+            .then(|| confusables::NormalizedText::new(source))
+            .flatten();
+        match (decoded, normalized) {
+            (Some(decoded), Some(normalized)) => Some(decoded.then(normalized)),
+            (decoded, normalized) => decoded.or(normalized),
+        }
+    }
+
+    /// The canonical form of a `pii_type` value, if the type has one.
+    pub(crate) fn normalized_value(&self, pii_type: &str, sample: &str) -> Option<String> {
+        match pii_type {
+            "email" => obfuscated::deobfuscate_email(sample),
+            #[cfg(feature = "phonenumber")]
+            "phone" if matches!(self.config.phone_validation, PhoneValidation::PhoneNumber) => {
+                self.e164(sample)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn detect_pii_with_options(
+        &self,
+        text: &str,
+        options: &DetectionOptions,
+    ) -> Result<DetectionReport, DataCloakError> {
+        let Detections {
+            results: detected,
+            limits_exceeded,
+            warnings,
+            ..
+        } = self.detect_pii_cancellable(text, options.cancel.as_ref())?;
+
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for pii in &detected {
+            *counts.entry(pii.pii_type.clone()).or_insert(0) += 1;
+        }
+
+        let results = if options.deduplicate {
+            let mut seen = std::collections::HashSet::new();
+            detected
+                .into_iter()
+                .filter(|pii| seen.insert((pii.pii_type.clone(), pii.sample.clone())))
+                .collect()
+        } else {
+            detected
+        };
+
+        let grouped = if options.group_by_type {
+            let mut groups: HashMap<String, Vec<PIIDetectionResult>> = HashMap::new();
+            for pii in &results {
+                groups.entry(pii.pii_type.clone()).or_default().push(pii.clone());
+            }
+            Some(groups)
+        } else {
+            None
+        };
+
+        Ok(DetectionReport {
+            results,
+            grouped,
+            counts,
+            limits_exceeded,
+            warnings,
+        })
+    }
+
+    pub fn mask_text(&self, text: &str) -> Result<MaskingResult, DataCloakError> {
         let start_time = std::time::Instant::now();
-        let detected_pii = self.detect_pii(text)?;
-        
-        let mut masked_text = text.to_string();
-        
-        // Sort by length (longest first) to avoid partial replacements
-        let mut sorted_pii = detected_pii.clone();
-        sorted_pii.sort_by(|a, b| b.sample.len().cmp(&a.sample.len()));
-        
-        for pii in &sorted_pii {
-            masked_text = masked_text.replace(&pii.sample, &pii.masked);
+        let mut detected = self.detect_for_masking(text)?;
+
+        let placeholders = matches!(self.config.masking_strategy, MaskingStrategy::Placeholder);
+        if placeholders {
+            self.assign_placeholders(text, &mut detected.results)?;
         }
-        
+
+        Ok(self.apply_masks(text, detected, start_time, placeholders))
+    }
+
+    fn assign_placeholders(
+        &self,
+        text: &str,
+        detected_pii: &mut [PIIDetectionResult],
+    ) -> Result<(), DataCloakError> {
+        let mut order: Vec<usize> = (0..detected_pii.len()).collect();
+        order.sort_by_key(|&i| text.find(&detected_pii[i].sample).unwrap_or(usize::MAX));
+
+        let mut registry = self
+            .placeholders
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Placeholder registry"))?;
+        for i in order {
+            let pii = &mut detected_pii[i];
+            pii.masked = registry.placeholder_for(&pii.pii_type, &pii.sample);
+        }
+
+        Ok(())
+    }
+
+    /// Exports every value→placeholder assignment this engine has made, so
+    /// another run can continue with the same numbering.
+    pub fn export_mapping(&self) -> Result<TokenMapping, DataCloakError> {
+        let registry = self
+            .placeholders
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Placeholder registry"))?;
+        Ok(registry.export())
+    }
+
+    /// Merges a previously exported mapping into this engine, resolving
+    /// conflicting entries according to `policy`.
+    pub fn import_mapping(
+        &self,
+        mapping: TokenMapping,
+        policy: MergePolicy,
+    ) -> Result<MergeReport, DataCloakError> {
+        let mut registry = self
+            .placeholders
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Placeholder registry"))?;
+        registry.import(mapping, policy)
+    }
+
+    /// Masks `text` with opaque tokens, recording each token→original mapping
+    /// in `vault` so authorized services can re-identify the values later.
+    pub fn mask_text_with_vault(
+        &self,
+        text: &str,
+        vault: &dyn TokenVault,
+    ) -> Result<MaskingResult, DataCloakError> {
+        let start_time = std::time::Instant::now();
+        let mut detected = self.detect_for_masking(text)?;
+
+        for pii in &mut detected.results {
+            pii.masked = vault.tokenize(&pii.pii_type, &pii.sample)?;
+        }
+
+        Ok(self.apply_masks(text, detected, start_time, true))
+    }
+
+    /// Detects PII for masking, leaving room in the memory budget for the
+    /// original and masked copies of the text kept in the result.
+    fn detect_for_masking(&self, text: &str) -> Result<Detections, DataCloakError> {
+        let budget = match self.config.memory_budget_bytes {
+            Some(budget) if budget < 2 * text.len() => {
+                return Err(DataCloakError::TextTooLarge {
+                    length: text.len(),
+                    max: budget / 2,
+                })
+            }
+            Some(budget) => Some(budget - 2 * text.len()),
+            None => None,
+        };
+        self.detect_within_budget(text, None, budget)
+    }
+
+    /// Restores the original values in previously masked text. Tokens the
+    /// reidentifier cannot resolve are left in place.
+    pub fn unmask_text(
+        &self,
+        masked: &str,
+        reidentifier: &Reidentifier<'_>,
+    ) -> Result<String, DataCloakError> {
+        match reidentifier {
+            Reidentifier::Vault(vault) => {
+                let mut restored = String::with_capacity(masked.len());
+                let mut last = 0;
+
+                for caps in token_pattern().captures_iter(masked) {
+                    let token = caps.get(0).expect("group 0 is always present");
+                    if let Some(original) = vault.detokenize(token.as_str())? {
+                        restored.push_str(&masked[last..token.start()]);
+                        restored.push_str(&original);
+                        last = token.end();
+                        self.record_reidentification(token.as_str(), &caps[1]);
+                    }
+                }
+
+                restored.push_str(&masked[last..]);
+                Ok(restored)
+            }
+            Reidentifier::FormatPreserving { key } => {
+                let cipher = FormatPreservingCipher::new(key)?;
+
+                let mut candidates = Vec::new();
+                for pii_type in ["credit_card", "ssn"] {
+                    if let Some(pattern) = self.patterns.get(pii_type) {
+                        for mat in pattern.find_iter(masked) {
+                            candidates.push((mat.start(), mat.end(), pii_type));
+                        }
+                    }
+                }
+                candidates.sort_by_key(|&(start, _, _)| start);
+
+                let mut restored = String::with_capacity(masked.len());
+                let mut last = 0;
+                for (start, end, pii_type) in candidates {
+                    if start < last {
+                        continue; // overlaps a value we already restored
+                    }
+                    let value = &masked[start..end];
+                    restored.push_str(&masked[last..start]);
+                    restored.push_str(&cipher.decrypt(value, pii_type)?);
+                    last = end;
+                    self.record_reidentification(value, pii_type);
+                }
+
+                restored.push_str(&masked[last..]);
+                Ok(restored)
+            }
+        }
+    }
+
+    fn record_reidentification(&self, token: &str, pii_type: &str) {
+        if let Some(AuditHook(hook)) = &self.audit_hook {
+            hook.record(&ReidentificationEvent {
+                token: token.to_string(),
+                pii_type: pii_type.to_string(),
+                timestamp: chrono::Utc::now(),
+            });
+        }
+    }
+
+    fn apply_masks(
+        &self,
+        text: &str,
+        detected: Detections,
+        start_time: std::time::Instant,
+        record_tokens: bool,
+    ) -> MaskingResult {
+        let masking_started = std::time::Instant::now();
+        let detected_pii = detected.results;
+        let token_map = if record_tokens {
+            detected_pii
+                .iter()
+                .map(|pii| (pii.masked.clone(), pii.sample.clone()))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let masked_text = splice_masks(text, &detected_pii);
+        let pii_items_found = detected_pii.len() as u32;
+
+        let mut counts_by_type: HashMap<String, u32> = HashMap::new();
+        for pii in &detected_pii {
+            *counts_by_type.entry(pii.pii_type.clone()).or_insert(0) += 1;
+        }
+        let masking_time_us = masking_started.elapsed().as_micros() as u64;
         let processing_time = start_time.elapsed().as_millis() as u64;
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_masking(start_time.elapsed());
+        }
         
-        Ok(MaskingResult {
+        MaskingResult {
             original_text: text.to_string(),
             masked_text,
             detected_pii,
             metadata: MaskingMetadata {
                 processing_time,
                 fields_processed: 1,
-                pii_items_found: sorted_pii.len() as u32,
+                pii_items_found,
+                counts_by_type,
+                bytes_processed: text.len(),
+                failed_validation: detected.validation.failed,
+                suppressed_by_validation: detected.validation.suppressed,
+                allowlisted: detected.validation.allowlisted,
+                already_masked: detected.validation.already_masked,
+                detection_time_us: detected.elapsed.as_micros() as u64,
+                masking_time_us,
+                limits_exceeded: detected.limits_exceeded,
+                warnings: detected.warnings,
             },
-        })
+            token_map,
+        }
     }
 
     fn validate_email(&self, email: &str) -> bool {
@@ -201,7 +1091,7 @@ impl DataCloakEngine {
         let mut alternate = false;
 
         for ch in digits.chars().rev() {
-            let mut digit = ch.to_digit(10).unwrap() as u32;
+            let mut digit = ch.to_digit(10).unwrap();
             
             if alternate {
                 digit *= 2;
@@ -218,142 +1108,122 @@ impl DataCloakEngine {
     }
 
     fn mask_value(&self, value: &str, pii_type: &str) -> String {
-        match pii_type {
-            "email" => {
-                if let Some(at_pos) = value.find('@') {
-                    let (local, domain) = value.split_at(at_pos);
-                    if !local.is_empty() {
-                        format!("{}***{}", &local[..1], domain)
-                    } else {
-                        "***@domain.com".to_string()
-                    }
-                } else {
-                    "***@domain.com".to_string()
-                }
-            }
-            "phone" => {
-                let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
-                if digits.len() >= 4 {
-                    let last_four = &digits[digits.len() - 4..];
-                    format!("***-***-{}", last_four)
-                } else {
-                    "***-***-****".to_string()
-                }
-            }
-            "ssn" => {
-                if value.len() >= 4 {
-                    format!("***-**-{}", &value[value.len() - 4..])
-                } else {
-                    "***-**-****".to_string()
-                }
-            }
-            "credit_card" => {
-                let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
-                if digits.len() >= 4 {
-                    let last_four = &digits[digits.len() - 4..];
-                    format!("**** **** **** {}", last_four)
-                } else {
-                    "**** **** **** ****".to_string()
-                }
+        match &self.config.masking_strategy {
+            MaskingStrategy::Partial => self.partial_mask(value, pii_type),
+            MaskingStrategy::Hmac { key } => self.hmac_pseudonym(value, pii_type, key),
+            MaskingStrategy::FormatPreserving { .. } => match &self.fpe {
+                Some(cipher) if FormatPreservingCipher::supports(pii_type) => cipher
+                    .encrypt(value, pii_type)
+                    .unwrap_or_else(|_| self.partial_mask(value, pii_type)),
+                _ => self.partial_mask(value, pii_type),
+            },
+            MaskingStrategy::SaltedHash { salt, hex_length } => {
+                self.salted_hash(value, pii_type, salt, *hex_length)
             }
-            _ => "***".to_string(),
+            MaskingStrategy::Synthesize { seed } => synthetic::synthesize(value, pii_type, seed),
+            // Numbering needs the whole document, so `mask_text` assigns it
+            MaskingStrategy::Placeholder => format!("[{}]", pii_type.to_uppercase()),
         }
     }
-}
 
-// C FFI interface
-#[no_mangle]
-pub extern "C" fn datacloak_create() -> *mut c_void {
-    let config = DataCloakConfig::default();
-    match DataCloakEngine::new(config) {
-        Ok(engine) => Box::into_raw(Box::new(engine)) as *mut c_void,
-        Err(_) => std::ptr::null_mut(),
-    }
-}
+    fn hmac_pseudonym(&self, value: &str, pii_type: &str, key: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(pii_type.as_bytes());
+        mac.update(b":");
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
 
-#[no_mangle]
-pub extern "C" fn datacloak_destroy(engine: *mut c_void) {
-    if !engine.is_null() {
-        unsafe {
-            let _ = Box::from_raw(engine as *mut DataCloakEngine);
-        }
+        // 8 bytes (16 hex chars) keeps tokens short while making collisions unlikely
+        format!("{}_{}", pii_type.to_uppercase(), hex::encode_hex(&digest[..8]))
     }
-}
 
-#[no_mangle]
-pub extern "C" fn datacloak_detect_pii(
-    engine: *mut c_void,
-    text: *const c_char,
-) -> *mut c_char {
-    if engine.is_null() || text.is_null() {
-        return std::ptr::null_mut();
-    }
+    fn salted_hash(&self, value: &str, pii_type: &str, salt: &[u8], hex_length: usize) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        hasher.update(value.as_bytes());
+        let digest = hasher.finalize();
 
-    unsafe {
-        let engine = &*(engine as *const DataCloakEngine);
-        let text_str = match CStr::from_ptr(text).to_str() {
-            Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
-        };
+        let hex = hex::encode_hex(&digest);
+        format!("{}:{}", pii_type, &hex[..hex_length])
+    }
 
-        match engine.detect_pii(text_str) {
-            Ok(results) => {
-                let json = serde_json::to_string(&results).unwrap_or_default();
-                match CString::new(json) {
-                    Ok(cstring) => cstring.into_raw(),
-                    Err(_) => std::ptr::null_mut(),
-                }
-            }
-            Err(_) => std::ptr::null_mut(),
+    fn partial_mask(&self, value: &str, pii_type: &str) -> String {
+        if let Some(template) = self.templates.get(pii_type) {
+            return template.render(value, pii_type);
         }
-    }
-}
 
-#[no_mangle]
-pub extern "C" fn datacloak_mask_text(
-    engine: *mut c_void,
-    text: *const c_char,
-) -> *mut c_char {
-    if engine.is_null() || text.is_null() {
-        return std::ptr::null_mut();
+        Self::reveal_mask(value, pii_type, self.reveal_length(pii_type))
     }
 
-    unsafe {
-        let engine = &*(engine as *const DataCloakEngine);
-        let text_str = match CStr::from_ptr(text).to_str() {
-            Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
-        };
-
-        match engine.mask_text(text_str) {
-            Ok(result) => {
-                let json = serde_json::to_string(&result).unwrap_or_default();
-                match CString::new(json) {
-                    Ok(cstring) => cstring.into_raw(),
-                    Err(_) => std::ptr::null_mut(),
+    /// The built-in partial mask of `value`, leaving `reveal` characters
+    /// visible.
+    fn reveal_mask(value: &str, pii_type: &str, reveal: usize) -> String {
+        match pii_type {
+            "email" => match value.split_once('@') {
+                Some((local, domain)) if !local.is_empty() && reveal > 0 => {
+                    let shown: String = local.graphemes(true).take(reveal).collect();
+                    format!("{}***@{}", shown, domain)
                 }
-            }
-            Err(_) => std::ptr::null_mut(),
+                _ => "***@domain.com".to_string(),
+            },
+            "phone" => Self::reveal_trailing_digits("***-***-****", value, reveal),
+            "ssn" => Self::reveal_trailing_digits("***-**-****", value, reveal),
+            "credit_card" => Self::reveal_trailing_digits("**** **** **** ****", value, reveal),
+            _ => "***".to_string(),
+        }
+    }
+
+    fn reveal_length(&self, pii_type: &str) -> usize {
+        match self.config.reveal_lengths.get(pii_type) {
+            Some(&n) => n,
+            None if pii_type == "email" => 1,
+            None => 4,
         }
     }
-}
 
-#[no_mangle]
-pub extern "C" fn datacloak_free_string(s: *mut c_char) {
-    if !s.is_null() {
-        unsafe {
-            let _ = CString::from_raw(s);
+    /// Fills the last `reveal` `*` slots of `mask` with the trailing digits of
+    /// `value`. Values with fewer digits than requested stay fully masked.
+    fn reveal_trailing_digits(mask: &str, value: &str, reveal: usize) -> String {
+        let digits: Vec<char> = value.chars().filter(|c| c.is_ascii_digit()).collect();
+        if reveal == 0 || digits.len() < reveal {
+            return mask.to_string();
         }
+
+        let mut shown = digits[digits.len() - reveal..].iter().rev();
+        let mut masked: Vec<char> = mask
+            .chars()
+            .rev()
+            .map(|c| match c {
+                '*' => shown.next().copied().unwrap_or('*'),
+                other => other,
+            })
+            .collect();
+        masked.reverse();
+        masked.into_iter().collect()
     }
 }
 
-#[no_mangle]
-pub extern "C" fn datacloak_version() -> *mut c_char {
-    let version = "1.0.0".to_string();
-    match CString::new(version) {
-        Ok(cstring) => cstring.into_raw(),
-        Err(_) => std::ptr::null_mut(),
+/// `text` with each finding's `start..end` replaced by its mask. Only those
+/// spans change, so other occurrences of a sample, such as ones past a match
+/// limit, are left alone. Of overlapping findings the one starting first is
+/// kept, or the longer of two starting together.
+pub(crate) fn splice_masks(text: &str, findings: &[PIIDetectionResult]) -> String {
+    let mut spans: Vec<&PIIDetectionResult> = findings.iter().collect();
+    spans.sort_by_key(|pii| (pii.start, Reverse(pii.end)));
+
+    let mut masked = String::with_capacity(text.len());
+    let mut last = 0;
+    for pii in spans {
+        if pii.start < last {
+            continue;
+        }
+        masked.push_str(&text[last..pii.start]);
+        masked.push_str(&pii.masked);
+        last = pii.end;
     }
+    masked.push_str(&text[last..]);
+    masked
 }
 
 #[cfg(test)]
@@ -379,10 +1249,10 @@ mod tests {
         let engine = DataCloakEngine::new(config).unwrap();
         
         // Valid Luhn number
-        assert!(engine.validate_luhn("4532123456789012"));
+        assert!(engine.validate_luhn("4532015112830366"));
         
         // Invalid Luhn number
-        assert!(!engine.validate_luhn("4532123456789013"));
+        assert!(!engine.validate_luhn("4532015112830367"));
     }
 
     #[test]
@@ -397,4 +1267,521 @@ mod tests {
         assert!(result.masked_text.contains("j***@test.com"));
         assert_eq!(result.metadata.pii_items_found, 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_deduplication_and_grouping() {
+        let config = DataCloakConfig::default();
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let text = "a@example.com, a@example.com, b@example.com and 123-45-6789";
+        let options = DetectionOptions {
+            deduplicate: true,
+            group_by_type: true,
+            ..Default::default()
+        };
+        let report = engine.detect_pii_with_options(text, &options).unwrap();
+
+        assert_eq!(report.counts["email"], 3);
+        assert_eq!(report.counts["ssn"], 1);
+        let grouped = report.grouped.unwrap();
+        assert_eq!(grouped["email"].len(), 2);
+        assert_eq!(grouped["ssn"].len(), 1);
+    }
+
+    #[test]
+    fn test_hmac_pseudonymization_is_deterministic() {
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::Hmac {
+                key: b"secret-key".to_vec(),
+            },
+            ..DataCloakConfig::default()
+        };
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let first = engine.mask_text("Email jane@example.com").unwrap();
+        let second = engine.mask_text("Reply to jane@example.com today").unwrap();
+
+        let token = &first.detected_pii[0].masked;
+        assert!(token.starts_with("EMAIL_"));
+        assert_eq!(token, &second.detected_pii[0].masked);
+        assert!(!first.masked_text.contains("jane@example.com"));
+    }
+
+    #[test]
+    fn test_hmac_requires_key() {
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::Hmac { key: Vec::new() },
+            ..DataCloakConfig::default()
+        };
+        assert!(DataCloakEngine::new(config).is_err());
+    }
+
+    #[test]
+    fn test_mask_text_with_vault() {
+        let config = DataCloakConfig::default();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let vault = InMemoryTokenVault::new();
+
+        let result = engine
+            .mask_text_with_vault("Contact jane@example.com", &vault)
+            .unwrap();
+
+        let token = &result.detected_pii[0].masked;
+        assert!(token.starts_with("tok_email_"));
+        assert_eq!(result.masked_text, format!("Contact {}", token));
+        assert_eq!(
+            vault.detokenize(token).unwrap().as_deref(),
+            Some("jane@example.com")
+        );
+    }
+
+    #[test]
+    fn test_unmask_text_with_vault_audits_each_value() {
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct RecordingAudit(Mutex<Vec<ReidentificationEvent>>);
+
+        impl ReidentificationAudit for RecordingAudit {
+            fn record(&self, event: &ReidentificationEvent) {
+                self.0.lock().unwrap().push(event.clone());
+            }
+        }
+
+        let audit = Arc::new(RecordingAudit::default());
+        let engine = DataCloakEngine::new(DataCloakConfig::default())
+            .unwrap()
+            .with_audit_hook(audit.clone());
+        let vault = InMemoryTokenVault::new();
+
+        let text = "Reach jane@example.com or 123-45-6789";
+        let masked = engine.mask_text_with_vault(text, &vault).unwrap();
+        let restored = engine
+            .unmask_text(&masked.masked_text, &Reidentifier::Vault(&vault))
+            .unwrap();
+
+        assert_eq!(restored, text);
+        let events = audit.0.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().any(|e| e.pii_type == "email"));
+        assert!(events.iter().any(|e| e.pii_type == "ssn"));
+    }
+
+    #[test]
+    fn test_format_preserving_mask_and_unmask() {
+        let key = vec![42u8; 32];
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::FormatPreserving { key: key.clone() },
+            ..DataCloakConfig::default()
+        };
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let text = "SSN 123-45-6789 on file";
+        let result = engine.mask_text(text).unwrap();
+        assert_ne!(result.masked_text, text);
+        assert_eq!(result.masked_text.len(), text.len());
+
+        let restored = engine
+            .unmask_text(
+                &result.masked_text,
+                &Reidentifier::FormatPreserving { key: &key },
+            )
+            .unwrap();
+        assert_eq!(restored, text);
+    }
+
+    #[test]
+    fn test_salted_hash_masking() {
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::SaltedHash {
+                salt: b"pepper".to_vec(),
+                hex_length: 12,
+            },
+            ..DataCloakConfig::default()
+        };
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let first = engine.mask_text("jane@example.com").unwrap();
+        let second = engine.mask_text("cc jane@example.com").unwrap();
+
+        let digest = &first.detected_pii[0].masked;
+        assert!(digest.starts_with("email:"));
+        assert_eq!(digest.len(), "email:".len() + 12);
+        assert_eq!(digest, &second.detected_pii[0].masked);
+    }
+
+    #[test]
+    fn test_synthesize_masking_keeps_text_readable() {
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::Synthesize {
+                seed: b"fixture-seed".to_vec(),
+            },
+            ..DataCloakConfig::default()
+        };
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let result = engine.mask_text("Mail jane@corp.com now").unwrap();
+        let fake = &result.detected_pii[0].masked;
+
+        assert!(fake.contains('@'));
+        assert_ne!(fake, "jane@corp.com");
+        assert_eq!(result.masked_text, format!("Mail {} now", fake));
+    }
+
+    #[test]
+    fn test_custom_mask_templates() {
+        let mut config = DataCloakConfig::default();
+        config
+            .mask_templates
+            .insert("ssn".to_string(), "XXX-XX-{last4}".to_string());
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let result = engine.mask_text("SSN: 123-45-6789").unwrap();
+        assert_eq!(result.masked_text, "SSN: XXX-XX-6789");
+
+        let mut config = DataCloakConfig::default();
+        config
+            .mask_templates
+            .insert("email".to_string(), "{bogus}".to_string());
+        assert!(DataCloakEngine::new(config).is_err());
+    }
+
+    #[test]
+    fn test_configurable_reveal_length() {
+        let mut config = DataCloakConfig::default();
+        config.reveal_lengths.insert("credit_card".to_string(), 0);
+        config.reveal_lengths.insert("phone".to_string(), 2);
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let result = engine
+            .mask_text("Card 4532015112830366, call 555-123-4567")
+            .unwrap();
+
+        assert!(result.masked_text.contains("**** **** **** ****"));
+        assert!(result.masked_text.contains("***-***-**67"));
+    }
+
+    #[test]
+    fn test_masking_multibyte_values_never_panics() {
+        let strategies = [
+            MaskingStrategy::Partial,
+            MaskingStrategy::Hmac { key: vec![1; 16] },
+            MaskingStrategy::FormatPreserving { key: vec![2; 32] },
+            MaskingStrategy::SaltedHash {
+                salt: vec![3; 8],
+                hex_length: 12,
+            },
+            MaskingStrategy::Synthesize { seed: vec![4; 8] },
+            MaskingStrategy::Placeholder,
+        ];
+        let values = [
+            "",
+            "@",
+            "é",
+            "ü@例え.jp",
+            "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}@example.com",
+            "１２３-４５-６７８９",
+            "४५३२ ०१५१",
+        ];
+        for strategy in strategies {
+            let config = DataCloakConfig::builder()
+                .masking_strategy(strategy)
+                .build()
+                .unwrap();
+            let engine = DataCloakEngine::new(config).unwrap();
+            for value in values {
+                for pii_type in ["email", "phone", "ssn", "credit_card", "name"] {
+                    engine.mask_value(value, pii_type);
+                }
+            }
+        }
+
+        // The reveal keeps a multi-codepoint emoji whole
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        assert_eq!(
+            engine.mask_value(
+                "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}@example.com",
+                "email"
+            ),
+            "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}***@example.com"
+        );
+    }
+
+    #[test]
+    fn test_placeholder_masking() {
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::Placeholder,
+            ..DataCloakConfig::default()
+        };
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let text = "From a@example.com to b@example.com, cc a@example.com";
+        let result = engine.mask_text(text).unwrap();
+
+        assert_eq!(
+            result.masked_text,
+            "From [EMAIL_1] to [EMAIL_2], cc [EMAIL_1]"
+        );
+    }
+
+    #[test]
+    fn test_token_map_rehydrates_reply() {
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::Placeholder,
+            ..DataCloakConfig::default()
+        };
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let result = engine
+            .mask_text("Ping jane@example.com or 555-123-4567, not jane@example.com")
+            .unwrap();
+        assert_eq!(result.token_map.len(), 2);
+        assert_eq!(result.token_map["[EMAIL_1]"], "jane@example.com");
+
+        let reply = "I have emailed [EMAIL_1] and called [PHONE_1].";
+        assert_eq!(
+            result.rehydrate(reply),
+            "I have emailed jane@example.com and called 555-123-4567."
+        );
+    }
+
+    #[test]
+    fn test_mapping_export_import_across_engines() {
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::Placeholder,
+            ..DataCloakConfig::default()
+        };
+        let first = DataCloakEngine::new(config.clone()).unwrap();
+        first.mask_text("a@example.com and b@example.com").unwrap();
+
+        let second = DataCloakEngine::new(config).unwrap();
+        let report = second
+            .import_mapping(first.export_mapping().unwrap(), MergePolicy::Reject)
+            .unwrap();
+        assert_eq!(report.added, 2);
+
+        let result = second.mask_text("b@example.com, c@example.com").unwrap();
+        assert_eq!(result.masked_text, "[EMAIL_2], [EMAIL_3]");
+    }
+
+    #[test]
+    fn test_typed_errors() {
+        let config = DataCloakConfig {
+            max_text_length: 10,
+            ..DataCloakConfig::default()
+        };
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let err = engine.detect_pii("this text is too long").unwrap_err();
+        assert_eq!(err, DataCloakError::TextTooLarge { length: 21, max: 10 });
+        assert_eq!(err.code(), 2);
+
+        let config = DataCloakConfig {
+            masking_strategy: MaskingStrategy::FormatPreserving { key: vec![1, 2, 3] },
+            ..DataCloakConfig::default()
+        };
+        assert!(matches!(
+            DataCloakEngine::new(config),
+            Err(DataCloakError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let config = DataCloakConfig::builder()
+            .custom_pattern("employee_id", r"\bEMP-\d{6}\b")
+            .mask_template("employee_id", "EMP-******")
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let result = engine.mask_text("Badge EMP-123456 issued").unwrap();
+        assert_eq!(result.masked_text, "Badge EMP-****** issued");
+    }
+
+    #[test]
+    fn test_many_custom_patterns_only_report_matches() {
+        let mut builder = DataCloakConfig::builder().custom_pattern("ssn", r"\bSSN\d{9}\b");
+        for i in 0..50 {
+            builder = builder
+                .custom_pattern(&format!("code_{}", i), &format!(r"\bC{}X\d{{4}}\b", i));
+        }
+        let engine = DataCloakEngine::new(builder.build().unwrap()).unwrap();
+
+        let results = engine.detect_pii("ids C7X1234 and SSN123456789").unwrap();
+        let mut types: Vec<&str> = results.iter().map(|r| r.pii_type.as_str()).collect();
+        types.sort();
+        assert_eq!(types, vec!["code_7", "ssn"]);
+        assert!(engine.detect_pii("nothing to see here").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_match_limits_truncate_and_flag() {
+        let text = "a@example.com b@example.com c@example.com call 555-123-4567";
+        let config = DataCloakConfig::builder()
+            .max_matches_per_type(2)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let report = engine
+            .detect_pii_with_options(text, &DetectionOptions::default())
+            .unwrap();
+        assert!(report.limits_exceeded);
+        assert_eq!(report.counts["email"], 2);
+        assert_eq!(report.counts["phone"], 1);
+
+        let config = DataCloakConfig::builder().max_findings(1).build().unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine.mask_text(text).unwrap();
+        assert_eq!(result.detected_pii.len(), 1);
+        assert!(result.metadata.limits_exceeded);
+
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        assert!(!engine.mask_text(text).unwrap().metadata.limits_exceeded);
+    }
+
+    #[test]
+    fn test_values_past_the_limit_stay_unmasked() {
+        let config = DataCloakConfig::builder()
+            .max_matches_per_type(1)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine
+            .mask_text("jane@example.com, again jane@example.com")
+            .unwrap();
+        assert!(result.metadata.limits_exceeded);
+        assert_eq!(
+            result.masked_text,
+            "j***@example.com, again jane@example.com"
+        );
+    }
+
+    #[test]
+    fn test_memory_budget_truncates_with_warning() {
+        let text = "reach a@example.com or b@example.com ".repeat(50);
+        let per_finding = std::mem::size_of::<PIIDetectionResult>() + 64;
+        let config = DataCloakConfig::builder()
+            .memory_budget_bytes(2 * text.len() + 3 * per_finding)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let result = engine.mask_text(&text).unwrap();
+        assert!(result.metadata.limits_exceeded);
+        assert!(!result.detected_pii.is_empty() && result.detected_pii.len() < 100);
+        assert!(result.metadata.warnings[0].contains("memory budget"));
+
+        assert!(matches!(
+            engine.mask_text(&"x".repeat(3 * text.len())),
+            Err(DataCloakError::TextTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_pii_borrows_from_input() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let text = "Mail john@example.com or call 555-123-4567";
+
+        let matches = engine.find_pii(text).unwrap();
+        let owned = engine.detect_pii(text).unwrap();
+        assert_eq!(matches.len(), owned.len());
+        for (found, pii) in matches.iter().zip(&owned) {
+            assert_eq!(found.sample, pii.sample);
+            assert_eq!(found.pii_type, pii.pii_type);
+            assert!(std::ptr::eq(found.sample, &text[found.start..found.end]));
+        }
+    }
+
+    #[test]
+    fn test_confidence_thresholds_drop_or_flag() {
+        // Fails Luhn, so scores 0.95 * 0.7
+        let text = "card 4111 1111 1111 1112 or 555-123-4567";
+
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let types: Vec<_> = engine
+            .detect_pii(text)
+            .unwrap()
+            .into_iter()
+            .map(|pii| pii.pii_type)
+            .collect();
+        assert_eq!(types, ["phone", "credit_card"]);
+
+        let config = DataCloakConfig::builder()
+            .type_confidence_threshold("credit_card", 0.8)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let findings = engine.detect_pii(text).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].pii_type, "phone");
+
+        let config = DataCloakConfig::builder()
+            .confidence_threshold(0.99)
+            .report_low_confidence(true)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine.mask_text(text).unwrap();
+        assert_eq!(result.detected_pii.len(), 2);
+        assert!(result.detected_pii.iter().all(|pii| pii.low_confidence));
+        assert!(!result.masked_text.contains("555-123-4567"));
+
+        assert!(DataCloakConfig::builder()
+            .confidence_threshold(1.5)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_clones_share_compiled_state_and_placeholders() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let worker = engine.clone();
+        assert!(Arc::ptr_eq(&engine.patterns, &worker.patterns));
+
+        let handle = std::thread::spawn(move || {
+            worker.mask_text("from a@example.com").unwrap().masked_text
+        });
+        assert_eq!(handle.join().unwrap(), "from [EMAIL_1]");
+        assert_eq!(
+            engine.mask_text("to b@example.com, cc a@example.com").unwrap().masked_text,
+            "to [EMAIL_2], cc [EMAIL_1]"
+        );
+    }
+
+    #[test]
+    fn test_engines_with_equal_patterns_share_compilation() {
+        let first = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let second = DataCloakEngine::new(DataCloakConfig {
+            max_text_length: 10,
+            ..DataCloakConfig::default()
+        })
+        .unwrap();
+        assert!(Arc::ptr_eq(&first.patterns, &second.patterns));
+
+        let custom = DataCloakConfig::builder()
+            .custom_pattern("ticket", r"\bT-\d+\b")
+            .build()
+            .unwrap();
+        let third = DataCloakEngine::new(custom).unwrap();
+        assert!(!Arc::ptr_eq(&first.patterns, &third.patterns));
+    }
+
+    #[test]
+    fn test_masking_metadata_reports_counts_and_validation() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let text = "Mail a@example.com, b@example.com or pay 4111 1111 1111 1112";
+
+        let metadata = engine.mask_text(text).unwrap().metadata;
+        assert_eq!(metadata.counts_by_type["email"], 2);
+        assert_eq!(metadata.counts_by_type["credit_card"], 1);
+        assert_eq!(metadata.bytes_processed, text.len());
+        assert_eq!(metadata.failed_validation, 1);
+        assert_eq!(metadata.suppressed_by_validation, 0);
+        assert_eq!(metadata.fields_processed, 1);
+    }
+}
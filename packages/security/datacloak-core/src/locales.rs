@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pattern_set::PatternSpec;
+
+const DIGITS: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+/// Countries with a locale pack: phone, postal code and national ID formats
+/// detected in addition to the built-in patterns. Serialized as lowercase
+/// ISO 3166 codes, e.g. `"gb"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    Us,
+    Gb,
+    De,
+    Fr,
+    Es,
+    It,
+    Nl,
+    Ca,
+    Br,
+    In,
+}
+
+/// Regions of a multinational scan, each bundling the locale packs of its
+/// countries. Serialized in lowercase, e.g. `"eu"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegionProfile {
+    Us,
+    Uk,
+    /// Germany, France, Spain, Italy and the Netherlands.
+    Eu,
+    Ca,
+    Br,
+    In,
+}
+
+impl RegionProfile {
+    pub fn locales(self) -> &'static [Locale] {
+        match self {
+            RegionProfile::Us => &[Locale::Us],
+            RegionProfile::Uk => &[Locale::Gb],
+            RegionProfile::Eu => &[Locale::De, Locale::Fr, Locale::Es, Locale::It, Locale::Nl],
+            RegionProfile::Ca => &[Locale::Ca],
+            RegionProfile::Br => &[Locale::Br],
+            RegionProfile::In => &[Locale::In],
+        }
+    }
+}
+
+/// Formats of one country, as regexes. `None` where the built-in patterns
+/// already cover the format or it has none distinctive enough to detect.
+struct LocalePack {
+    phone: Option<&'static str>,
+    /// Purely numeric codes are matched together with the city or state
+    /// that follows or precedes them, and masked with it.
+    postal_code: Option<&'static str>,
+    national_id: Option<(&'static str, &'static str)>,
+}
+
+impl Locale {
+    /// The phone format of the locale pack, if the built-in pattern doesn't
+    /// already cover it.
+    #[cfg(feature = "phonenumber")]
+    pub(crate) fn phone_pattern(self) -> Option<&'static str> {
+        self.pack().phone
+    }
+
+    fn pack(self) -> LocalePack {
+        match self {
+            // NANP phones and SSNs are built in
+            Locale::Us => LocalePack {
+                phone: None,
+                postal_code: Some(r"\b[A-Z]{2},?[ ]\d{5}(?:-\d{4})?\b"),
+                national_id: None,
+            },
+            Locale::Gb => LocalePack {
+                phone: Some(
+                    r"(?:\+44[\s-]?(?:\(0\)[\s-]?)?|\b0)\d{2,4}[\s-]?\d{3,4}[\s-]?\d{3,4}\b",
+                ),
+                postal_code: Some(r"\b[A-Z]{1,2}\d[A-Z\d]?[ ]?\d[A-Z]{2}\b"),
+                national_id: Some((
+                    "nino",
+                    r"\b[A-CEGHJ-PR-TW-Z][A-CEGHJ-NPR-TW-Z][ ]?\d{2}[ ]?\d{2}[ ]?\d{2}[ ]?[A-D]\b",
+                )),
+            },
+            Locale::De => LocalePack {
+                phone: Some(r"(?:\+49[\s-]?(?:\(0\)[\s-]?)?|\b0)\d{2,5}[\s/-]?\d{3,8}\b"),
+                postal_code: Some(r"\b\d{5}[ ]\p{Lu}\p{Ll}+"),
+                national_id: Some(("de_tax_id", r"\b[1-9]\d{10}\b")),
+            },
+            Locale::Fr => LocalePack {
+                phone: Some(r"(?:\+33[\s.-]?|\b0)[1-9](?:[\s.-]?\d{2}){4}\b"),
+                postal_code: Some(r"\b\d{5}[ ]\p{Lu}\p{Ll}+"),
+                national_id: Some((
+                    "nir",
+                    r"\b[12][ ]?\d{2}[ ]?(?:0[1-9]|1[0-2]|[2-9]\d)[ ]?(?:\d{2}|2[AB])[ ]?\d{3}[ ]?\d{3}(?:[ ]?\d{2})?\b",
+                )),
+            },
+            Locale::Es => LocalePack {
+                phone: Some(r"(?:\+34[\s-]?|\b)[6-9]\d{2}[\s-]?\d{3}[\s-]?\d{3}\b"),
+                postal_code: Some(r"\b\d{5}[ ]\p{Lu}\p{Ll}+"),
+                national_id: Some(("dni", r"\b(?:\d{8}|[XYZ]-?\d{7})-?[A-HJ-NP-TV-Z]\b")),
+            },
+            Locale::It => LocalePack {
+                phone: Some(r"(?:\+39[\s-]?|\b)(?:3\d{2}[\s-]?\d{6,7}|0\d{1,3}[\s-]?\d{5,8})\b"),
+                postal_code: Some(r"\b\d{5}[ ]\p{Lu}\p{Ll}+"),
+                national_id: Some((
+                    "codice_fiscale",
+                    r"\b[A-Z]{6}\d{2}[A-EHLMPR-T]\d{2}[A-Z]\d{3}[A-Z]\b",
+                )),
+            },
+            Locale::Nl => LocalePack {
+                phone: Some(
+                    r"(?:\+31[\s-]?(?:\(0\)[\s-]?)?|\b0)(?:6[\s-]?\d{8}|\d{2,3}[\s-]?\d{6,7})\b",
+                ),
+                postal_code: Some(r"\b\d{4}[ ]?[A-Z]{2}\b"),
+                national_id: Some(("bsn", r"\b\d{4}\.?\d{2}\.?\d{3}\b")),
+            },
+            Locale::Ca => LocalePack {
+                phone: None,
+                postal_code: Some(r"\b[A-Z]\d[A-Z][ ]?\d[A-Z]\d\b"),
+                national_id: Some(("sin", r"\b\d{3}[ -]\d{3}[ -]\d{3}\b")),
+            },
+            Locale::Br => LocalePack {
+                phone: Some(r"(?:\+55[\s-]?)?(?:\(\d{2}\)|\b\d{2})[\s-]?9?\d{4}-?\d{4}\b"),
+                postal_code: Some(r"\b\d{5}-\d{3}\b"),
+                national_id: Some(("cpf", r"\b\d{3}\.\d{3}\.\d{3}-\d{2}\b")),
+            },
+            // Six-digit PIN codes look like any other number
+            Locale::In => LocalePack {
+                phone: Some(r"(?:\+91[\s-]?|\b0?)[6-9]\d{4}[\s-]?\d{5}\b"),
+                postal_code: None,
+                national_id: Some(("aadhaar", r"\b[2-9]\d{3}[ ]\d{4}[ ]\d{4}\b")),
+            },
+        }
+    }
+}
+
+/// Adds the formats of `locales` to `patterns`. Phone formats extend the
+/// built-in `phone` pattern; postal codes of all locales form one
+/// `postal_code` pattern, and each national ID its own.
+pub(crate) fn add_locale_patterns(patterns: &mut Vec<PatternSpec>, locales: &[Locale]) {
+    let mut phones = Vec::new();
+    let mut postal_codes = Vec::new();
+    for &locale in locales {
+        let pack = locale.pack();
+        phones.extend(pack.phone);
+        if let Some(postal_code) = pack.postal_code {
+            if !postal_codes.contains(&postal_code) {
+                postal_codes.push(postal_code);
+            }
+        }
+        if let Some((pii_type, pattern)) = pack.national_id {
+            add_alternatives(patterns, pii_type, &[pattern]);
+        }
+    }
+    add_alternatives(patterns, "phone", &phones);
+    add_alternatives(patterns, "postal_code", &postal_codes);
+}
+
+fn add_alternatives(patterns: &mut Vec<PatternSpec>, pii_type: &str, alternatives: &[&str]) {
+    if alternatives.is_empty() {
+        return;
+    }
+    let alternatives: Vec<String> = alternatives.iter().map(|a| format!("(?:{})", a)).collect();
+    match patterns.iter_mut().find(|spec| spec.name == pii_type) {
+        // The existing pattern stays first, so it wins at the same position
+        Some(spec) => {
+            spec.pattern = format!("(?:{})|{}", spec.pattern, alternatives.join("|"));
+        }
+        None => patterns.push(PatternSpec {
+            name: pii_type.to_string(),
+            pattern: alternatives.join("|"),
+            literals: DIGITS,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LOCALE_TYPES;
+    use crate::{DataCloakConfig, DataCloakEngine};
+
+    fn findings(locales: &[Locale], text: &str) -> Vec<(String, String)> {
+        let config = DataCloakConfig::builder()
+            .locales(locales.iter().copied())
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        engine
+            .detect_pii(text)
+            .unwrap()
+            .into_iter()
+            .map(|pii| (pii.pii_type, pii.sample))
+            .collect()
+    }
+
+    #[test]
+    fn test_locale_packs_add_international_formats() {
+        let text = "Call +44 20 7946 0958 or +49 30 12345678";
+        assert!(findings(&[], text).is_empty());
+
+        let found = findings(&[Locale::Gb, Locale::De], text);
+        assert_eq!(
+            found,
+            [
+                ("phone".to_string(), "+44 20 7946 0958".to_string()),
+                ("phone".to_string(), "+49 30 12345678".to_string()),
+            ]
+        );
+
+        let found = findings(&[Locale::Gb], "NINO: AB 12 34 56 C, postcode SW1A 1AA");
+        let types: Vec<_> = found.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(types, ["nino", "postal_code"]);
+    }
+
+    #[test]
+    fn test_regions_bundle_locales() {
+        let config = DataCloakConfig::builder()
+            .locales([Locale::Fr])
+            .regions([RegionProfile::Uk, RegionProfile::Eu])
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.all_locales(),
+            [
+                Locale::Fr,
+                Locale::Gb,
+                Locale::De,
+                Locale::Es,
+                Locale::It,
+                Locale::Nl
+            ]
+        );
+
+        let engine = DataCloakEngine::new(config).unwrap();
+        let found = engine
+            .detect_pii("Tel. +33 6 12 34 56 78, DNI 12345678Z, NINO: AB 12 34 56 C")
+            .unwrap();
+        let types: Vec<_> = found.iter().map(|pii| pii.pii_type.as_str()).collect();
+        assert_eq!(types, ["phone", "nino", "dni"]);
+    }
+
+    #[test]
+    fn test_locale_types_are_enabled_by_default() {
+        let all = [
+            Locale::Us,
+            Locale::Gb,
+            Locale::De,
+            Locale::Fr,
+            Locale::Es,
+            Locale::It,
+            Locale::Nl,
+            Locale::Ca,
+            Locale::Br,
+            Locale::In,
+        ];
+        let mut patterns = Vec::new();
+        add_locale_patterns(&mut patterns, &all);
+        for spec in &patterns {
+            assert!(
+                spec.name == "phone" || LOCALE_TYPES.contains(&spec.name.as_str()),
+                "{} is missing from LOCALE_TYPES",
+                spec.name
+            );
+        }
+    }
+}
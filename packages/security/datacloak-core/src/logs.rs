@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+
+use crate::document::FieldTotals;
+use crate::{DataCloakEngine, DataCloakError, DocumentMaskingResult, PIIDetectionResult};
+
+/// Log line layouts understood by `mask_log` and `detect_pii_log`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// RFC 5424 or RFC 3164 syslog; only the message is scanned.
+    Syslog,
+    /// Common or Combined Log Format; the identity, user, request line,
+    /// referrer and user agent are scanned.
+    CommonLog,
+    /// `key=value` pairs; every value except those of `LOGFMT_STANDARD_KEYS`
+    /// is scanned.
+    Logfmt,
+}
+
+/// logfmt keys holding timestamps, levels and identifiers, which look like
+/// numbers and codes rather than personal data.
+const LOGFMT_STANDARD_KEYS: &[&str] = &[
+    "ts",
+    "time",
+    "timestamp",
+    "level",
+    "lvl",
+    "severity",
+    "caller",
+    "source",
+    "logger",
+    "trace_id",
+    "span_id",
+    "request_id",
+    "duration",
+    "status",
+];
+
+/// A scannable value within a log line.
+#[derive(Debug, PartialEq)]
+struct LogField {
+    name: String,
+    start: usize,
+    end: usize,
+    /// The value sits between double quotes, which masks must not break.
+    quoted: bool,
+}
+
+impl DataCloakEngine {
+    /// Detects PII in the value fields of each line of `log`. Each finding's
+    /// `field_name` is `<line>:<field>`, such as `3:msg`, with offsets
+    /// relative to the field value. Lines that do not parse as `format` are
+    /// scanned whole as field `line`.
+    pub fn detect_pii_log(
+        &self,
+        log: &str,
+        format: LogFormat,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut findings = Vec::new();
+        for (index, line) in log.lines().enumerate() {
+            for field in log_fields(line, format) {
+                let value = &line[field.start..field.end];
+                findings.extend(self.detect_pii(value)?.into_iter().map(|mut pii| {
+                    pii.field_name = format!("{}:{}", index + 1, field.name);
+                    pii
+                }));
+            }
+        }
+        Ok(findings)
+    }
+
+    /// Masks the value fields of each line of `log`, leaving timestamps,
+    /// hosts, levels, status codes and the layout itself untouched. Masks
+    /// written into quoted values have their quotes escaped, and unquoted
+    /// logfmt values are quoted when a mask needs it.
+    pub fn mask_log(
+        &self,
+        log: &str,
+        format: LogFormat,
+    ) -> Result<DocumentMaskingResult, DataCloakError> {
+        let mut totals = FieldTotals::new();
+        let mut masked = String::with_capacity(log.len());
+
+        for (index, line) in log.split_inclusive('\n').enumerate() {
+            let content = line.trim_end_matches(['\n', '\r']);
+            let mut copied = 0;
+            for field in log_fields(content, format) {
+                let value = &content[field.start..field.end];
+                let label = format!("{}:{}", index + 1, field.name);
+                let result = totals.add(&label, self.mask_text(value)?);
+                if result == value {
+                    continue;
+                }
+                masked.push_str(&content[copied..field.start]);
+                if field.quoted {
+                    masked.push_str(&result.replace('"', "\\\""));
+                } else if format == LogFormat::Logfmt
+                    && result.contains(|c: char| c.is_whitespace() || c == '"' || c == '=')
+                {
+                    masked.push('"');
+                    masked.push_str(&result.replace('"', "\\\""));
+                    masked.push('"');
+                } else {
+                    masked.push_str(&result);
+                }
+                copied = field.end;
+            }
+            masked.push_str(&line[copied..]);
+        }
+
+        Ok(totals.into_document(masked))
+    }
+}
+
+fn log_fields(line: &str, format: LogFormat) -> Vec<LogField> {
+    let fields = match format {
+        LogFormat::Syslog => syslog_fields(line),
+        LogFormat::CommonLog => common_log_fields(line),
+        LogFormat::Logfmt => Some(logfmt_fields(line)),
+    };
+    fields.unwrap_or_else(|| {
+        vec![LogField {
+            name: "line".to_string(),
+            start: 0,
+            end: line.len(),
+            quoted: false,
+        }]
+    })
+}
+
+/// The message of an RFC 5424 line (`<PRI>1 TIMESTAMP HOST APP PROCID MSGID
+/// SD MSG`) or an RFC 3164 line (`<PRI>Mmm dd hh:mm:ss HOST TAG: MSG`).
+fn syslog_fields(line: &str) -> Option<Vec<LogField>> {
+    let rest = line.strip_prefix('<')?;
+    let close = rest.find('>')?;
+    if close == 0 || !rest[..close].bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let header = close + 2;
+
+    let start = if line[header..].starts_with("1 ") {
+        // VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID
+        let mut position = header;
+        for _ in 0..6 {
+            position += line[position..].find(' ')? + 1;
+        }
+        if line[position..].starts_with('-') {
+            position + 1
+        } else {
+            structured_data_end(line, position)?
+        }
+    } else {
+        // Mmm dd hh:mm:ss HOST TAG[PID]: MSG
+        let timestamp_end = header + 15;
+        let after_host = timestamp_end + 1 + line.get(timestamp_end + 1..)?.find(' ')?;
+        after_host + line[after_host..].find(": ")? + 1
+    };
+
+    let start = start + line[start..].len() - line[start..].trim_start().len();
+    Some(vec![LogField {
+        name: "msg".to_string(),
+        start,
+        end: line.len(),
+        quoted: false,
+    }])
+}
+
+/// End of the `[id param="value"]...` blocks starting at `start`.
+fn structured_data_end(line: &str, start: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut i = start;
+    while bytes.get(i) == Some(&b'[') {
+        let mut in_quotes = false;
+        loop {
+            i += 1;
+            match bytes.get(i)? {
+                b'\\' if in_quotes => i += 1,
+                b'"' => in_quotes = !in_quotes,
+                b']' if !in_quotes => break,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    Some(i)
+}
+
+/// `host ident authuser [date] "request" status bytes`, optionally followed
+/// by `"referer" "user-agent"`.
+fn common_log_fields(line: &str) -> Option<Vec<LogField>> {
+    let mut fields = Vec::new();
+    let mut position = line.find(' ')? + 1;
+    for name in ["ident", "authuser"] {
+        let end = position + line[position..].find(' ')?;
+        if &line[position..end] != "-" {
+            fields.push(LogField {
+                name: name.to_string(),
+                start: position,
+                end,
+                quoted: false,
+            });
+        }
+        position = end + 1;
+    }
+    let date = line[position..].strip_prefix('[')?;
+    position += 1 + date.find(']')? + 1;
+
+    for name in ["request", "", "", "referer", "user_agent"] {
+        position += line[position..].len() - line[position..].trim_start().len();
+        if position >= line.len() {
+            break;
+        }
+        if line[position..].starts_with('"') {
+            let end = quoted_end(line, position + 1);
+            if !name.is_empty() {
+                fields.push(LogField {
+                    name: name.to_string(),
+                    start: position + 1,
+                    end,
+                    quoted: true,
+                });
+            }
+            position = (end + 1).min(line.len());
+        } else {
+            // status and byte count
+            position += line[position..].find(' ').unwrap_or(line.len() - position);
+        }
+    }
+    Some(fields)
+}
+
+/// Every `key=value` or `key="value"` pair whose key is not standard.
+fn logfmt_fields(line: &str) -> Vec<LogField> {
+    let bytes = line.as_bytes();
+    let mut fields = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        while i < line.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let key_start = i;
+        while i < line.len() && bytes[i] != b'=' && !bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let key = &line[key_start..i];
+        if bytes.get(i) != Some(&b'=') {
+            continue;
+        }
+        i += 1;
+
+        let (start, end, quoted) = if bytes.get(i) == Some(&b'"') {
+            let end = quoted_end(line, i + 1);
+            (i + 1, end, true)
+        } else {
+            let start = i;
+            while i < line.len() && !bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            (start, i, false)
+        };
+        i = if quoted {
+            (end + 1).min(line.len())
+        } else {
+            end
+        };
+
+        if start < end && !LOGFMT_STANDARD_KEYS.contains(&key.to_ascii_lowercase().as_str()) {
+            fields.push(LogField {
+                name: key.to_string(),
+                start,
+                end,
+                quoted,
+            });
+        }
+    }
+    fields
+}
+
+/// Index of the closing `"` for a quoted value starting at `start`, honoring
+/// backslash escapes, or the end of the line if it is never closed.
+fn quoted_end(line: &str, start: usize) -> usize {
+    let bytes = line.as_bytes();
+    let mut i = start;
+    while i < line.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i,
+            _ => i += 1,
+        }
+    }
+    line.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    fn engine() -> DataCloakEngine {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        DataCloakEngine::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_logfmt_skips_standard_fields() {
+        let log = "ts=1705312345 level=info msg=\"login by a@example.com\" user=a@example.com\n";
+        let engine = engine();
+        assert!(engine
+            .detect_pii(log)
+            .unwrap()
+            .iter()
+            .any(|pii| pii.pii_type == "phone"));
+
+        let result = engine.mask_log(log, LogFormat::Logfmt).unwrap();
+        assert_eq!(
+            result.masked,
+            "ts=1705312345 level=info msg=\"login by [EMAIL_1]\" user=[EMAIL_1]\n"
+        );
+        let fields: Vec<&str> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.field_name.as_str())
+            .collect();
+        assert_eq!(fields, ["1:msg", "1:user"]);
+    }
+
+    #[test]
+    fn test_syslog_and_common_log_scope() {
+        let engine = engine();
+        let syslog = "<34>1 2024-01-15T10:23:45Z host app 4123 ID47 \
+                      [meta seq=\"5551234567\"] reset for a@example.com\n\
+                      <13>Jan 15 10:23:45 web01 sshd[4123]: login 555-123-4567";
+        let result = engine.mask_log(syslog, LogFormat::Syslog).unwrap();
+        assert_eq!(
+            result.masked,
+            "<34>1 2024-01-15T10:23:45Z host app 4123 ID47 \
+             [meta seq=\"5551234567\"] reset for [EMAIL_1]\n\
+             <13>Jan 15 10:23:45 web01 sshd[4123]: login [PHONE_1]"
+        );
+
+        let clf = "10.0.0.1 - bob@example.com [15/Jan/2024:10:23:45 +0000] \
+                   \"GET /a?to=c@example.com HTTP/1.1\" 200 5551234567 \"-\" \"curl\"";
+        let findings = engine.detect_pii_log(clf, LogFormat::CommonLog).unwrap();
+        let fields: Vec<&str> = findings.iter().map(|pii| pii.field_name.as_str()).collect();
+        assert_eq!(fields, ["1:authuser", "1:request"]);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::DataCloakError;
+
+/// A portable snapshot of the value→placeholder assignments made by an
+/// engine, used to keep numbering consistent across runs and instances.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenMapping {
+    pub entries: Vec<MappingEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MappingEntry {
+    pub pii_type: String,
+    pub value: String,
+    pub token: String,
+}
+
+/// How `import_mapping` resolves an imported entry that disagrees with an
+/// existing one, either because the value already has a different token or
+/// because the token is already assigned to a different value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the existing assignment and skip the imported entry.
+    KeepExisting,
+    /// Drop the conflicting existing assignments in favor of the imported one.
+    PreferImported,
+    /// Fail the whole import without applying any entries.
+    Reject,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeReport {
+    pub added: usize,
+    pub replaced: usize,
+    pub conflicts: usize,
+}
+
+type ValueKey = (String, String);
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PlaceholderRegistry {
+    by_value: HashMap<ValueKey, String>,
+    by_token: HashMap<String, ValueKey>,
+    counters: HashMap<String, usize>,
+}
+
+impl PlaceholderRegistry {
+    pub(crate) fn placeholder_for(&mut self, pii_type: &str, value: &str) -> String {
+        let key = (pii_type.to_string(), value.to_string());
+        if let Some(token) = self.by_value.get(&key) {
+            return token.clone();
+        }
+
+        let token = loop {
+            let n = self.counters.entry(pii_type.to_string()).or_insert(0);
+            *n += 1;
+            let candidate = format!("[{}_{}]", pii_type.to_uppercase(), n);
+            if !self.by_token.contains_key(&candidate) {
+                break candidate;
+            }
+        };
+
+        self.insert(key, token.clone());
+        token
+    }
+
+    pub(crate) fn export(&self) -> TokenMapping {
+        let mut entries: Vec<MappingEntry> = self
+            .by_value
+            .iter()
+            .map(|((pii_type, value), token)| MappingEntry {
+                pii_type: pii_type.clone(),
+                value: value.clone(),
+                token: token.clone(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.token.cmp(&b.token));
+        TokenMapping { entries }
+    }
+
+    pub(crate) fn import(
+        &mut self,
+        mapping: TokenMapping,
+        policy: MergePolicy,
+    ) -> Result<MergeReport, DataCloakError> {
+        // Work on a copy so a rejected import leaves the registry untouched
+        let mut merged = self.clone();
+        let mut report = MergeReport::default();
+
+        for entry in mapping.entries {
+            let key = (entry.pii_type.clone(), entry.value.clone());
+            let existing_token = merged.by_value.get(&key);
+            let existing_value = merged.by_token.get(&entry.token);
+
+            if existing_token == Some(&entry.token) {
+                continue;
+            }
+
+            if existing_token.is_none() && existing_value.is_none() {
+                merged.insert(key, entry.token);
+                report.added += 1;
+                continue;
+            }
+
+            match policy {
+                MergePolicy::Reject => {
+                    return Err(DataCloakError::MappingConflict { token: entry.token });
+                }
+                MergePolicy::KeepExisting => report.conflicts += 1,
+                MergePolicy::PreferImported => {
+                    if let Some(old_token) = merged.by_value.remove(&key) {
+                        merged.by_token.remove(&old_token);
+                    }
+                    if let Some(old_key) = merged.by_token.remove(&entry.token) {
+                        merged.by_value.remove(&old_key);
+                    }
+                    merged.insert(key, entry.token);
+                    report.replaced += 1;
+                }
+            }
+        }
+
+        *self = merged;
+        Ok(report)
+    }
+
+    fn insert(&mut self, key: ValueKey, token: String) {
+        // Keep counters ahead of imported numbers so new placeholders don't collide
+        if let Some(n) = token
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .rsplit_once('_')
+            .and_then(|(_, n)| n.parse::<usize>().ok())
+        {
+            let counter = self.counters.entry(key.0.clone()).or_insert(0);
+            *counter = (*counter).max(n);
+        }
+
+        self.by_token.insert(token.clone(), key.clone());
+        self.by_value.insert(key, token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(value: &str, token: &str) -> MappingEntry {
+        MappingEntry {
+            pii_type: "email".to_string(),
+            value: value.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_import_continues_numbering() {
+        let mut registry = PlaceholderRegistry::default();
+        let report = registry
+            .import(
+                TokenMapping {
+                    entries: vec![entry("a@example.com", "[EMAIL_4]")],
+                },
+                MergePolicy::Reject,
+            )
+            .unwrap();
+
+        assert_eq!(report.added, 1);
+        assert_eq!(
+            registry.placeholder_for("email", "a@example.com"),
+            "[EMAIL_4]"
+        );
+        assert_eq!(
+            registry.placeholder_for("email", "b@example.com"),
+            "[EMAIL_5]"
+        );
+    }
+
+    #[test]
+    fn test_merge_policies() {
+        let mut registry = PlaceholderRegistry::default();
+        registry.placeholder_for("email", "a@example.com");
+        let conflicting = TokenMapping {
+            entries: vec![entry("b@example.com", "[EMAIL_1]")],
+        };
+
+        assert!(registry
+            .import(conflicting.clone(), MergePolicy::Reject)
+            .is_err());
+
+        let report = registry
+            .import(conflicting.clone(), MergePolicy::KeepExisting)
+            .unwrap();
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(registry.export().entries[0].value, "a@example.com");
+
+        let report = registry
+            .import(conflicting, MergePolicy::PreferImported)
+            .unwrap();
+        assert_eq!(report.replaced, 1);
+        assert_eq!(
+            registry.export().entries,
+            vec![entry("b@example.com", "[EMAIL_1]")]
+        );
+    }
+}
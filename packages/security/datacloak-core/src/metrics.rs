@@ -0,0 +1,142 @@
+use prometheus::{
+    exponential_buckets, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry,
+};
+
+use crate::{DataCloakEngine, DataCloakError, Detections};
+
+/// Prometheus collectors for engine activity, attached to engines with
+/// `DataCloakEngine::with_metrics`.
+///
+/// Register one set per registry and share it between engines; values never
+/// include matched text, only types and counts.
+#[derive(Clone)]
+pub struct EngineMetrics {
+    findings: IntCounterVec,
+    scan_seconds: HistogramVec,
+    errors: IntCounterVec,
+    validation_rejections: IntCounter,
+}
+
+impl std::fmt::Debug for EngineMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EngineMetrics").finish_non_exhaustive()
+    }
+}
+
+impl EngineMetrics {
+    /// Creates the collectors and registers them with `registry`.
+    pub fn register(registry: &Registry) -> Result<Self, DataCloakError> {
+        let metrics = Self::new().map_err(metrics_error)?;
+        for collector in [
+            Box::new(metrics.findings.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(metrics.scan_seconds.clone()),
+            Box::new(metrics.errors.clone()),
+            Box::new(metrics.validation_rejections.clone()),
+        ] {
+            registry.register(collector).map_err(metrics_error)?;
+        }
+        Ok(metrics)
+    }
+
+    fn new() -> Result<Self, prometheus::Error> {
+        Ok(Self {
+            findings: IntCounterVec::new(
+                Opts::new("datacloak_findings_total", "PII findings by type"),
+                &["pii_type"],
+            )?,
+            scan_seconds: HistogramVec::new(
+                HistogramOpts::new(
+                    "datacloak_scan_duration_seconds",
+                    "Time spent per detection or masking call",
+                )
+                .buckets(exponential_buckets(0.0001, 4.0, 10)?),
+                &["operation"],
+            )?,
+            errors: IntCounterVec::new(
+                Opts::new("datacloak_errors_total", "Failed scans by error kind"),
+                &["kind"],
+            )?,
+            validation_rejections: IntCounter::new(
+                "datacloak_validation_rejections_total",
+                "Matches that failed format validation",
+            )?,
+        })
+    }
+
+    pub(crate) fn record_detection(&self, detections: &Detections) {
+        self.scan_seconds
+            .with_label_values(&["detect"])
+            .observe(detections.elapsed.as_secs_f64());
+        for pii in &detections.results {
+            self.findings.with_label_values(&[&pii.pii_type]).inc();
+        }
+        self.validation_rejections
+            .inc_by(u64::from(detections.validation.failed));
+    }
+
+    pub(crate) fn record_masking(&self, elapsed: std::time::Duration) {
+        self.scan_seconds
+            .with_label_values(&["mask"])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn record_error(&self, error: &DataCloakError) {
+        let kind = match error {
+            DataCloakError::PatternCompile { .. } => "pattern_compile",
+            DataCloakError::TextTooLarge { .. } => "text_too_large",
+            DataCloakError::Timeout { .. } => "timeout",
+            DataCloakError::Cancelled => "cancelled",
+            _ => "other",
+        };
+        self.errors.with_label_values(&[kind]).inc();
+    }
+}
+
+fn metrics_error(e: prometheus::Error) -> DataCloakError {
+    DataCloakError::InvalidConfig(format!("Failed to register metrics: {}", e))
+}
+
+impl DataCloakEngine {
+    /// Records detection counts, latencies, errors and validation rejections
+    /// of every scan in `metrics`.
+    pub fn with_metrics(mut self, metrics: EngineMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_metrics_record_findings_and_errors() {
+        let registry = Registry::new();
+        let metrics = EngineMetrics::register(&registry).unwrap();
+        let config = DataCloakConfig::builder()
+            .max_text_length(100)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config)
+            .unwrap()
+            .with_metrics(metrics.clone());
+
+        engine.mask_text("a@example.com and b@example.com").unwrap();
+        assert!(engine.detect_pii(&"x".repeat(200)).is_err());
+
+        assert_eq!(metrics.findings.with_label_values(&["email"]).get(), 2);
+        assert_eq!(
+            metrics.errors.with_label_values(&["text_too_large"]).get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .scan_seconds
+                .with_label_values(&["mask"])
+                .get_sample_count(),
+            1
+        );
+        assert!(EngineMetrics::register(&registry).is_err());
+    }
+}
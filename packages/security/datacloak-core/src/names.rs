@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use crate::{DataCloakConfig, DataCloakEngine, DataCloakError, PiiMatch, Visit};
+
+/// PII type of dictionary names, shared with the NER detector.
+const NAME_TYPE: &str = "person";
+
+/// Confidence of a lone first name, and of a lone surname, before its
+/// commonness is added. Surnames double as ordinary words more often.
+const FIRST_NAME_BASE: f64 = 0.5;
+const SURNAME_BASE: f64 = 0.4;
+
+/// Added to a lone name's confidence in proportion to its commonness, so
+/// the most frequent first name scores 0.85.
+const COMMONNESS_WEIGHT: f64 = 0.35;
+
+/// Compiled `first_names` and `surnames`: lowercase name → commonness,
+/// from 0 to 1 relative to the most frequent name in its list.
+#[derive(Debug, Default)]
+pub(crate) struct NameDictionary {
+    first_names: HashMap<String, f64>,
+    surnames: HashMap<String, f64>,
+}
+
+impl NameDictionary {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Self {
+        Self {
+            first_names: commonness(&config.first_names),
+            surnames: commonness(&config.surnames),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.first_names.is_empty() && self.surnames.is_empty()
+    }
+
+    /// Names in `text` as `(start, end, confidence)`. Only capitalized
+    /// words count; a first name directly followed by a surname is one
+    /// finding scoring higher than either alone.
+    fn find(&self, text: &str) -> Vec<(usize, usize, f64)> {
+        let words = words(text);
+        let mut found = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            let (start, end) = words[i];
+            let word = &text[start..end];
+            if !word.starts_with(char::is_uppercase) {
+                i += 1;
+                continue;
+            }
+            let first = self.first_score(word);
+            let surname = words
+                .get(i + 1)
+                .filter(|&&(next, _)| &text[end..next] == " ")
+                .and_then(|&(next, next_end)| {
+                    let word = &text[next..next_end];
+                    if !word.starts_with(char::is_uppercase) {
+                        return None;
+                    }
+                    Some((next_end, self.surname_score(word)?))
+                });
+
+            match (first, surname) {
+                (Some(first), Some((full_end, surname))) => {
+                    // Either word alone would have been evidence enough
+                    let confidence = 1.0 - (1.0 - first) * (1.0 - surname);
+                    found.push((start, full_end, confidence.min(0.99)));
+                    i += 2;
+                }
+                (Some(first), None) => {
+                    found.push((start, end, first));
+                    i += 1;
+                }
+                (None, _) => {
+                    if let Some(surname) = self.surname_score(word) {
+                        found.push((start, end, surname));
+                    }
+                    i += 1;
+                }
+            }
+        }
+        found
+    }
+
+    fn first_score(&self, word: &str) -> Option<f64> {
+        let commonness = self.first_names.get(&word.to_lowercase())?;
+        Some(FIRST_NAME_BASE + COMMONNESS_WEIGHT * commonness)
+    }
+
+    fn surname_score(&self, word: &str) -> Option<f64> {
+        let commonness = self.surnames.get(&word.to_lowercase())?;
+        Some(SURNAME_BASE + COMMONNESS_WEIGHT * commonness)
+    }
+
+    /// The confidence `find` gives `sample` when it is a name on its own.
+    pub(crate) fn score(&self, sample: &str) -> Option<f64> {
+        match self.find(sample).as_slice() {
+            [(0, end, confidence)] if *end == sample.len() => Some(*confidence),
+            _ => None,
+        }
+    }
+}
+
+impl DataCloakEngine {
+    /// Hands the dictionary names in `text` to `visit`, as `for_each_match`
+    /// does for pattern matches.
+    pub(crate) fn for_each_name<'a>(
+        &'a self,
+        text: &'a str,
+        visit: &mut impl FnMut(PiiMatch<'a>) -> Visit,
+    ) {
+        if self.names.is_empty() || !self.config.enabled_types.contains(NAME_TYPE) {
+            return;
+        }
+        let threshold = self.config.confidence_threshold_for(NAME_TYPE);
+        for (start, end, confidence) in self.names.find(text) {
+            let low_confidence = confidence <= threshold;
+            if low_confidence && !self.config.report_low_confidence {
+                continue;
+            }
+            let found = PiiMatch {
+                pii_type: NAME_TYPE,
+                sample: &text[start..end],
+                confidence,
+                start,
+                end,
+                low_confidence,
+            };
+            match visit(found) {
+                Visit::Continue => {}
+                Visit::NextType | Visit::Stop => break,
+            }
+        }
+    }
+
+    /// Whether `found` came from the name lists rather than a pattern.
+    pub(crate) fn is_dictionary_name(&self, found: &PiiMatch) -> bool {
+        found.pii_type == NAME_TYPE
+            && !self.config.custom_patterns.contains_key(NAME_TYPE)
+            && self.names.score(found.sample) == Some(found.confidence)
+    }
+}
+
+/// Parses a name list with one `name` or `name,frequency` per line, such
+/// as a census export. Blank lines and lines starting with `#` are
+/// skipped; names without a frequency count as 1.
+pub fn parse_name_list(list: &str) -> Result<HashMap<String, f64>, DataCloakError> {
+    let mut names = HashMap::new();
+    for (number, line) in list.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, frequency) = match line.split_once(',') {
+            Some((name, frequency)) => {
+                let frequency = frequency.trim().parse::<f64>().map_err(|_| {
+                    DataCloakError::InvalidConfig(format!(
+                        "Invalid frequency on line {} of name list: '{}'",
+                        number + 1,
+                        line
+                    ))
+                })?;
+                (name.trim(), frequency)
+            }
+            None => (line, 1.0),
+        };
+        names.insert(name.to_string(), frequency);
+    }
+    Ok(names)
+}
+
+fn commonness(list: &HashMap<String, f64>) -> HashMap<String, f64> {
+    let max = list.values().copied().fold(0.0, f64::max);
+    list.iter()
+        .map(|(name, frequency)| {
+            // Square root so mid-frequency names aren't all scored as rare
+            (name.to_lowercase(), (frequency / max).sqrt())
+        })
+        .collect()
+}
+
+/// Byte ranges of the words in `text`: letters with inner apostrophes or
+/// hyphens, as in `O'Brien` or `Smith-Jones`.
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let joins = matches!(c, '\'' | '’' | '-')
+            && start.is_some()
+            && chars.peek().is_some_and(|&(_, next)| next.is_alphabetic());
+        if c.is_alphabetic() || joins {
+            start.get_or_insert(i);
+        } else if let Some(s) = start.take() {
+            words.push((s, i));
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, text.len()));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> DataCloakEngine {
+        let config = DataCloakConfig::builder()
+            .first_names([("John", 3.2), ("Mary", 2.6), ("Will", 0.2)])
+            .surnames([("Smith", 1.0), ("O'Brien", 0.05)])
+            .build()
+            .unwrap();
+        DataCloakEngine::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_dictionary_finds_capitalized_names() {
+        let engine = engine();
+        let findings = engine
+            .detect_pii("Ask John Smith or mary. Mary O'Brien will call; Will may not.")
+            .unwrap();
+        let names: Vec<_> = findings
+            .iter()
+            .map(|pii| (pii.sample.as_str(), pii.confidence))
+            .collect();
+
+        // "Will" is too rare a first name to report on its own
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0].0, "John Smith");
+        assert!(names[0].1 > 0.95);
+        assert_eq!(names[1].0, "Mary O'Brien");
+        assert!(names[1].1 > 0.8);
+    }
+
+    #[test]
+    fn test_name_lists_parse_with_frequencies() {
+        let names = parse_name_list("# census\nJohn,3.271\n\nMary, 2.629\nZelda\n").unwrap();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names["Mary"], 2.629);
+        assert_eq!(names["Zelda"], 1.0);
+        assert!(parse_name_list("John,often").is_err());
+        assert!(DataCloakConfig::builder()
+            .first_names([("John", 0.0)])
+            .build()
+            .is_err());
+    }
+}
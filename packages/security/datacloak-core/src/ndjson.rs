@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{DataCloakEngine, DataCloakError, PIIDetectionResult};
+
+/// One finding in an NDJSON line. Samples and masked values are left out so
+/// the sidecar can be stored next to the masked output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NdjsonFinding {
+    /// JSON pointer of the string holding the finding; empty for a
+    /// malformed line masked as plain text.
+    pub pointer: String,
+    pub pii_type: String,
+    pub confidence: f64,
+    /// Byte offsets within the string.
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Sidecar entry for a line that had findings or could not be parsed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NdjsonLineReport {
+    /// One-based line number in the input.
+    pub line: u64,
+    pub findings: Vec<NdjsonFinding>,
+    /// Why the line was not valid JSON. Such lines are masked as plain text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Totals from `process_ndjson`, plus the per-line sidecar in input order.
+/// Writing each `sidecar` entry as one JSON line gives a findings file that
+/// lines up with the masked output by line number.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NdjsonReport {
+    /// Lines read, including blank and malformed ones.
+    pub lines: u64,
+    pub malformed: u64,
+    pub counts_by_type: HashMap<String, u64>,
+    /// Set when any line hit a match limit or the memory budget.
+    pub limits_exceeded: bool,
+    pub sidecar: Vec<NdjsonLineReport>,
+}
+
+impl DataCloakEngine {
+    /// Masks newline-delimited JSON from `reader` into `writer`, one line at
+    /// a time. Each line is parsed and masked with `mask_json` on its own.
+    /// A line that is not valid JSON does not stop the run: it is masked as
+    /// plain text, written out, and reported with its error in the sidecar.
+    /// Blank lines are copied through.
+    pub fn process_ndjson<R: Read, W: Write>(
+        &self,
+        reader: R,
+        mut writer: W,
+    ) -> Result<NdjsonReport, DataCloakError> {
+        let mut reader = BufReader::new(reader);
+        let mut report = NdjsonReport::default();
+        let mut buffer = Vec::new();
+
+        loop {
+            buffer.clear();
+            let read = reader
+                .read_until(b'\n', &mut buffer)
+                .map_err(|e| DataCloakError::Io(e.to_string()))?;
+            if read == 0 {
+                break;
+            }
+            report.lines += 1;
+            let line = trim_line_ending(&buffer);
+
+            let (masked, findings, limited, error) = if line.iter().all(u8::is_ascii_whitespace) {
+                (
+                    String::from_utf8_lossy(line).into_owned(),
+                    Vec::new(),
+                    false,
+                    None,
+                )
+            } else {
+                match parse_line(line) {
+                    Ok(document) => {
+                        let result = self.mask_json(&document)?;
+                        let masked = serde_json::to_string(&result.masked)
+                            .map_err(|e| DataCloakError::Internal(e.to_string()))?;
+                        let limited = result.metadata.limits_exceeded;
+                        (masked, result.detected_pii, limited, None)
+                    }
+                    Err(error) => {
+                        report.malformed += 1;
+                        let result = self.mask_text(&String::from_utf8_lossy(line))?;
+                        let limited = result.metadata.limits_exceeded;
+                        (
+                            result.masked_text,
+                            result.detected_pii,
+                            limited,
+                            Some(error),
+                        )
+                    }
+                }
+            };
+
+            writer
+                .write_all(masked.as_bytes())
+                .and_then(|()| writer.write_all(b"\n"))
+                .map_err(|e| DataCloakError::Io(e.to_string()))?;
+
+            report.limits_exceeded |= limited;
+            for pii in &findings {
+                *report
+                    .counts_by_type
+                    .entry(pii.pii_type.clone())
+                    .or_insert(0) += 1;
+            }
+            if !findings.is_empty() || error.is_some() {
+                report.sidecar.push(NdjsonLineReport {
+                    line: report.lines,
+                    findings: findings.into_iter().map(NdjsonFinding::from).collect(),
+                    error,
+                });
+            }
+        }
+
+        writer
+            .flush()
+            .map_err(|e| DataCloakError::Io(e.to_string()))?;
+        Ok(report)
+    }
+}
+
+impl From<PIIDetectionResult> for NdjsonFinding {
+    fn from(pii: PIIDetectionResult) -> Self {
+        Self {
+            pointer: pii.field_name,
+            pii_type: pii.pii_type,
+            confidence: pii.confidence,
+            start: pii.start,
+            end: pii.end,
+        }
+    }
+}
+
+fn parse_line(line: &[u8]) -> Result<Value, String> {
+    let text = std::str::from_utf8(line).map_err(|e| e.to_string())?;
+    serde_json::from_str(text).map_err(|e| e.to_string())
+}
+
+fn trim_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    #[test]
+    fn test_process_ndjson_survives_malformed_lines() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let input = "{\"user\":\"a@example.com\"}\n\
+                     {not json a@example.com\n\
+                     \n\
+                     {\"msg\":\"nothing here\"}\n";
+
+        let mut output = Vec::new();
+        let report = engine
+            .process_ndjson(input.as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"user\":\"[EMAIL_1]\"}\n{not json [EMAIL_1]\n\n{\"msg\":\"nothing here\"}\n"
+        );
+        assert_eq!(report.lines, 4);
+        assert_eq!(report.malformed, 1);
+        assert_eq!(report.counts_by_type["email"], 2);
+
+        assert_eq!(report.sidecar.len(), 2);
+        assert_eq!(report.sidecar[0].line, 1);
+        assert_eq!(report.sidecar[0].findings[0].pointer, "/user");
+        assert_eq!(report.sidecar[1].line, 2);
+        assert!(report.sidecar[1].error.is_some());
+    }
+}
@@ -0,0 +1,350 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokenizers::{Encoding, Tokenizer, TruncationParams};
+use tract_onnx::prelude::*;
+
+use crate::{DataCloakEngine, DataCloakError, PiiMatch, Visit};
+
+/// Tokens per model input; BERT-style models accept at most 512.
+const MAX_TOKENS: usize = 512;
+
+/// Tokens shared by consecutive inputs of a long text, so an entity cut by
+/// one input's end is seen whole in the next.
+const INPUT_OVERLAP: usize = 32;
+
+/// Entity label suffixes reported, and the PII types they map to. Other
+/// entities, such as `LOC` or `MISC`, are ignored.
+const ENTITY_LABELS: &[(&str, &str)] = &[("PER", "person"), ("ORG", "organization")];
+
+/// An ONNX token-classification model, such as a BERT NER fine-tune, with
+/// its tokenizer. Finds the `person` and `organization` types the regex
+/// patterns cannot.
+pub struct NerModel {
+    plan: TypedRunnableModel<TypedModel>,
+    tokenizer: Tokenizer,
+    labels: Vec<Option<EntityLabel>>,
+}
+
+/// A label of the model's output, parsed from BIO (or BIOES) notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EntityLabel {
+    pii_type: &'static str,
+    /// `B-` and `S-` labels start an entity even right after another one.
+    begins: bool,
+}
+
+/// The most likely label of one token.
+#[derive(Debug, Clone, Copy)]
+struct TokenPrediction {
+    label: usize,
+    probability: f32,
+    offsets: (usize, usize),
+    special: bool,
+    word: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct NerEntity {
+    pub(crate) pii_type: &'static str,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    /// Mean probability of the entity's tokens.
+    pub(crate) confidence: f64,
+}
+
+impl NerModel {
+    /// Loads the model at `model_path` and the `tokenizer.json` it was
+    /// trained with. `labels` are the model's output classes in order, as
+    /// in its `id2label`, e.g. `["O", "B-PER", "I-PER", "B-ORG", "I-ORG"]`.
+    pub fn load(
+        model_path: impl AsRef<Path>,
+        tokenizer_path: impl AsRef<Path>,
+        labels: &[&str],
+    ) -> Result<Self, DataCloakError> {
+        let plan = tract_onnx::onnx()
+            .model_for_path(model_path)
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(model_error)?;
+
+        let mut tokenizer = Tokenizer::from_file(tokenizer_path).map_err(model_error)?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS,
+                stride: INPUT_OVERLAP,
+                ..Default::default()
+            }))
+            .map_err(model_error)?;
+
+        Ok(Self {
+            plan,
+            tokenizer,
+            labels: labels.iter().map(|label| parse_label(label)).collect(),
+        })
+    }
+
+    /// Entities in `text`, ordered by position, with byte offsets.
+    pub(crate) fn entities(&self, text: &str) -> Result<Vec<NerEntity>, DataCloakError> {
+        let encoding = self.tokenizer.encode(text, true).map_err(model_error)?;
+
+        let mut entities: Vec<NerEntity> = Vec::new();
+        for input in std::iter::once(&encoding).chain(encoding.get_overflowing()) {
+            let predictions = self.predict(input).map_err(model_error)?;
+            for entity in group_entities(&self.labels, &predictions) {
+                // Overlapping inputs see boundary entities twice; the longer
+                // sighting is the one that wasn't cut off
+                let seen = entities
+                    .iter_mut()
+                    .find(|seen| seen.start < entity.end && entity.start < seen.end);
+                match seen {
+                    Some(seen) if seen.end - seen.start < entity.end - entity.start => {
+                        *seen = entity
+                    }
+                    Some(_) => {}
+                    None => entities.push(entity),
+                }
+            }
+        }
+        entities.sort_by_key(|entity| entity.start);
+        Ok(entities)
+    }
+
+    fn predict(&self, input: &Encoding) -> TractResult<Vec<TokenPrediction>> {
+        let len = input.len();
+        let tensor = |values: &[u32]| -> TractResult<TValue> {
+            let values = values.iter().map(|&v| i64::from(v)).collect();
+            let array = tract_ndarray::Array2::from_shape_vec((1, len), values)?;
+            Ok(array.into_tensor().into())
+        };
+        let mut inputs = tvec!(
+            tensor(input.get_ids())?,
+            tensor(input.get_attention_mask())?
+        );
+        if self.plan.model().inputs.len() > 2 {
+            inputs.push(tensor(input.get_type_ids())?);
+        }
+
+        let outputs = self.plan.run(inputs)?;
+        let logits = outputs[0]
+            .to_array_view::<f32>()?
+            .into_dimensionality::<tract_ndarray::Ix3>()?;
+
+        let mut predictions = Vec::with_capacity(len);
+        for (token, scores) in logits
+            .index_axis(tract_ndarray::Axis(0), 0)
+            .outer_iter()
+            .enumerate()
+        {
+            let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let total: f32 = scores.iter().map(|score| (score - max).exp()).sum();
+            let label = scores.iter().position(|&score| score == max).unwrap_or(0);
+            predictions.push(TokenPrediction {
+                label,
+                probability: 1.0 / total,
+                offsets: input.get_offsets()[token],
+                special: input.get_special_tokens_mask()[token] == 1,
+                word: input.get_word_ids()[token],
+            });
+        }
+        Ok(predictions)
+    }
+}
+
+impl fmt::Debug for NerModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NerModel")
+            .field("labels", &self.labels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DataCloakEngine {
+    /// Detects `person` and `organization` with `model`, alongside the
+    /// patterns. Entities are reported with the model's probability as
+    /// confidence and masked like any other finding.
+    pub fn with_ner_model(mut self, model: Arc<NerModel>) -> Self {
+        self.ner = Some(model);
+        self
+    }
+
+    /// Hands the enabled entities in `text` to `visit`, as `for_each_match`
+    /// does for pattern matches.
+    pub(crate) fn for_each_entity<'a>(
+        &'a self,
+        text: &'a str,
+        visit: &mut impl FnMut(PiiMatch<'a>) -> Visit,
+    ) -> Result<(), DataCloakError> {
+        let Some(model) = &self.ner else {
+            return Ok(());
+        };
+        let mut finished: Vec<&str> = Vec::new();
+        for entity in model.entities(text)? {
+            if finished.contains(&entity.pii_type)
+                || !self.config.enabled_types.contains(entity.pii_type)
+            {
+                continue;
+            }
+            let threshold = self.config.confidence_threshold_for(entity.pii_type);
+            let low_confidence = entity.confidence <= threshold;
+            if low_confidence && !self.config.report_low_confidence {
+                continue;
+            }
+
+            let found = PiiMatch {
+                pii_type: entity.pii_type,
+                sample: &text[entity.start..entity.end],
+                confidence: entity.confidence,
+                start: entity.start,
+                end: entity.end,
+                low_confidence,
+            };
+            match visit(found) {
+                Visit::Continue => {}
+                Visit::NextType => finished.push(entity.pii_type),
+                Visit::Stop => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `found` came from the NER model rather than a pattern.
+    pub(crate) fn is_entity(&self, found: &PiiMatch) -> bool {
+        self.ner.is_some()
+            && ENTITY_LABELS.iter().any(|&(_, t)| t == found.pii_type)
+            && !self.config.custom_patterns.contains_key(found.pii_type)
+    }
+}
+
+fn parse_label(label: &str) -> Option<EntityLabel> {
+    let (begins, entity) = match label.split_once('-') {
+        Some(("B" | "S", entity)) => (true, entity),
+        Some(("I" | "E", entity)) => (false, entity),
+        Some(_) => return None,
+        None => (false, label),
+    };
+    ENTITY_LABELS
+        .iter()
+        .find(|&&(suffix, _)| suffix == entity)
+        .map(|&(_, pii_type)| EntityLabel { pii_type, begins })
+}
+
+/// Joins consecutive tokens of the same entity type. A `B-` label starts a
+/// new entity unless the token continues the previous token's word.
+fn group_entities(labels: &[Option<EntityLabel>], tokens: &[TokenPrediction]) -> Vec<NerEntity> {
+    let mut entities = Vec::new();
+    let mut current: Option<(NerEntity, f64, usize)> = None;
+    let mut previous_word = None;
+
+    let mut close = |current: &mut Option<(NerEntity, f64, usize)>| {
+        if let Some((mut entity, total, count)) = current.take() {
+            entity.confidence = total / count as f64;
+            entities.push(entity);
+        }
+    };
+
+    for token in tokens {
+        let label = labels.get(token.label).copied().flatten();
+        let same_word = token.word.is_some() && token.word == previous_word;
+        previous_word = token.word;
+        let Some(label) = label.filter(|_| !token.special) else {
+            close(&mut current);
+            continue;
+        };
+
+        match &mut current {
+            Some((entity, total, count))
+                if entity.pii_type == label.pii_type && (!label.begins || same_word) =>
+            {
+                entity.end = token.offsets.1;
+                *total += f64::from(token.probability);
+                *count += 1;
+            }
+            _ => {
+                close(&mut current);
+                let entity = NerEntity {
+                    pii_type: label.pii_type,
+                    start: token.offsets.0,
+                    end: token.offsets.1,
+                    confidence: 0.0,
+                };
+                current = Some((entity, f64::from(token.probability), 1));
+            }
+        }
+    }
+    close(&mut current);
+    entities
+}
+
+fn model_error(error: impl fmt::Display) -> DataCloakError {
+    DataCloakError::Model(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABELS: &[&str] = &["O", "B-PER", "I-PER", "B-ORG", "I-ORG", "B-LOC"];
+
+    fn token(label: usize, offsets: (usize, usize), word: u32) -> TokenPrediction {
+        TokenPrediction {
+            label,
+            probability: 0.9,
+            offsets,
+            special: false,
+            word: Some(word),
+        }
+    }
+
+    #[test]
+    fn test_labels_map_to_pii_types() {
+        assert_eq!(parse_label("O"), None);
+        assert_eq!(parse_label("B-LOC"), None);
+        assert_eq!(
+            parse_label("B-PER"),
+            Some(EntityLabel {
+                pii_type: "person",
+                begins: true
+            })
+        );
+        assert_eq!(
+            parse_label("ORG"),
+            Some(EntityLabel {
+                pii_type: "organization",
+                begins: false
+            })
+        );
+    }
+
+    #[test]
+    fn test_tokens_group_into_entities() {
+        let labels: Vec<_> = LABELS.iter().map(|label| parse_label(label)).collect();
+        // "[CLS] Ada Love ##lace , Acme Corp Paris [SEP]"
+        let mut cls = token(1, (0, 0), 0);
+        cls.special = true;
+        cls.word = None;
+        let tokens = [
+            cls,
+            token(1, (0, 3), 0),
+            token(2, (4, 8), 1),
+            token(1, (8, 12), 1),
+            token(0, (12, 13), 2),
+            token(3, (14, 18), 3),
+            token(4, (19, 23), 4),
+            token(5, (24, 29), 5),
+        ];
+
+        let entities = group_entities(&labels, &tokens);
+        assert_eq!(entities.len(), 2);
+        assert_eq!(
+            (entities[0].pii_type, entities[0].start, entities[0].end),
+            ("person", 0, 12)
+        );
+        assert_eq!(
+            (entities[1].pii_type, entities[1].start, entities[1].end),
+            ("organization", 14, 23)
+        );
+        assert!((entities[0].confidence - 0.9).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::{DataCloakEngine, PiiMatch, Visit};
+
+/// Confidence of an address with a bracketed `[at]`, which is rarely
+/// anything but an email.
+const BRACKETED_CONFIDENCE: f64 = 0.95;
+
+/// Confidence of an address spelled with a plain ` at `. These also need an
+/// obfuscated dot, so `see you at example.com` isn't an address.
+const SPELLED_CONFIDENCE: f64 = 0.85;
+
+/// An email with `@` written as `[at]`, `(at)`, `{at}`, `<at>` or ` at `,
+/// and dots as `.` or any of those forms of `dot`.
+fn obfuscated_email() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?xi)
+            \b(?P<local>[a-z0-9._%+-]+)
+            (?:(?P<bracketed>\s*[\[({<]\s*at\s*[\])}>]\s*)|\s+at\s+)
+            (?P<domain>(?:[a-z0-9-]+(?:\s*[\[({<]\s*dot\s*[\])}>]\s*|\s+dot\s+|\.))+[a-z]{2,})
+            \b",
+        )
+        .expect("obfuscated email pattern is valid")
+    })
+}
+
+/// A `dot` in any of its obfuscated forms.
+fn obfuscated_dot() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)\s*[\[({<]\s*dot\s*[\])}>]\s*|\s+dot\s+")
+            .expect("obfuscated dot pattern is valid")
+    })
+}
+
+/// The address an obfuscated email spells and the confidence it is one,
+/// or `None` if the match doesn't qualify.
+fn address(captures: &Captures) -> Option<(String, f64)> {
+    let domain = &captures["domain"];
+    let confidence = if captures.name("bracketed").is_some() {
+        BRACKETED_CONFIDENCE
+    } else if obfuscated_dot().is_match(domain) {
+        SPELLED_CONFIDENCE
+    } else {
+        return None;
+    };
+    let domain = obfuscated_dot().replace_all(domain, ".");
+    Some((format!("{}@{}", &captures["local"], domain), confidence))
+}
+
+/// The address `sample` spells, if it is an obfuscated email as a whole.
+pub(crate) fn deobfuscate_email(sample: &str) -> Option<String> {
+    if sample.contains('@') {
+        return None;
+    }
+    let captures = obfuscated_email().captures(sample)?;
+    let whole = captures.get(0)?;
+    if whole.start() != 0 || whole.end() != sample.len() {
+        return None;
+    }
+    address(&captures).map(|(address, _)| address)
+}
+
+impl DataCloakEngine {
+    /// Hands the obfuscated emails in `text` to `visit`, as `for_each_match`
+    /// does for pattern matches. Their `normalized` form is the plain
+    /// address, which is what gets masked.
+    pub(crate) fn for_each_obfuscated_email<'a>(
+        &'a self,
+        text: &'a str,
+        visit: &mut impl FnMut(PiiMatch<'a>) -> Visit,
+    ) {
+        if !self.config.obfuscated_emails || !self.config.enabled_types.contains("email") {
+            return;
+        }
+        let threshold = self.config.confidence_threshold_for("email");
+        for captures in obfuscated_email().captures_iter(text) {
+            let (Some(whole), Some((_, confidence))) = (captures.get(0), address(&captures)) else {
+                continue;
+            };
+            let low_confidence = confidence <= threshold;
+            if low_confidence && !self.config.report_low_confidence {
+                continue;
+            }
+            let found = PiiMatch {
+                pii_type: "email",
+                sample: whole.as_str(),
+                confidence,
+                start: whole.start(),
+                end: whole.end(),
+                low_confidence,
+            };
+            match visit(found) {
+                Visit::Continue => {}
+                Visit::NextType | Visit::Stop => break,
+            }
+        }
+    }
+
+    /// Whether `found` came from `for_each_obfuscated_email`.
+    pub(crate) fn is_obfuscated_email(&self, found: &PiiMatch) -> bool {
+        found.pii_type == "email" && deobfuscate_email(found.sample).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_obfuscated_emails_are_normalized() {
+        assert_eq!(
+            deobfuscate_email("john.smith [at] example [dot] co [dot] uk").as_deref(),
+            Some("john.smith@example.co.uk")
+        );
+        assert_eq!(
+            deobfuscate_email("jane(AT)example.com").as_deref(),
+            Some("jane@example.com")
+        );
+        assert_eq!(
+            deobfuscate_email("john at example dot com").as_deref(),
+            Some("john@example.com")
+        );
+        // A plain "at" needs an obfuscated dot too
+        assert_eq!(deobfuscate_email("us at example.com"), None);
+        assert_eq!(deobfuscate_email("john@example.com"), None);
+    }
+
+    #[test]
+    fn test_obfuscated_emails_are_masked() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let result = engine
+            .mask_text("Write john at example dot com or visit us at example.com")
+            .unwrap();
+
+        assert_eq!(result.detected_pii.len(), 1);
+        let pii = &result.detected_pii[0];
+        assert_eq!(pii.sample, "john at example dot com");
+        assert_eq!(pii.normalized.as_deref(), Some("john@example.com"));
+        assert_eq!(
+            result.masked_text,
+            "Write j***@example.com or visit us at example.com"
+        );
+
+        let config = DataCloakConfig::builder()
+            .obfuscated_emails(false)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        assert!(engine
+            .detect_pii("john [at] example.com")
+            .unwrap()
+            .is_empty());
+    }
+}
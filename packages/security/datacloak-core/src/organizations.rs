@@ -0,0 +1,281 @@
+use std::sync::OnceLock;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{DataCloakConfig, DataCloakEngine, DataCloakError, PiiMatch, Visit};
+
+/// PII type of organization names, shared with the NER detector.
+const ORGANIZATION_TYPE: &str = "organization";
+
+/// Confidence of a name from `organization_names`.
+const LISTED_CONFIDENCE: f64 = 0.95;
+
+/// Confidence of capitalized words ending in a legal-form suffix.
+const SUFFIX_CONFIDENCE: f64 = 0.9;
+
+/// Legal forms, optionally after a comma: `, Ltd`, ` GmbH`, ` Inc.`.
+const LEGAL_FORM: &str = r"(?x)
+    ,?[\ ]
+    (?P<form>Inc|Incorporated|Corp|Corporation|Co|Ltd|Limited|LLC|L\.L\.C|LLP|LP|PLC|plc
+      |GmbH|AG|KG|SE|S\.A|SA|SAS|S\.p\.A|SpA|SRL|S\.r\.l|B\.V|BV|N\.V|NV|Oy|AB
+      |Pty[\ ]Ltd)
+    \b\.?";
+
+/// Legal forms that are as often abbreviations or words (`SE` for
+/// south-east, `AB` for a blood type), only trusted after a proper name.
+const AMBIGUOUS_FORMS: &[&str] = &["SE", "SA", "AB", "AG"];
+
+/// Capitalized words that begin sentences rather than names, left out of
+/// the organization they precede: `Call Nokia Oy`.
+const LEADING_WORDS: &[&str] = &[
+    "A", "An", "The", "And", "Or", "But", "If", "When", "From", "To", "At", "For", "With", "By",
+    "In", "On", "Our", "Your", "My", "We", "Dear", "Hi", "Hello", "Please", "Thanks", "Ask",
+    "Call", "Contact", "Email", "Join", "Pay", "See", "Send", "Try", "Use", "Visit",
+];
+
+/// Up to six capitalized words (joined by `&`, `and`, `of` or `the`)
+/// followed by a legal form: `Acme Widgets Inc.`, `Bank of the West, Ltd`,
+/// `Müller & Söhne GmbH`. Words may not start with a digit.
+fn suffix_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?x)
+            \b\p{{Lu}}[\p{{L}}\d&'’.-]*
+            (?:[\ ](?:&|and|of|the|\p{{Lu}}[\p{{L}}\d&'’.-]*)){{0,5}}
+            {}",
+            LEGAL_FORM
+        ))
+        .expect("organization suffix pattern is valid")
+    })
+}
+
+/// A legal form at the start of the text.
+fn leading_form_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(&format!(r"^(?:{})", LEGAL_FORM)).expect("legal form pattern is valid")
+    })
+}
+
+/// Matches of `suffix_pattern` in `text` as `(start, end)`, without leading
+/// words, and with ambiguous forms only after a name of three or more
+/// letters that doesn't follow a house number.
+fn suffixed_organizations(text: &str) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    for caps in suffix_pattern().captures_iter(text) {
+        let whole = caps.get(0).expect("group 0 is always present");
+        let form = caps.name("form").expect("the form group always matches");
+        let mut start = whole.start();
+        let mut stripped = false;
+        while let Some((word, _)) = text[start..form.start()].split_once(' ') {
+            if !LEADING_WORDS.contains(&word) {
+                break;
+            }
+            start += word.len() + 1;
+            stripped = true;
+        }
+        let name = text[start..form.start()].trim_end_matches([' ', ',']);
+        let Some(last_word) = name.rsplit(' ').next().filter(|word| !word.is_empty()) else {
+            continue;
+        };
+        if AMBIGUOUS_FORMS.contains(&form.as_str()) {
+            let proper_name = last_word.chars().count() >= 3;
+            let after_number = text[..start]
+                .trim_end()
+                .ends_with(|c: char| c.is_ascii_digit());
+            if stripped || !proper_name || after_number {
+                continue;
+            }
+        }
+        found.push((start, whole.end()));
+    }
+    found
+}
+
+/// Compiled `organization_names`.
+#[derive(Debug, Default)]
+pub(crate) struct OrganizationDictionary {
+    listed: Option<Regex>,
+}
+
+impl OrganizationDictionary {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Result<Self, DataCloakError> {
+        if config.organization_names.is_empty() {
+            return Ok(Self::default());
+        }
+        // Longest first, so `Acme Corp` wins over `Acme`
+        let mut names: Vec<&str> = config.organization_names.iter().map(|n| n.trim()).collect();
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        let alternatives: Vec<String> = names
+            .iter()
+            .map(|name| {
+                let word_edge = |c: Option<char>| match c {
+                    Some(c) if c.is_alphanumeric() => r"\b",
+                    _ => "",
+                };
+                format!(
+                    "{}{}{}",
+                    word_edge(name.chars().next()),
+                    regex::escape(name),
+                    word_edge(name.chars().next_back())
+                )
+            })
+            .collect();
+
+        let listed = RegexBuilder::new(&alternatives.join("|"))
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| DataCloakError::PatternCompile {
+                name: ORGANIZATION_TYPE.to_string(),
+                message: e.to_string(),
+            })?;
+        Ok(Self {
+            listed: Some(listed),
+        })
+    }
+
+    /// Organizations in `text` as `(start, end, confidence)`, ordered by
+    /// position. Listed names extend over a legal form after them, and take
+    /// precedence over suffix matches they overlap.
+    fn find(&self, text: &str) -> Vec<(usize, usize, f64)> {
+        let mut found: Vec<_> = self
+            .listed
+            .iter()
+            .flat_map(|listed| listed.find_iter(text))
+            .map(|m| {
+                let form = leading_form_pattern().find(&text[m.end()..]);
+                let end = m.end() + form.map_or(0, |form| form.end());
+                (m.start(), end, LISTED_CONFIDENCE)
+            })
+            .collect();
+        for (suffix_start, suffix_end) in suffixed_organizations(text) {
+            let overlaps = found
+                .iter()
+                .any(|&(start, end, _)| start < suffix_end && suffix_start < end);
+            if !overlaps {
+                found.push((suffix_start, suffix_end, SUFFIX_CONFIDENCE));
+            }
+        }
+        found.sort_by_key(|&(start, ..)| start);
+        found
+    }
+
+    /// The confidence `find` gives `sample` when it is an organization on
+    /// its own.
+    pub(crate) fn score(&self, sample: &str) -> Option<f64> {
+        match self.find(sample).as_slice() {
+            [(0, end, confidence)] if *end == sample.len() => Some(*confidence),
+            _ => None,
+        }
+    }
+}
+
+impl DataCloakEngine {
+    /// Hands the organization names in `text` to `visit`, as
+    /// `for_each_match` does for pattern matches.
+    pub(crate) fn for_each_organization<'a>(
+        &'a self,
+        text: &'a str,
+        visit: &mut impl FnMut(PiiMatch<'a>) -> Visit,
+    ) {
+        if !self.config.enabled_types.contains(ORGANIZATION_TYPE) {
+            return;
+        }
+        let threshold = self.config.confidence_threshold_for(ORGANIZATION_TYPE);
+        for (start, end, confidence) in self.organizations.find(text) {
+            let low_confidence = confidence <= threshold;
+            if low_confidence && !self.config.report_low_confidence {
+                continue;
+            }
+            let found = PiiMatch {
+                pii_type: ORGANIZATION_TYPE,
+                sample: &text[start..end],
+                confidence,
+                start,
+                end,
+                low_confidence,
+            };
+            match visit(found) {
+                Visit::Continue => {}
+                Visit::NextType | Visit::Stop => break,
+            }
+        }
+    }
+
+    /// Whether `found` came from the organization heuristics rather than a
+    /// pattern.
+    pub(crate) fn is_listed_or_suffixed(&self, found: &PiiMatch) -> bool {
+        found.pii_type == ORGANIZATION_TYPE
+            && !self.config.custom_patterns.contains_key(ORGANIZATION_TYPE)
+            && self.organizations.score(found.sample) == Some(found.confidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(names: &[&str]) -> DataCloakEngine {
+        let mut config = DataCloakConfig::builder()
+            .organization_names(names.iter().copied())
+            .build()
+            .unwrap();
+        config.enabled_types.insert(ORGANIZATION_TYPE.to_string());
+        DataCloakEngine::new(config).unwrap()
+    }
+
+    fn samples(engine: &DataCloakEngine, text: &str) -> Vec<String> {
+        engine
+            .detect_pii(text)
+            .unwrap()
+            .into_iter()
+            .filter(|pii| pii.pii_type == ORGANIZATION_TYPE)
+            .map(|pii| pii.sample)
+            .collect()
+    }
+
+    #[test]
+    fn test_legal_suffixes_mark_organizations() {
+        let engine = engine(&[]);
+        assert_eq!(
+            samples(
+                &engine,
+                "employer: Acme Widgets Inc. (formerly Bank of the West, Ltd) and Müller GmbH"
+            ),
+            ["Acme Widgets Inc.", "Bank of the West, Ltd", "Müller GmbH"]
+        );
+        // `Co` only counts as a whole word
+        assert!(samples(&engine, "joined Acme Company in May").is_empty());
+        assert_eq!(samples(&engine, "Call Nokia Oy today"), ["Nokia Oy"]);
+
+        let default = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        assert!(samples(&default, "employer: Acme Widgets Inc.").is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_forms_need_a_name() {
+        let engine = engine(&[]);
+        for text in [
+            "1200 Main St SE",
+            "Take Route 66 SA",
+            "Use Python AB",
+            "The Board AG met",
+        ] {
+            assert!(samples(&engine, text).is_empty(), "{}", text);
+        }
+        assert_eq!(
+            samples(&engine, "Siemens AG, SAP SE"),
+            ["Siemens AG", "SAP SE"]
+        );
+    }
+
+    #[test]
+    fn test_listed_organizations_win() {
+        let engine = engine(&["Initech", "Initech Labs"]);
+        assert_eq!(
+            samples(&engine, "Worked at initech labs, then Initech Labs LLC."),
+            ["initech labs", "Initech Labs LLC."]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use regex::Regex;
+
+use crate::{DataCloakConfig, DataCloakError, MaskingStrategy};
+
+/// Partial masks and plain redactions: `***-**-6789`, `**** **** **** 1234`,
+/// `j***@example.com` and `***`.
+const PARTIAL_MASK: &str =
+    r"\*{3,}(?:[-. ]\*{2,4})+[-. ]?\d*|[A-Za-z0-9._%+-]*\*{3,}@[A-Za-z0-9.-]+|\*{3,}";
+
+/// The mask formats this engine produces, compiled into one pattern.
+/// Synthesized and format-preserving values look like real data by design,
+/// so they are not recognized.
+#[derive(Debug, Default)]
+pub(crate) struct OwnMasks {
+    pattern: Option<Regex>,
+}
+
+impl OwnMasks {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Result<Self, DataCloakError> {
+        if !config.skip_masked_values {
+            return Ok(Self::default());
+        }
+        let mut formats = vec![PARTIAL_MASK.to_string()];
+        match &config.masking_strategy {
+            MaskingStrategy::Hmac { .. } => {
+                formats.push(r"\b[A-Z][A-Z0-9_]*_[0-9a-f]{16}\b".to_string());
+            }
+            MaskingStrategy::SaltedHash { hex_length, .. } => {
+                formats.push(format!(r"\b[a-z][a-z0-9_]*:[0-9a-f]{{{}}}\b", hex_length));
+            }
+            MaskingStrategy::Placeholder => {
+                formats.push(r"\[[A-Z][A-Z0-9_]*\]".to_string());
+            }
+            MaskingStrategy::Partial
+            | MaskingStrategy::FormatPreserving { .. }
+            | MaskingStrategy::Synthesize { .. } => {}
+        }
+        let pattern =
+            Regex::new(&formats.join("|")).map_err(|e| DataCloakError::PatternCompile {
+                name: "own_masks".to_string(),
+                message: e.to_string(),
+            })?;
+        Ok(Self {
+            pattern: Some(pattern),
+        })
+    }
+
+    /// Spans of `text` holding masks, as `(start, end)` in order.
+    pub(crate) fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        match &self.pattern {
+            Some(pattern) => pattern
+                .find_iter(text)
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Whether `start..end` overlaps one of `masks`, the spans found by
+/// `OwnMasks::find`, so the match is output of an earlier masking run.
+pub(crate) fn overlaps_mask(masks: &[(usize, usize)], start: usize, end: usize) -> bool {
+    masks
+        .iter()
+        .any(|&(mask_start, mask_end)| start < mask_end && mask_start < end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakEngine;
+
+    #[test]
+    fn test_partial_masks_are_recognized() {
+        let masks = OwnMasks::compile(&DataCloakConfig::default()).unwrap();
+        let text =
+            "SSN ***-**-6789, card **** **** **** 1234, mail j***@example.com, *** 555-123-4567";
+        let found: Vec<_> = masks
+            .find(text)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "***-**-6789",
+                "**** **** **** 1234",
+                "j***@example.com",
+                "***"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_masking_masked_output_changes_nothing() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let first = engine
+            .mask_text("Email john@example.com or call 555-123-4567")
+            .unwrap();
+        let second = engine.mask_text(&first.masked_text).unwrap();
+        assert_eq!(second.masked_text, first.masked_text);
+        assert!(second.detected_pii.is_empty());
+
+        // The revealed digits of a masked SSN aren't a new `last_four`
+        let config = DataCloakConfig::builder()
+            .custom_pattern("last_four", r"\b\d{4}\b")
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine.mask_text("SSN ***-**-6789 and 987-65-4321").unwrap();
+        let found: Vec<_> = result
+            .detected_pii
+            .iter()
+            .map(|pii| (pii.pii_type.as_str(), pii.sample.as_str()))
+            .collect();
+        assert_eq!(found, [("ssn", "987-65-4321"), ("last_four", "4321")]);
+        assert_eq!(result.metadata.already_masked, 1);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+
+use crate::{ColumnFindings, ColumnSelection, DataCloakEngine, DataCloakError};
+
+/// Totals from `scan_parquet` and `mask_parquet`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParquetReport {
+    pub rows: u64,
+    pub row_groups: usize,
+    /// One entry per selected string column, in schema order.
+    pub columns: Vec<ColumnFindings>,
+    /// Set when any value hit a match limit or the memory budget.
+    pub limits_exceeded: bool,
+}
+
+impl ParquetReport {
+    fn add(&mut self, columns: Vec<ColumnFindings>, limits_exceeded: bool) {
+        if self.columns.is_empty() {
+            self.columns = columns;
+        } else {
+            for (total, batch) in self.columns.iter_mut().zip(columns) {
+                total.merge(batch);
+            }
+        }
+        self.limits_exceeded |= limits_exceeded;
+    }
+}
+
+impl DataCloakEngine {
+    /// Reports findings in the `Utf8`/`LargeUtf8` columns `columns` selects
+    /// without writing anything. Batches are decoded one at a time, so memory
+    /// use follows the batch size rather than the file size.
+    pub fn scan_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        columns: &ColumnSelection,
+    ) -> Result<ParquetReport, DataCloakError> {
+        let file = File::open(path.as_ref()).map_err(|e| DataCloakError::Io(e.to_string()))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(parquet_error)?;
+        let mut report = ParquetReport {
+            row_groups: builder.metadata().num_row_groups(),
+            ..ParquetReport::default()
+        };
+
+        for batch in builder.build().map_err(parquet_error)? {
+            let batch = batch.map_err(|e| DataCloakError::Io(format!("Parquet: {}", e)))?;
+            let (findings, limits_exceeded) = self.scan_record_batch(&batch, columns)?;
+            report.rows += batch.num_rows() as u64;
+            report.add(findings, limits_exceeded);
+        }
+        Ok(report)
+    }
+
+    /// Masks the string columns `columns` selects from `input` into a new
+    /// Parquet file at `output`, one row group at a time. The output keeps
+    /// the input's schema, row group boundaries and per-column compression
+    /// codecs; every other column is copied through unchanged.
+    pub fn mask_parquet<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        input: P,
+        output: Q,
+        columns: &ColumnSelection,
+    ) -> Result<ParquetReport, DataCloakError> {
+        let file = File::open(input.as_ref()).map_err(|e| DataCloakError::Io(e.to_string()))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(
+            file.try_clone()
+                .map_err(|e| DataCloakError::Io(e.to_string()))?,
+        )
+        .map_err(parquet_error)?;
+        let metadata = builder.metadata().clone();
+        let schema = builder.schema().clone();
+
+        let mut compression = HashMap::new();
+        let mut max_rows = 1;
+        for row_group in metadata.row_groups() {
+            max_rows = max_rows.max(row_group.num_rows() as usize);
+            for column in row_group.columns() {
+                compression
+                    .entry(column.column_path().clone())
+                    .or_insert_with(|| column.compression());
+            }
+        }
+        let mut properties = WriterProperties::builder().set_max_row_group_size(max_rows);
+        for (path, codec) in compression {
+            properties = properties.set_column_compression(path, codec);
+        }
+
+        let output =
+            File::create(output.as_ref()).map_err(|e| DataCloakError::Io(e.to_string()))?;
+        let mut writer = ArrowWriter::try_new(output, schema, Some(properties.build()))
+            .map_err(parquet_error)?;
+        let mut report = ParquetReport {
+            row_groups: metadata.num_row_groups(),
+            ..ParquetReport::default()
+        };
+
+        for row_group in 0..metadata.num_row_groups() {
+            let reader = ParquetRecordBatchReaderBuilder::try_new(
+                file.try_clone()
+                    .map_err(|e| DataCloakError::Io(e.to_string()))?,
+            )
+            .and_then(|builder| builder.with_row_groups(vec![row_group]).build())
+            .map_err(parquet_error)?;
+            for batch in reader {
+                let batch = batch.map_err(|e| DataCloakError::Io(format!("Parquet: {}", e)))?;
+                let result = self.mask_record_batch_columns(&batch, columns)?;
+                writer.write(&result.batch).map_err(parquet_error)?;
+                report.rows += batch.num_rows() as u64;
+                report.add(result.columns, result.limits_exceeded);
+            }
+            writer.flush().map_err(parquet_error)?;
+        }
+
+        writer.close().map_err(parquet_error)?;
+        Ok(report)
+    }
+}
+
+fn parquet_error(e: ParquetError) -> DataCloakError {
+    DataCloakError::Io(format!("Parquet: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+    use arrow_array::cast::AsArray;
+    use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::basic::Compression;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_mask_parquet_preserves_layout() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("datacloak-in-{}.parquet", std::process::id()));
+        let output = dir.join(format!("datacloak-out-{}.parquet", std::process::id()));
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("email", DataType::Utf8, true),
+        ]));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(2)
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            File::create(&input).unwrap(),
+            schema.clone(),
+            Some(properties),
+        )
+        .unwrap();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec![
+                Some("a@example.com"),
+                None,
+                Some("b@example.com"),
+            ])),
+        ];
+        writer
+            .write(&RecordBatch::try_new(schema, columns).unwrap())
+            .unwrap();
+        writer.close().unwrap();
+
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let scanned = engine.scan_parquet(&input, &ColumnSelection::All).unwrap();
+        let report = engine
+            .mask_parquet(&input, &output, &ColumnSelection::All)
+            .unwrap();
+        assert_eq!(scanned, report);
+        assert_eq!(report.rows, 3);
+        assert_eq!(report.row_groups, 2);
+        assert_eq!(report.columns[0].counts_by_type["email"], 2);
+
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&output).unwrap()).unwrap();
+        assert_eq!(builder.metadata().num_row_groups(), 2);
+        assert_eq!(
+            builder.metadata().row_group(0).column(1).compression(),
+            Compression::SNAPPY
+        );
+        let masked: Vec<Option<String>> = builder
+            .build()
+            .unwrap()
+            .flat_map(|batch| {
+                let batch = batch.unwrap();
+                let values = batch.column(1).as_string::<i32>();
+                values
+                    .iter()
+                    .map(|v| v.map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(
+            masked,
+            vec![Some("[EMAIL_1]".into()), None, Some("[EMAIL_2]".into())]
+        );
+
+        std::fs::remove_file(input).ok();
+        std::fs::remove_file(output).ok();
+    }
+
+    #[test]
+    fn test_mask_parquet_masks_selected_columns_only() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("datacloak-sel-in-{}.parquet", std::process::id()));
+        let output = dir.join(format!("datacloak-sel-out-{}.parquet", std::process::id()));
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("email", DataType::Utf8, false),
+            Field::new("support_inbox", DataType::Utf8, false),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec!["a@example.com"])),
+            Arc::new(StringArray::from(vec!["help@example.com"])),
+        ];
+        let mut writer =
+            ArrowWriter::try_new(File::create(&input).unwrap(), schema.clone(), None).unwrap();
+        writer
+            .write(&RecordBatch::try_new(schema, columns).unwrap())
+            .unwrap();
+        writer.close().unwrap();
+
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let selection = ColumnSelection::Only(vec!["email".to_string()]);
+        let scanned = engine.scan_parquet(&input, &selection).unwrap();
+        let report = engine.mask_parquet(&input, &output, &selection).unwrap();
+        assert_eq!(scanned, report);
+        assert_eq!(report.columns.len(), 1);
+        assert_eq!(report.columns[0].name, "email");
+
+        let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(&output).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            batch.column(0).as_string::<i32>().value(0),
+            "a***@example.com"
+        );
+        assert_eq!(
+            batch.column(1).as_string::<i32>().value(0),
+            "help@example.com"
+        );
+
+        std::fs::remove_file(input).ok();
+        std::fs::remove_file(output).ok();
+    }
+}
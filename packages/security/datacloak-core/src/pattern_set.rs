@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use aho_corasick::AhoCorasick;
+use regex::{Regex, RegexSet};
+
+use crate::error::DataCloakError;
+
+/// Distinct pattern lists kept compiled by `PatternSet::cached`. Hosts use a
+/// handful of configs, so this only bounds pathological churn.
+const CACHE_CAPACITY: usize = 32;
+
+type PatternCache = Mutex<HashMap<Vec<PatternSpec>, Arc<PatternSet>>>;
+
+fn cache() -> &'static PatternCache {
+    static CACHE: OnceLock<PatternCache> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Detection patterns compiled both into one `RegexSet` and individually.
+///
+/// The set finds which patterns occur anywhere in a text in a single pass,
+/// so only those patterns run their own `find_iter` to locate matches. On
+/// mostly-clean text with many custom patterns this avoids one full pass per
+/// pattern.
+///
+/// Patterns that declare required literals are additionally gated by an
+/// Aho-Corasick scan: when a text contains none of a pattern's literals the
+/// pattern is never evaluated, and when no pattern is a candidate the regex
+/// engines are skipped entirely. Streaming and batch scans detect per chunk,
+/// so clean chunks cost a single literal scan.
+#[derive(Debug)]
+pub(crate) struct PatternSet {
+    names: Vec<String>,
+    regexes: Vec<Regex>,
+    set: RegexSet,
+    /// Whether each pattern needs one of its literals present to match.
+    gated: Vec<bool>,
+    literals: Option<AhoCorasick>,
+    /// Pattern indices gated by each literal, by literal index.
+    literal_owners: Vec<Vec<usize>>,
+}
+
+/// One detection pattern and the literals any match must contain. An empty
+/// literal list means the pattern is always evaluated.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PatternSpec {
+    pub(crate) name: String,
+    pub(crate) pattern: String,
+    pub(crate) literals: &'static [&'static str],
+}
+
+impl PatternSet {
+    /// Returns the compiled set for `patterns`, compiling it only the first
+    /// time this process sees that exact list. Engines created and dropped
+    /// per job then share one compilation.
+    pub(crate) fn cached(patterns: Vec<PatternSpec>) -> Result<Arc<Self>, DataCloakError> {
+        if let Some(set) = cache()
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Pattern cache"))?
+            .get(&patterns)
+        {
+            return Ok(set.clone());
+        }
+
+        // Compile outside the lock; a concurrent miss just compiles twice
+        let set = Arc::new(Self::new(patterns.clone())?);
+        let mut cache = cache()
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Pattern cache"))?;
+        if cache.len() >= CACHE_CAPACITY {
+            cache.retain(|_, set| Arc::strong_count(set) > 1);
+            if cache.len() >= CACHE_CAPACITY {
+                cache.clear();
+            }
+        }
+        Ok(cache.entry(patterns).or_insert(set).clone())
+    }
+
+    /// Forgets every cached set; sets still held by engines live on.
+    pub(crate) fn clear_cache() -> Result<(), DataCloakError> {
+        cache()
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Pattern cache"))?
+            .clear();
+        Ok(())
+    }
+
+    /// Compiles the patterns; match order follows this order.
+    pub(crate) fn new(patterns: Vec<PatternSpec>) -> Result<Self, DataCloakError> {
+        let compile_error = |name: &str, e: regex::Error| DataCloakError::PatternCompile {
+            name: name.to_string(),
+            message: e.to_string(),
+        };
+
+        // Compile individually first so a bad pattern is reported by name
+        let regexes = patterns
+            .iter()
+            .map(|spec| Regex::new(&spec.pattern).map_err(|e| compile_error(&spec.name, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let set = RegexSet::new(patterns.iter().map(|spec| &spec.pattern))
+            .map_err(|e| compile_error("pattern set", e))?;
+
+        let mut literal_list: Vec<&str> = Vec::new();
+        let mut literal_owners: Vec<Vec<usize>> = Vec::new();
+        for (index, spec) in patterns.iter().enumerate() {
+            for literal in spec.literals {
+                match literal_list.iter().position(|l| l == literal) {
+                    Some(existing) => literal_owners[existing].push(index),
+                    None => {
+                        literal_list.push(literal);
+                        literal_owners.push(vec![index]);
+                    }
+                }
+            }
+        }
+        let literals = if literal_list.is_empty() {
+            None
+        } else {
+            Some(AhoCorasick::new(&literal_list).map_err(|e| {
+                DataCloakError::Internal(format!("Failed to build literal prefilter: {}", e))
+            })?)
+        };
+
+        Ok(Self {
+            gated: patterns
+                .iter()
+                .map(|spec| !spec.literals.is_empty())
+                .collect(),
+            names: patterns.into_iter().map(|spec| spec.name).collect(),
+            regexes,
+            set,
+            literals,
+            literal_owners,
+        })
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Regex> {
+        let index = self.names.iter().position(|n| n == name)?;
+        Some(&self.regexes[index])
+    }
+
+    /// The patterns that match somewhere in `text`, in registration order.
+    pub(crate) fn matching<'a>(&'a self, text: &str) -> Vec<(&'a str, &'a Regex)> {
+        let candidates = self.candidates(text);
+        if !candidates.iter().any(|&c| c) {
+            return Vec::new();
+        }
+
+        self.set
+            .matches(text)
+            .into_iter()
+            .filter(|&i| candidates[i])
+            .map(|i| (self.names[i].as_str(), &self.regexes[i]))
+            .collect()
+    }
+
+    /// Marks the patterns worth evaluating: ungated ones always, gated ones
+    /// only when one of their literals occurs in `text`.
+    fn candidates(&self, text: &str) -> Vec<bool> {
+        let mut candidates: Vec<bool> = self.gated.iter().map(|&g| !g).collect();
+        let Some(literals) = &self.literals else {
+            return candidates;
+        };
+
+        let mut pending = self.gated.iter().filter(|&&g| g).count();
+        for mat in literals.find_overlapping_iter(text) {
+            for &index in &self.literal_owners[mat.pattern().as_usize()] {
+                if !candidates[index] {
+                    candidates[index] = true;
+                    pending -= 1;
+                }
+            }
+            if pending == 0 {
+                break;
+            }
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(patterns: &[(&str, &str)]) -> Result<PatternSet, DataCloakError> {
+        gated_set(
+            &patterns
+                .iter()
+                .map(|&(n, p)| (n, p, &[][..]))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn gated_set(
+        patterns: &[(&str, &str, &'static [&'static str])],
+    ) -> Result<PatternSet, DataCloakError> {
+        PatternSet::new(
+            patterns
+                .iter()
+                .map(|&(name, pattern, literals)| PatternSpec {
+                    name: name.to_string(),
+                    pattern: pattern.to_string(),
+                    literals,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_matching_reports_only_present_patterns() {
+        let patterns = set(&[("digits", r"\d+"), ("at", "@"), ("word", "hello")]).unwrap();
+        let names: Vec<&str> = patterns
+            .matching("call 42 hello")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["digits", "word"]);
+        assert!(patterns.get("at").is_some());
+
+        let err = set(&[("ok", "a"), ("broken", "(")]).unwrap_err();
+        assert!(matches!(err, DataCloakError::PatternCompile { ref name, .. } if name == "broken"));
+    }
+
+    #[test]
+    fn test_literal_prefilter_gates_patterns() {
+        let patterns = gated_set(&[
+            ("at", r"\w+@\w+", &["@"]),
+            ("dashed", r"\d+-\d+", &["-"]),
+            ("word", "hello", &[]),
+        ])
+        .unwrap();
+
+        assert_eq!(patterns.candidates("plain words"), vec![false, false, true]);
+        assert_eq!(patterns.candidates("a@b 1-2"), vec![true, true, true]);
+        assert!(patterns.matching("plain words").is_empty());
+
+        let names: Vec<&str> = patterns
+            .matching("mail a@b")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["at"]);
+    }
+}
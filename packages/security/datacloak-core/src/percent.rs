@@ -0,0 +1,118 @@
+use crate::confusables::NormalizedText;
+
+/// The byte a `%XX` escape at `i` stands for.
+fn escaped_byte(bytes: &[u8], i: usize) -> Option<u8> {
+    let escape = bytes.get(i..i + 3)?;
+    if escape[0] != b'%' {
+        return None;
+    }
+    let hex = std::str::from_utf8(&escape[1..]).ok()?;
+    u8::from_str_radix(hex, 16).ok()
+}
+
+/// `original` with its `%XX` escapes decoded, or `None` if it has none.
+/// Runs of escapes that don't form UTF-8 are left encoded. `+` is kept, as
+/// it is as often a literal plus as a form-encoded space.
+pub(crate) fn percent_decode(original: &str) -> Option<NormalizedText> {
+    let bytes = original.as_bytes();
+    let mut text = String::with_capacity(original.len());
+    let mut origins = Vec::with_capacity(original.len() + 1);
+    let mut changed = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        // Consecutive escapes are decoded together, so multi-byte
+        // characters like `%C3%A9` survive
+        let mut decoded = Vec::new();
+        let mut starts = Vec::new();
+        let mut end = i;
+        while let Some(byte) = escaped_byte(bytes, end) {
+            decoded.push(byte);
+            starts.push(end);
+            end += 3;
+        }
+        if decoded.is_empty() {
+            let c = original[i..].chars().next().expect("i is a char boundary");
+            text.push(c);
+            origins.resize(text.len(), i);
+            i += c.len_utf8();
+            continue;
+        }
+        match std::str::from_utf8(&decoded) {
+            Ok(chars) => {
+                for (offset, c) in chars.char_indices() {
+                    text.push(c);
+                    origins.resize(text.len(), starts[offset]);
+                }
+                changed = true;
+            }
+            Err(_) => {
+                text.push_str(&original[i..end]);
+                origins.extend(i..end);
+            }
+        }
+        i = end;
+    }
+    origins.push(original.len());
+    changed.then(|| NormalizedText::from_parts(text, origins))
+}
+
+/// Whether `text` holds an escape `percent_decode` would decode.
+pub(crate) fn is_percent_encoded(text: &str) -> bool {
+    percent_decode(text).is_some()
+}
+
+/// Escapes everything in `text` but unreserved URL characters and the `*`
+/// of masks, so a mask can stand in for a percent-encoded value.
+pub(crate) fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for &b in text.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~*".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, DataCloakEngine};
+
+    #[test]
+    fn test_percent_decoding_keeps_original_offsets() {
+        let original = "q=caf%C3%A9&to=john%40example.com&bad=%FF";
+        let decoded = percent_decode(original).unwrap();
+        assert_eq!(decoded.as_str(), "q=café&to=john@example.com&bad=%FF");
+
+        let start = decoded.as_str().find("john").unwrap();
+        let (start, end) = decoded.original_span(start, start + "john@example.com".len());
+        assert_eq!(&original[start..end], "john%40example.com");
+
+        assert!(percent_decode("100% sure").is_none());
+        assert_eq!(percent_encode("j***@example.com"), "j***%40example.com");
+    }
+
+    #[test]
+    fn test_percent_encoded_pii_is_masked_in_place() {
+        let text = "GET /signup?email=john%40example.com&ref=home";
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        assert!(engine.detect_pii(text).unwrap().is_empty());
+
+        let config = DataCloakConfig::builder()
+            .decode_percent_encoding(true)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine.mask_text(text).unwrap();
+        assert_eq!(result.detected_pii.len(), 1);
+        let pii = &result.detected_pii[0];
+        assert_eq!(pii.sample, "john%40example.com");
+        assert_eq!(pii.normalized.as_deref(), Some("john@example.com"));
+        assert_eq!(
+            result.masked_text,
+            "GET /signup?email=j***%40example.com&ref=home"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use phonenumber::country::Id;
+use phonenumber::{Mode, PhoneNumber};
+use regex::Regex;
+
+use crate::{DataCloakConfig, DataCloakEngine, DataCloakError, Locale, PHONE_PATTERN};
+
+/// The phone formats of an engine with the country each belongs to, so a
+/// number written without a country code is parsed under the numbering plan
+/// of the format it was written in.
+#[derive(Debug)]
+pub(crate) struct PhoneRegions {
+    /// Anchored formats in pattern order: the built-in NANP format, then
+    /// those of the locale packs.
+    formats: Vec<(Id, Regex)>,
+    /// The country of numbers no format matches whole: that of the first
+    /// configured locale, else the US.
+    fallback: Id,
+}
+
+impl PhoneRegions {
+    pub(crate) fn compile(config: &DataCloakConfig) -> Result<Self, DataCloakError> {
+        let locales = config.all_locales();
+        let formats = std::iter::once((Id::US, PHONE_PATTERN))
+            .chain(
+                locales
+                    .iter()
+                    .filter_map(|&locale| Some((region(locale), locale.phone_pattern()?))),
+            )
+            .map(|(id, pattern)| {
+                let anchored = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+                    DataCloakError::PatternCompile {
+                        name: "phone".to_string(),
+                        message: e.to_string(),
+                    }
+                })?;
+                Ok((id, anchored))
+            })
+            .collect::<Result<_, DataCloakError>>()?;
+        Ok(Self {
+            formats,
+            fallback: locales.first().map_or(Id::US, |&locale| region(locale)),
+        })
+    }
+
+    /// The country of the first format matching all of `sample`.
+    fn region_of(&self, sample: &str) -> Id {
+        self.formats
+            .iter()
+            .find(|(_, format)| format.is_match(sample))
+            .map_or(self.fallback, |&(id, _)| id)
+    }
+}
+
+fn region(locale: Locale) -> Id {
+    match locale {
+        Locale::Us => Id::US,
+        Locale::Gb => Id::GB,
+        Locale::De => Id::DE,
+        Locale::Fr => Id::FR,
+        Locale::Es => Id::ES,
+        Locale::It => Id::IT,
+        Locale::Nl => Id::NL,
+        Locale::Ca => Id::CA,
+        Locale::Br => Id::BR,
+        Locale::In => Id::IN,
+    }
+}
+
+impl DataCloakEngine {
+    /// Whether a phone match is a number that can be dialled under the
+    /// numbering plan of its country.
+    pub(crate) fn validate_phone(&self, sample: &str) -> bool {
+        self.parse_phone(sample).is_some()
+    }
+
+    /// `sample` in E.164 form, e.g. `+442079460958`, if it is valid.
+    pub(crate) fn e164(&self, sample: &str) -> Option<String> {
+        let number = self.parse_phone(sample)?;
+        Some(number.format().mode(Mode::E164).to_string())
+    }
+
+    fn parse_phone(&self, sample: &str) -> Option<PhoneNumber> {
+        let region = self.phone_regions.region_of(sample);
+        let number = phonenumber::parse(Some(region), sample).ok()?;
+        phonenumber::is_valid(&number).then_some(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataCloakConfig, DataCloakEngine, Locale, PhoneValidation};
+
+    fn engine(locales: &[Locale]) -> DataCloakEngine {
+        let config = DataCloakConfig::builder()
+            .phone_validation(PhoneValidation::PhoneNumber)
+            .locales(locales.iter().copied())
+            .build()
+            .unwrap();
+        DataCloakEngine::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_valid_phones_are_normalized() {
+        let engine = engine(&[Locale::Gb]);
+        let findings = engine
+            .detect_pii("Numbers 201-555-0123 or 020 7946 0958")
+            .unwrap();
+        let normalized: Vec<_> = findings
+            .iter()
+            .map(|pii| pii.normalized.as_deref())
+            .collect();
+        assert_eq!(normalized, [Some("+12015550123"), Some("+442079460958")]);
+        assert!(findings.iter().all(|pii| pii.confidence == 0.95));
+    }
+
+    #[test]
+    fn test_invalid_phones_lose_confidence() {
+        // No US area code starts with 1
+        let engine = engine(&[]);
+        let findings = engine.detect_pii("ID 123-456-7890").unwrap();
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].confidence < 0.95);
+        assert_eq!(findings[0].normalized, None);
+    }
+}
@@ -0,0 +1,151 @@
+use polars::prelude::*;
+
+use crate::{DataCloakEngine, DataCloakError, PIIDetectionResult};
+
+/// Rows of a findings frame, one per detection. Samples are left out so the
+/// frame can be displayed in a notebook without re-exposing the values.
+#[derive(Default)]
+struct FindingRows {
+    column: Vec<String>,
+    row: Vec<u64>,
+    pii_type: Vec<String>,
+    confidence: Vec<f64>,
+    start: Vec<u64>,
+    end: Vec<u64>,
+    masked: Vec<String>,
+}
+
+impl FindingRows {
+    fn push(&mut self, column: &str, row: usize, results: Vec<PIIDetectionResult>) {
+        for pii in results {
+            self.column.push(column.to_string());
+            self.row.push(row as u64);
+            self.pii_type.push(pii.pii_type);
+            self.confidence.push(pii.confidence);
+            self.start.push(pii.start as u64);
+            self.end.push(pii.end as u64);
+            self.masked.push(pii.masked);
+        }
+    }
+
+    fn into_frame(self) -> Result<DataFrame, DataCloakError> {
+        DataFrame::new(vec![
+            Series::new("column", self.column),
+            Series::new("row", self.row),
+            Series::new("pii_type", self.pii_type),
+            Series::new("confidence", self.confidence),
+            Series::new("start", self.start),
+            Series::new("end", self.end),
+            Series::new("masked", self.masked),
+        ])
+        .map_err(polars_error)
+    }
+}
+
+impl DataCloakEngine {
+    /// Masks a `String` series, returning the masked series under the same
+    /// name and a findings frame with columns `column`, `row`, `pii_type`,
+    /// `confidence`, `start`, `end` and `masked`. Nulls stay null.
+    pub fn mask_series(&self, series: &Series) -> Result<(Series, DataFrame), DataCloakError> {
+        let mut findings = FindingRows::default();
+        let masked = self.mask_string_series(series, &mut findings)?;
+        Ok((masked, findings.into_frame()?))
+    }
+
+    /// Masks every `String` column of `df`, passing other columns through,
+    /// and returns the masked frame with a findings frame covering all
+    /// columns.
+    pub fn mask_dataframe(&self, df: &DataFrame) -> Result<(DataFrame, DataFrame), DataCloakError> {
+        let mut findings = FindingRows::default();
+        let mut columns = Vec::with_capacity(df.width());
+        for series in df.get_columns() {
+            if series.dtype() == &DataType::String {
+                columns.push(self.mask_string_series(series, &mut findings)?);
+            } else {
+                columns.push(series.clone());
+            }
+        }
+        let masked = DataFrame::new(columns).map_err(polars_error)?;
+        Ok((masked, findings.into_frame()?))
+    }
+
+    /// The findings frame `mask_dataframe` would return, without masking.
+    pub fn detect_in_dataframe(&self, df: &DataFrame) -> Result<DataFrame, DataCloakError> {
+        let mut findings = FindingRows::default();
+        for series in df.get_columns() {
+            if series.dtype() != &DataType::String {
+                continue;
+            }
+            for (row, value) in series.str().map_err(polars_error)?.into_iter().enumerate() {
+                if let Some(value) = value {
+                    findings.push(series.name(), row, self.detect_pii(value)?);
+                }
+            }
+        }
+        findings.into_frame()
+    }
+
+    fn mask_string_series(
+        &self,
+        series: &Series,
+        findings: &mut FindingRows,
+    ) -> Result<Series, DataCloakError> {
+        let values = series.str().map_err(|_| {
+            DataCloakError::InvalidArgument(format!(
+                "Series '{}' has type {:?}, expected String",
+                series.name(),
+                series.dtype()
+            ))
+        })?;
+        let mut masked = Vec::with_capacity(series.len());
+        for (row, value) in values.into_iter().enumerate() {
+            let Some(value) = value else {
+                masked.push(None);
+                continue;
+            };
+            let result = self.mask_text(value)?;
+            findings.push(series.name(), row, result.detected_pii);
+            masked.push(Some(result.masked_text));
+        }
+        Ok(Series::new(series.name(), masked))
+    }
+}
+
+fn polars_error(e: PolarsError) -> DataCloakError {
+    DataCloakError::Internal(format!("Polars: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    #[test]
+    fn test_mask_dataframe_returns_findings_frame() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let df = DataFrame::new(vec![
+            Series::new("id", vec![1u64, 2]),
+            Series::new("contact", vec![None, Some("mail a@example.com")]),
+        ])
+        .unwrap();
+
+        assert_eq!(engine.detect_in_dataframe(&df).unwrap().height(), 1);
+        let (masked, findings) = engine.mask_dataframe(&df).unwrap();
+        let contact = masked.column("contact").unwrap().str().unwrap();
+        assert_eq!(contact.get(0), None);
+        assert_eq!(contact.get(1), Some("mail [EMAIL_1]"));
+        assert_eq!(findings.height(), 1);
+        let rows = findings.column("row").unwrap();
+        assert_eq!(rows.dtype(), &DataType::UInt64);
+
+        let ids = masked.column("id").unwrap();
+        assert!(matches!(
+            engine.mask_series(ids),
+            Err(DataCloakError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::allowlist::domain_matches;
+use crate::config_file::ConfigFormat;
+use crate::tokenization::TokenVault;
+use crate::{
+    obfuscated, DataCloakEngine, DataCloakError, MaskingResult, MaskingStrategy, PIIDetectionResult,
+};
+
+/// What happens to a finding a policy rule applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Masked with the engine's `masking_strategy`.
+    #[default]
+    Mask,
+    /// Replaced by a token from the vault, so it can be re-identified.
+    Tokenize,
+    /// Left in the text and not reported, like an allowlisted value.
+    Ignore,
+}
+
+/// The findings a rule applies to. Each list left empty matches anything;
+/// otherwise the finding must match one of its entries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyCondition {
+    /// PII types, e.g. `ssn`.
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    /// Source labels passed to `mask_text_with_policy`, e.g. `export`.
+    #[serde(rename = "source")]
+    pub sources: Vec<String>,
+    /// Email domains, subdomains included. Findings without a domain never
+    /// match a non-empty list.
+    #[serde(rename = "domain")]
+    pub domains: Vec<String>,
+}
+
+/// One rule of a `Policy`: `action` for the findings matching `when`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    #[serde(default)]
+    pub when: PolicyCondition,
+    pub action: PolicyAction,
+}
+
+/// Rules deciding per finding whether it is masked, tokenized or ignored.
+/// The first rule whose condition matches applies; findings no rule matches
+/// get `default_action`.
+///
+/// ```yaml
+/// rules:
+///   - when: { type: [ssn], source: [export] }
+///     action: tokenize
+///   - when: { type: [email], domain: [ourcompany.com] }
+///     action: ignore
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    pub rules: Vec<PolicyRule>,
+    pub default_action: PolicyAction,
+}
+
+impl PolicyCondition {
+    fn matches(&self, pii: &PIIDetectionResult, source: &str) -> bool {
+        (self.types.is_empty() || self.types.contains(&pii.pii_type))
+            && (self.sources.is_empty() || self.sources.iter().any(|s| s == source))
+            && (self.domains.is_empty()
+                || email_domain(pii).is_some_and(|domain| {
+                    self.domains
+                        .iter()
+                        .any(|allowed| domain_matches(&domain, allowed))
+                }))
+    }
+}
+
+/// The domain of an email finding, obfuscated addresses included.
+fn email_domain(pii: &PIIDetectionResult) -> Option<String> {
+    if pii.pii_type != "email" {
+        return None;
+    }
+    let email = pii
+        .normalized
+        .clone()
+        .or_else(|| obfuscated::deobfuscate_email(&pii.sample))
+        .unwrap_or_else(|| pii.sample.clone());
+    let (_, domain) = email.rsplit_once('@')?;
+    Some(domain.to_string())
+}
+
+impl Policy {
+    /// Loads a policy from a JSON, YAML or TOML file, chosen by extension.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, DataCloakError> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path).ok_or_else(|| {
+            DataCloakError::InvalidConfig(format!(
+                "Unsupported policy file extension: {}",
+                path.display()
+            ))
+        })?;
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| DataCloakError::Io(format!("{}: {}", path.display(), e)))?;
+
+        Self::parse(&contents, format)
+    }
+
+    /// Parses a policy document. Domains are lowercased and lose any leading
+    /// `@`.
+    pub fn parse(contents: &str, format: ConfigFormat) -> Result<Self, DataCloakError> {
+        let mut policy: Policy = match format {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+        }
+        .map_err(DataCloakError::InvalidConfig)?;
+
+        for rule in &mut policy.rules {
+            for domain in &mut rule.when.domains {
+                *domain = domain.trim().trim_start_matches('@').to_lowercase();
+                if domain.is_empty() {
+                    return Err(DataCloakError::InvalidConfig(
+                        "Policy domains must not be empty".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    /// The action for `pii`, found in input from `source`.
+    pub fn action_for(&self, pii: &PIIDetectionResult, source: &str) -> PolicyAction {
+        self.rules
+            .iter()
+            .find(|rule| rule.when.matches(pii, source))
+            .map_or(self.default_action, |rule| rule.action)
+    }
+}
+
+impl DataCloakEngine {
+    /// Masks `text` from `source` as `policy` decides per finding. Tokenized
+    /// values are recorded in `vault`; ignored findings stay in the text and
+    /// are counted as `allowlisted`.
+    pub fn mask_text_with_policy(
+        &self,
+        text: &str,
+        source: &str,
+        policy: &Policy,
+        vault: &dyn TokenVault,
+    ) -> Result<MaskingResult, DataCloakError> {
+        let start_time = std::time::Instant::now();
+        let mut detected = self.detect_for_masking(text)?;
+
+        let mut kept = Vec::with_capacity(detected.results.len());
+        let mut tokenized = false;
+        for pii in detected.results {
+            match policy.action_for(&pii, source) {
+                PolicyAction::Ignore => detected.validation.allowlisted += 1,
+                PolicyAction::Mask => kept.push((pii, false)),
+                PolicyAction::Tokenize => {
+                    tokenized = true;
+                    kept.push((pii, true));
+                }
+            }
+        }
+
+        let placeholders = matches!(self.config.masking_strategy, MaskingStrategy::Placeholder);
+        let (mut results, tokenize): (Vec<_>, Vec<_>) = kept.into_iter().unzip();
+        if placeholders {
+            self.assign_placeholders(text, &mut results)?;
+        }
+        for (pii, tokenize) in results.iter_mut().zip(tokenize) {
+            if tokenize {
+                pii.masked = vault.tokenize(&pii.pii_type, &pii.sample)?;
+            }
+        }
+        detected.results = results;
+
+        Ok(self.apply_masks(text, detected, start_time, placeholders || tokenized))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, InMemoryTokenVault};
+
+    const POLICY: &str = "\
+rules:
+  - when: { type: [ssn], source: [export] }
+    action: tokenize
+  - when: { type: [email], domain: ['@OurCompany.com'] }
+    action: ignore
+";
+
+    #[test]
+    fn test_parse_policy() {
+        let policy = Policy::parse(POLICY, ConfigFormat::Yaml).unwrap();
+        assert_eq!(policy.rules.len(), 2);
+        assert_eq!(policy.rules[0].action, PolicyAction::Tokenize);
+        assert_eq!(policy.rules[1].when.domains, ["ourcompany.com"]);
+        assert_eq!(policy.default_action, PolicyAction::Mask);
+
+        let toml = "default_action = \"ignore\"\n[[rules]]\naction = \"mask\"\nwhen = { type = [\"ssn\"] }\n";
+        let policy = Policy::parse(toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(policy.default_action, PolicyAction::Ignore);
+        assert_eq!(policy.rules[0].when.types, ["ssn"]);
+
+        assert!(Policy::parse("rules: [{ action: redact }]", ConfigFormat::Yaml).is_err());
+        assert!(Policy::parse(
+            "rules: [{ when: { domain: ['@'] }, action: ignore }]",
+            ConfigFormat::Yaml
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_policy_actions_apply_per_finding() {
+        let policy = Policy::parse(POLICY, ConfigFormat::Yaml).unwrap();
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let vault = InMemoryTokenVault::new();
+        let text = "SSN 123-45-6789 for ops@mail.ourcompany.com and jane@gmail.com";
+
+        let result = engine
+            .mask_text_with_policy(text, "export", &policy, &vault)
+            .unwrap();
+        let ssn = result
+            .detected_pii
+            .iter()
+            .find(|pii| pii.pii_type == "ssn")
+            .unwrap();
+        assert!(ssn.masked.starts_with("tok_ssn_"));
+        assert_eq!(
+            result.masked_text,
+            format!(
+                "SSN {} for ops@mail.ourcompany.com and j***@gmail.com",
+                ssn.masked
+            )
+        );
+        assert_eq!(result.detected_pii.len(), 2);
+        assert_eq!(result.metadata.allowlisted, 1);
+        assert_eq!(vault.len(), 1);
+
+        let result = engine
+            .mask_text_with_policy(text, "support", &policy, &vault)
+            .unwrap();
+        assert_eq!(
+            result.masked_text,
+            "SSN ***-**-6789 for ops@mail.ourcompany.com and j***@gmail.com"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{DataCloakConfig, DataCloakEngine, DataCloakError};
+
+// Engines are shared across threads; fail the build if that ever stops
+// being sound.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DataCloakEngine>();
+    assert_send_sync::<EnginePool>();
+};
+
+/// A fixed set of identically configured engines handed out round-robin.
+///
+/// One engine already serves concurrent callers, so a pool only helps when
+/// placeholder masking makes threads contend for the registry lock. Each
+/// engine numbers placeholders independently; hosts that need one numbering
+/// across calls should share a single engine instead.
+#[derive(Debug)]
+pub struct EnginePool {
+    engines: Vec<DataCloakEngine>,
+    next: AtomicUsize,
+}
+
+impl EnginePool {
+    pub fn new(config: DataCloakConfig, size: usize) -> Result<Self, DataCloakError> {
+        if size == 0 {
+            return Err(DataCloakError::InvalidConfig(
+                "Engine pool size must be greater than zero".to_string(),
+            ));
+        }
+
+        // Compile once; members share the patterns but not the registry
+        let first = DataCloakEngine::new(config)?;
+        let mut engines = Vec::with_capacity(size);
+        for _ in 1..size {
+            engines.push(first.clone_with_own_placeholders());
+        }
+        engines.push(first);
+        Ok(Self {
+            engines,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.engines.len()
+    }
+
+    /// Returns the next engine in round-robin order.
+    pub fn engine(&self) -> &DataCloakEngine {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.engines.len();
+        &self.engines[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_pool_serves_concurrent_scans() {
+        let pool = Arc::new(EnginePool::new(DataCloakConfig::default(), 3).unwrap());
+        assert_eq!(pool.size(), 3);
+        assert!(EnginePool::new(DataCloakConfig::default(), 0).is_err());
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let pool = Arc::clone(&pool);
+                std::thread::spawn(move || {
+                    let text = format!("user{}@example.com", i);
+                    pool.engine().detect_pii(&text).unwrap().len()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 1);
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+use crate::config::LOCALE_TYPES;
+use crate::{CreditCardValidation, DataCloakConfig, Locale, MaskingStrategy, RegionProfile};
+
+/// Magnetic stripe track 1 (`%B<PAN>^<NAME>^<YYMM><service code>...?`) and
+/// track 2 (`;<PAN>=<YYMM><service code>...?`) data, sentinels optional.
+const TRACK_DATA: &str = r"%?B\d{13,19}\^[^^\n]{2,26}\^\d{7}[^?\n]*\??|;?\d{13,19}=\d{7}\d*\??";
+
+/// Card verification codes, recognized by their label: `CVV: 123`.
+const CARD_SECURITY_CODE: &str =
+    r"(?i)\b(?:cvv2?|cvc2?|cid|security code)[ \t]*[:#]?[ \t]*\d{3,4}\b";
+
+/// Dates more precise than a year: `03/14/2024`, `2024-03-14`, `Mar 14, 2024`.
+const DATE: &str = r"(?i)\b(?:\d{1,2}[/-]\d{1,2}[/-](?:\d{4}|\d{2})|\d{4}-\d{2}-\d{2}|(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?[ \t]+\d{1,2}(?:st|nd|rd|th)?,?[ \t]+\d{4})\b";
+
+/// Ages over 89, which Safe Harbor folds into a single "90 or older".
+const AGE_OVER_89: &str = r"(?i)\b(?:9\d|1[0-2]\d)[- ](?:years?|yrs?)[- ]old\b";
+
+/// Street addresses: a house number, up to four capitalized words and a
+/// street suffix.
+const STREET_ADDRESS: &str = r"\b\d{1,6}[ \t]+(?:[A-Z][a-z]+[ \t]+){1,4}(?:Street|St|Avenue|Ave|Road|Rd|Boulevard|Blvd|Lane|Ln|Drive|Dr|Court|Ct|Way|Place|Pl)\b";
+
+/// Vehicle identification numbers, whose ninth character is a check digit.
+const VIN: &str = r"\b[A-HJ-NPR-Z0-9]{8}[0-9X][A-HJ-NPR-Z0-9]{8}\b";
+
+/// `http(s)://` and `www.` URLs, without trailing punctuation.
+const URL: &str = r#"\bhttps?://[^\s<>"]*[^\s<>".,;:!?)]|\bwww\.[^\s<>"]*[^\s<>".,;:!?)]"#;
+
+/// IPv4 addresses and uncompressed IPv6 addresses.
+const IP_ADDRESS: &str = r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b|\b(?:[0-9A-Fa-f]{1,4}:){7}[0-9A-Fa-f]{1,4}\b";
+
+/// International bank account numbers, compact or in groups of four.
+const IBAN: &str = r"\b[A-Z]{2}\d{2}(?:[ ]?[A-Z0-9]{4}){2,7}(?:[ ]?[A-Z0-9]{1,3})?\b";
+
+/// An identifier recognized by one of the `|`-separated `labels` before it,
+/// as in `MRN: 00123456` or `Member ID XJ-4410298`. The identifier must hold
+/// a digit, so `account closed` is not one.
+fn labeled_identifier(labels: &str) -> String {
+    format!(
+        r"(?i)\b(?:{})\b[ \t]*(?:no\.?|number|id)?[ \t]*[:#]?[ \t]*[A-Z0-9-]*\d[A-Z0-9-]*\b",
+        labels
+    )
+}
+
+impl DataCloakConfig {
+    /// Cardholder data under PCI DSS: card numbers, cardholder names, track
+    /// data and card verification codes.
+    ///
+    /// PANs are masked down to their last four digits, the most requirement
+    /// 3.4 allows to be shown; track data and verification codes, which may
+    /// not be stored at all, are redacted whole. Numbers failing the Luhn
+    /// check are still masked and flagged as `low_confidence`, and every
+    /// finding carries the `explanation` of why it was flagged, for audit
+    /// trails. Card data hidden in percent-encoded or base64 log fields is
+    /// decoded and masked too.
+    pub fn pci_dss() -> Self {
+        let mut config = Self {
+            credit_card_validation: CreditCardValidation::Luhn,
+            masking_strategy: MaskingStrategy::Partial,
+            enabled_types: ["credit_card", "person"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            explain_confidence: true,
+            report_low_confidence: true,
+            decode_base64: true,
+            decode_percent_encoding: true,
+            ..Self::default()
+        };
+        config.reveal_lengths.insert("credit_card".to_string(), 4);
+        for (pii_type, pattern) in [
+            ("track_data", TRACK_DATA),
+            ("card_security_code", CARD_SECURITY_CODE),
+        ] {
+            config
+                .custom_patterns
+                .insert(pii_type.to_string(), pattern.to_string());
+        }
+        config
+    }
+
+    /// Protected health information under the HIPAA Safe Harbor method,
+    /// covering its identifier categories that appear in text: names, street
+    /// addresses and ZIP codes, dates and ages over 89, phone and fax numbers,
+    /// email addresses, SSNs, medical record, health plan, account, license,
+    /// vehicle and device numbers, URLs and IP addresses.
+    ///
+    /// Every identifier is replaced by a numbered placeholder such as `[DATE_1]`.
+    /// Safe Harbor forbids re-identification codes derived from the data, so
+    /// no partial masks or pseudonyms are used. Record numbers are recognized
+    /// by their label and redacted along with it. Names are only found when
+    /// `first_names`/`surnames` are set or with the `ner` feature.
+    pub fn hipaa_safe_harbor() -> Self {
+        let mut config = Self {
+            masking_strategy: MaskingStrategy::Placeholder,
+            enabled_types: [
+                "email",
+                "phone",
+                "ssn",
+                "credit_card",
+                "person",
+                "postal_code",
+            ]
+            .iter()
+            .map(|t| t.to_string())
+            .collect(),
+            locales: vec![Locale::Us],
+            decode_percent_encoding: true,
+            ..Self::default()
+        };
+        let labeled = [
+            ("medical_record_number", "mrn|medical record"),
+            (
+                "health_plan_id",
+                "member|beneficiary|health plan|insurance|policy",
+            ),
+            ("account_number", "account|acct"),
+            ("license_number", "license|licence|certificate|dea|npi"),
+            ("device_id", "device|serial|imei|udi"),
+            ("license_plate", "license plate|plate"),
+        ];
+        for (pii_type, labels) in labeled {
+            config
+                .custom_patterns
+                .insert(pii_type.to_string(), labeled_identifier(labels));
+        }
+        for (pii_type, pattern) in [
+            ("date", DATE),
+            ("age_over_89", AGE_OVER_89),
+            ("street_address", STREET_ADDRESS),
+            ("vin", VIN),
+            ("url", URL),
+            ("ip_address", IP_ADDRESS),
+        ] {
+            config
+                .custom_patterns
+                .insert(pii_type.to_string(), pattern.to_string());
+        }
+        config
+    }
+
+    /// Personal data under the GDPR across the EU and UK: names, emails,
+    /// phone numbers, postal codes and national IDs of every EU and UK locale
+    /// pack, card numbers, IBANs and IP addresses, which the regulation counts
+    /// as personal data too.
+    ///
+    /// Values are pseudonymized with HMAC under `key`, so the same person
+    /// maps to the same token across documents and datasets can still be
+    /// joined. Keep `key` apart from the masked data: with it the tokens can
+    /// be linked back to candidate values, which is what makes them
+    /// pseudonymous rather than anonymous under Article 4(5).
+    pub fn gdpr(key: &[u8]) -> Self {
+        let mut enabled_types: HashSet<String> = ["email", "phone", "credit_card", "person"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        enabled_types.extend(LOCALE_TYPES.iter().map(|t| t.to_string()));
+        let mut config = Self {
+            masking_strategy: MaskingStrategy::Hmac { key: key.to_vec() },
+            enabled_types,
+            regions: vec![RegionProfile::Eu, RegionProfile::Uk],
+            decode_percent_encoding: true,
+            ..Self::default()
+        };
+        for (pii_type, pattern) in [("iban", IBAN), ("ip_address", IP_ADDRESS)] {
+            config
+                .custom_patterns
+                .insert(pii_type.to_string(), pattern.to_string());
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataCloakConfig, DataCloakEngine};
+
+    #[test]
+    fn test_gdpr_pseudonymizes_consistently() {
+        let engine = DataCloakEngine::new(DataCloakConfig::gdpr(b"pseudonym-key")).unwrap();
+        let result = engine
+            .mask_text(
+                "anna@example.de paid from DE89370400440532013000 at 192.168.1.20; \
+                 refund anna@example.de",
+            )
+            .unwrap();
+
+        let types: Vec<_> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.pii_type.as_str())
+            .collect();
+        assert!(types.contains(&"iban"));
+        assert!(types.contains(&"ip_address"));
+        let email = result
+            .detected_pii
+            .iter()
+            .find(|pii| pii.pii_type == "email")
+            .unwrap();
+        assert!(email.masked.starts_with("EMAIL_"));
+        assert_eq!(result.masked_text.matches(&email.masked).count(), 2);
+        assert!(!result.masked_text.contains("192.168.1.20"));
+
+        assert!(DataCloakEngine::new(DataCloakConfig::gdpr(b"")).is_err());
+    }
+
+    #[test]
+    fn test_hipaa_safe_harbor_redacts_identifiers() {
+        let engine = DataCloakEngine::new(DataCloakConfig::hipaa_safe_harbor()).unwrap();
+        let result = engine
+            .mask_text(
+                "Seen 03/14/2024, MRN: 00123456, a 93-year-old of 221 Baker Street. \
+                 Portal https://portal.example.org/p?id=7 from 10.0.12.7, account closed.",
+            )
+            .unwrap();
+
+        assert_eq!(
+            result.masked_text,
+            "Seen [DATE_1], [MEDICAL_RECORD_NUMBER_1], a [AGE_OVER_89_1] of [STREET_ADDRESS_1]. \
+             Portal [URL_1] from [IP_ADDRESS_1], account closed."
+        );
+    }
+
+    #[test]
+    fn test_pci_dss_masks_cardholder_data() {
+        let engine = DataCloakEngine::new(DataCloakConfig::pci_dss()).unwrap();
+        let result = engine
+            .mask_text(
+                "Card 4532015112830366, CVV: 123, swipe ;4532015112830366=27121010000012345678? \
+                 reply to jane@example.com",
+            )
+            .unwrap();
+
+        assert_eq!(
+            result.masked_text,
+            "Card **** **** **** 0366, ***, swipe *** reply to jane@example.com"
+        );
+        let types: Vec<_> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.pii_type.as_str())
+            .collect();
+        assert!(types.contains(&"track_data"));
+        assert!(types.contains(&"card_security_code"));
+        assert!(result
+            .detected_pii
+            .iter()
+            .all(|pii| pii.explanation.is_some()));
+    }
+}
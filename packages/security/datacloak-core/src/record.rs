@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::document::FieldTotals;
+use crate::{DataCloakEngine, DataCloakError, FieldContext, MaskingMetadata, PIIDetectionResult};
+
+/// A record of named fields with each value masked.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordMaskingResult {
+    /// The input record with every value masked; keys are unchanged.
+    pub masked: HashMap<String, String>,
+    /// Findings with `field_name` set to the key of their value and offsets
+    /// relative to that value.
+    pub detected_pii: Vec<PIIDetectionResult>,
+    /// Totals over all fields; `fields_processed` counts the fields.
+    pub metadata: MaskingMetadata,
+    pub token_map: HashMap<String, String>,
+}
+
+impl DataCloakEngine {
+    /// Detects PII in every value of `record` with
+    /// `detect_pii_with_context`, using each key as the field name: each
+    /// finding's `field_name` is the key of the value it was found in, and
+    /// findings of the type a key names (`ssn`, `billing_phone`,
+    /// `contactEmail`) are reported with raised confidence. Fields are
+    /// scanned in key order.
+    pub fn detect_record(
+        &self,
+        record: &HashMap<String, String>,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut findings = Vec::new();
+        for (field, value) in sorted(record) {
+            findings.extend(self.detect_pii_with_context(value, &FieldContext::named(field))?);
+        }
+        Ok(findings)
+    }
+
+    /// Masks every value of `record` with `mask_text_with_context`,
+    /// reporting findings the way `detect_record` does. Fields are masked in
+    /// key order, so placeholder numbering does not depend on the map's
+    /// iteration order.
+    pub fn mask_record(
+        &self,
+        record: &HashMap<String, String>,
+    ) -> Result<RecordMaskingResult, DataCloakError> {
+        let mut totals = FieldTotals::new();
+        let mut masked = HashMap::with_capacity(record.len());
+        for (field, value) in sorted(record) {
+            let result = self.mask_text_with_context(value, &FieldContext::named(field))?;
+            masked.insert(field.clone(), totals.add(field, result));
+        }
+
+        let totals = totals.finish();
+        Ok(RecordMaskingResult {
+            masked,
+            detected_pii: totals.detected_pii,
+            metadata: totals.metadata,
+            token_map: totals.token_map,
+        })
+    }
+}
+
+fn sorted(record: &HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut fields: Vec<_> = record.iter().collect();
+    fields.sort();
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::HINTED_CONFIDENCE;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    fn record(fields: &[(&str, &str)]) -> HashMap<String, String> {
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_findings_carry_field_names() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let findings = engine
+            .detect_record(&record(&[
+                ("billing_phone", "555-123-4567"),
+                ("notes", "reach me at 555-987-6543"),
+                ("id", "42"),
+            ]))
+            .unwrap();
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].field_name, "billing_phone");
+        assert_eq!(findings[0].confidence, HINTED_CONFIDENCE);
+        assert_eq!(findings[1].field_name, "notes");
+        assert!(findings[1].confidence < HINTED_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_mask_record_numbers_fields_in_key_order() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let result = engine
+            .mask_record(&record(&[
+                ("secondary", "b@example.com"),
+                ("primary", "a@example.com"),
+            ]))
+            .unwrap();
+
+        assert_eq!(result.masked["primary"], "[EMAIL_1]");
+        assert_eq!(result.masked["secondary"], "[EMAIL_2]");
+        assert_eq!(result.metadata.fields_processed, 2);
+        assert_eq!(result.token_map["[EMAIL_2]"], "b@example.com");
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::tokenization::TokenVault;
+
+/// The secret material needed to reverse a mask.
+pub enum Reidentifier<'a> {
+    /// Resolve `tok_*` tokens through the vault that issued them.
+    Vault(&'a dyn TokenVault),
+    /// Decrypt credit card numbers and SSNs masked with
+    /// `MaskingStrategy::FormatPreserving` under this key.
+    FormatPreserving { key: &'a [u8] },
+}
+
+/// A single restored value. The original value is deliberately not part of
+/// the event so audit sinks never become a second copy of the PII.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReidentificationEvent {
+    pub token: String,
+    pub pii_type: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Receives an event for every value restored by `DataCloakEngine::unmask_text`.
+pub trait ReidentificationAudit: Send + Sync {
+    fn record(&self, event: &ReidentificationEvent);
+}
+
+#[derive(Clone)]
+pub(crate) struct AuditHook(pub(crate) Arc<dyn ReidentificationAudit>);
+
+impl fmt::Debug for AuditHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditHook")
+    }
+}
+
+pub(crate) fn token_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\btok_([A-Za-z0-9_]+)_([0-9a-f]{32})\b").expect("token pattern is valid")
+    })
+}
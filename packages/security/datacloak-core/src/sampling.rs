@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DataCloakEngine, DataCloakError, PIIDetectionResult};
+
+/// How much of an input `detect_pii_sampled` looks at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanMode {
+    /// Scan the whole input.
+    #[default]
+    Full,
+    /// Scan about `bytes` bytes of the input and extrapolate counts from them.
+    /// Inputs no larger than `bytes` are scanned in full.
+    Sample {
+        bytes: usize,
+        strategy: SampleStrategy,
+    },
+}
+
+/// Which parts of the input a sampled scan covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleStrategy {
+    /// The first bytes of the input.
+    Head,
+    /// `windows` windows at random positions, one in each equal slice of the
+    /// input so the whole file is represented. The same seed picks the same
+    /// windows.
+    RandomWindows { windows: usize, seed: u64 },
+}
+
+/// Findings of a sampled scan, with per-type counts scaled up to the size of
+/// the whole input.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SampleReport {
+    /// Findings in the scanned windows; offsets are bytes into the full input.
+    pub results: Vec<PIIDetectionResult>,
+    pub bytes_scanned: usize,
+    pub total_bytes: usize,
+    /// Expected number of matches per PII type across the whole input.
+    pub estimated_counts: HashMap<String, u64>,
+}
+
+impl SampleReport {
+    /// Whether the scan covered the input completely, so the estimates are
+    /// exact counts.
+    pub fn is_complete(&self) -> bool {
+        self.bytes_scanned == self.total_bytes
+    }
+}
+
+impl ScanMode {
+    /// Byte ranges of an input of `total` bytes to scan, in order and
+    /// non-overlapping.
+    fn windows(&self, total: usize) -> Vec<Range<usize>> {
+        let (bytes, strategy) = match *self {
+            ScanMode::Sample { bytes, strategy } if bytes < total => (bytes, strategy),
+            _ => return std::iter::once(0..total).collect(),
+        };
+
+        match strategy {
+            SampleStrategy::Head => std::iter::once(0..bytes).collect(),
+            SampleStrategy::RandomWindows { windows, seed } => {
+                let windows = windows.clamp(1, bytes.max(1));
+                let window = bytes / windows;
+                let slice = total / windows;
+                let mut rng = SplitMix64(seed);
+                (0..windows)
+                    .map(|i| {
+                        let start = i * slice + (rng.next() % (slice - window + 1) as u64) as usize;
+                        start..start + window
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Small deterministic generator for window placement; sampling needs
+/// reproducibility, not unpredictability.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl DataCloakEngine {
+    /// Detects PII in the parts of `text` selected by `mode` to estimate how
+    /// much PII the whole text holds, e.g. for a quick profile of a large
+    /// export. Windows are scanned like `detect_stream`, so they may exceed
+    /// `max_text_length`.
+    pub fn detect_pii_sampled(
+        &self,
+        text: &str,
+        mode: &ScanMode,
+    ) -> Result<SampleReport, DataCloakError> {
+        self.sample_bytes(text.as_bytes(), mode)
+    }
+
+    fn sample_bytes(&self, input: &[u8], mode: &ScanMode) -> Result<SampleReport, DataCloakError> {
+        let mut results = Vec::new();
+        let mut bytes_scanned = 0;
+
+        for window in mode.windows(input.len()) {
+            let window = snap_window(input, window);
+            for mut pii in self.detect_stream(&input[window.clone()])? {
+                pii.start += window.start;
+                pii.end += window.start;
+                results.push(pii);
+            }
+            bytes_scanned += window.len();
+        }
+
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for pii in &results {
+            *counts.entry(pii.pii_type.clone()).or_insert(0) += 1;
+        }
+        let scale = if bytes_scanned == 0 {
+            0.0
+        } else {
+            input.len() as f64 / bytes_scanned as f64
+        };
+        let estimated_counts = counts
+            .into_iter()
+            .map(|(pii_type, count)| (pii_type, (count as f64 * scale).round() as u64))
+            .collect();
+
+        Ok(SampleReport {
+            results,
+            bytes_scanned,
+            total_bytes: input.len(),
+            estimated_counts,
+        })
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl DataCloakEngine {
+    /// Memory-maps the file at `path` and samples it like
+    /// `detect_pii_sampled`, reading only the selected windows from disk.
+    pub fn scan_file_sampled<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        mode: &ScanMode,
+    ) -> Result<SampleReport, DataCloakError> {
+        let path = path.as_ref();
+        let io_error = |e: std::io::Error| DataCloakError::Io(format!("{}: {}", path.display(), e));
+
+        let file = std::fs::File::open(path).map_err(io_error)?;
+        if file.metadata().map_err(io_error)?.len() == 0 {
+            return self.sample_bytes(&[], mode);
+        }
+
+        // Safety: see `scan_file`
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+        self.sample_bytes(&map[..], mode)
+    }
+}
+
+/// Shrinks a window to whitespace boundaries so it neither starts nor ends
+/// inside a value (or a multi-byte character). Windows touching either end of
+/// the input keep that end.
+fn snap_window(input: &[u8], window: Range<usize>) -> Range<usize> {
+    let mut start = window.start;
+    if start > 0 {
+        while start < window.end && !input[start - 1].is_ascii_whitespace() {
+            start += 1;
+        }
+    }
+    let mut end = window.end;
+    if end < input.len() {
+        while end > start && !input[end].is_ascii_whitespace() {
+            end -= 1;
+        }
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_head_sample_scans_prefix_and_extrapolates() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let text = "contact jane@example.com now ".repeat(100);
+
+        let mode = ScanMode::Sample {
+            bytes: text.len() / 4,
+            strategy: SampleStrategy::Head,
+        };
+        let report = engine.detect_pii_sampled(&text, &mode).unwrap();
+        assert!(!report.is_complete());
+        assert!(report.bytes_scanned <= text.len() / 4);
+        assert_eq!(report.results.len(), 25);
+        let estimate = report.estimated_counts["email"];
+        assert!((95..=105).contains(&estimate), "estimate {}", estimate);
+
+        let full = engine.detect_pii_sampled(&text, &ScanMode::Full).unwrap();
+        assert!(full.is_complete());
+        assert_eq!(full.estimated_counts["email"], 100);
+    }
+
+    #[test]
+    fn test_random_windows_report_input_offsets() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let text = "call 555-123-4567 or not ".repeat(400);
+
+        let mode = ScanMode::Sample {
+            bytes: 1000,
+            strategy: SampleStrategy::RandomWindows {
+                windows: 4,
+                seed: 7,
+            },
+        };
+        let report = engine.detect_pii_sampled(&text, &mode).unwrap();
+        assert!(!report.results.is_empty());
+        for pii in &report.results {
+            assert_eq!(&text[pii.start..pii.end], "555-123-4567");
+        }
+        assert_eq!(
+            engine
+                .detect_pii_sampled(&text, &mode)
+                .unwrap()
+                .results
+                .len(),
+            report.results.len()
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::DataCloakEngine;
+
+/// A pattern match about to be scored, with the confidence the built-in
+/// heuristics gave it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreCandidate<'a> {
+    pub pii_type: &'a str,
+    pub sample: &'a str,
+    /// The whole scanned text; the match is at `start..end`.
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+    /// Outcome of email or Luhn validation, `None` if the type has none or
+    /// it is turned off.
+    pub validated: Option<bool>,
+    /// Confidence after validation and context keywords.
+    pub confidence: f64,
+}
+
+/// Replaces the built-in confidence of each match, e.g. with a calibrated
+/// model. Scores are clamped to `0.0..=1.0` and then held to the
+/// configured confidence thresholds like built-in ones.
+pub trait ConfidenceScorer: Send + Sync {
+    fn score(&self, candidate: &ScoreCandidate<'_>) -> f64;
+}
+
+#[derive(Clone)]
+pub(crate) struct ScorerHook(pub(crate) Arc<dyn ConfidenceScorer>);
+
+impl fmt::Debug for ScorerHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ScorerHook")
+    }
+}
+
+impl DataCloakEngine {
+    /// Scores every candidate match with `scorer` instead of the built-in
+    /// heuristics, which it receives as `ScoreCandidate::confidence`.
+    pub fn with_confidence_scorer(mut self, scorer: Arc<dyn ConfidenceScorer>) -> Self {
+        self.scorer = Some(ScorerHook(scorer));
+        self
+    }
+
+    /// The registered scorer's confidence for `candidate`, if there is one.
+    pub(crate) fn custom_score(&self, candidate: &ScoreCandidate<'_>) -> Option<f64> {
+        let ScorerHook(scorer) = self.scorer.as_ref()?;
+        Some(scorer.score(candidate).clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    /// Trusts only card numbers that pass Luhn, and distrusts everything
+    /// that looks like a phone number.
+    struct Calibrated;
+
+    impl ConfidenceScorer for Calibrated {
+        fn score(&self, candidate: &ScoreCandidate<'_>) -> f64 {
+            match (candidate.pii_type, candidate.validated) {
+                ("credit_card", Some(true)) => 0.8,
+                ("phone", _) => 0.1,
+                _ => candidate.confidence,
+            }
+        }
+    }
+
+    #[test]
+    fn test_scorer_overrides_confidence() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default())
+            .unwrap()
+            .with_confidence_scorer(Arc::new(Calibrated));
+        let findings = engine
+            .detect_pii("pay 4111 1111 1111 1111, call 555-123-4567, 123-45-6789")
+            .unwrap();
+
+        let types: Vec<_> = findings.iter().map(|pii| pii.pii_type.as_str()).collect();
+        assert_eq!(types, ["ssn", "credit_card"]);
+        assert_eq!(findings[0].confidence, 0.95);
+        assert_eq!(findings[1].confidence, 0.8);
+    }
+}
@@ -0,0 +1,655 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use serde::ser::{
+    self, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+use crate::DataCloakEngine;
+
+/// How one struct field is handled by `DataCloakEngine::masked`. Fields
+/// without a rule have their strings scanned like any other value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldRule {
+    /// Serialized unchanged, nested values included.
+    Skip,
+    /// Every string and number in the field is masked whole as this PII
+    /// type, without detection; numbers become strings.
+    Mask(&'static str),
+}
+
+/// Field rules by serialized struct name, then by serialized field name.
+#[derive(Debug, Clone, Default)]
+pub struct FieldRules {
+    by_struct: HashMap<&'static str, HashMap<&'static str, FieldRule>>,
+}
+
+impl FieldRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts the rules of `struct_name`. Returns false if they were
+    /// already added, which ends the recursion for self-referential types.
+    pub fn visit(&mut self, struct_name: &'static str) -> bool {
+        match self.by_struct.entry(struct_name) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(HashMap::new());
+                true
+            }
+        }
+    }
+
+    pub fn add(&mut self, struct_name: &'static str, field: &'static str, rule: FieldRule) {
+        self.by_struct
+            .entry(struct_name)
+            .or_default()
+            .insert(field, rule);
+    }
+
+    fn get(&self, struct_name: &str, field: &str) -> Option<FieldRule> {
+        self.by_struct.get(struct_name)?.get(field).copied()
+    }
+}
+
+/// Types that declare per-field masking rules, usually through
+/// `#[derive(DataCloak)]` with `#[datacloak(mask = "email")]`,
+/// `#[datacloak(skip)]` or `#[datacloak(nested)]` on fields.
+pub trait MaskRules {
+    fn mask_rules(rules: &mut FieldRules);
+}
+
+impl<T: MaskRules + ?Sized> MaskRules for Box<T> {
+    fn mask_rules(rules: &mut FieldRules) {
+        T::mask_rules(rules)
+    }
+}
+
+impl<T: MaskRules> MaskRules for Option<T> {
+    fn mask_rules(rules: &mut FieldRules) {
+        T::mask_rules(rules)
+    }
+}
+
+impl<T: MaskRules> MaskRules for Vec<T> {
+    fn mask_rules(rules: &mut FieldRules) {
+        T::mask_rules(rules)
+    }
+}
+
+impl<T: MaskRules> MaskRules for [T] {
+    fn mask_rules(rules: &mut FieldRules) {
+        T::mask_rules(rules)
+    }
+}
+
+/// A value that serializes with its PII masked. Created by
+/// `DataCloakEngine::masked`.
+pub struct Masked<'a, T: ?Sized> {
+    engine: &'a DataCloakEngine,
+    value: &'a T,
+    rules: FieldRules,
+}
+
+impl<T: ?Sized> Masked<'_, T> {
+    /// Applies the field rules of `R` and every type it marks `nested`.
+    pub fn with_rules<R: MaskRules + ?Sized>(mut self) -> Self {
+        R::mask_rules(&mut self.rules);
+        self
+    }
+}
+
+impl DataCloakEngine {
+    /// Wraps `value` so that serializing it, with any serde format, masks
+    /// every string the way `mask_text` would. Map keys, numbers, booleans
+    /// and enum variant names are written unchanged. Field rules declared
+    /// with `#[derive(DataCloak)]` apply once added with `with_rules`:
+    ///
+    /// ```ignore
+    /// let json = serde_json::to_string(&engine.masked(&user).with_rules::<User>())?;
+    /// ```
+    ///
+    /// Rules match the names serde writes, so a container-level
+    /// `#[serde(rename_all)]` or a `#[serde(flatten)]` field hides them;
+    /// the derive rejects both. A string the engine cannot scan fails the
+    /// serialization with the engine's error.
+    pub fn masked<'a, T: Serialize + ?Sized>(&'a self, value: &'a T) -> Masked<'a, T> {
+        Masked {
+            engine: self,
+            value,
+            rules: FieldRules::new(),
+        }
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for Masked<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let cx = Cx {
+            engine: self.engine,
+            rules: &self.rules,
+        };
+        self.value.serialize(MaskingSerializer {
+            inner: serializer,
+            cx,
+            mode: Mode::Scan,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Cx<'a> {
+    engine: &'a DataCloakEngine,
+    rules: &'a FieldRules,
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Scan,
+    Mask(&'static str),
+}
+
+/// A value serialized through `MaskingSerializer` in `mode`.
+struct MaskedValue<'a, T: ?Sized> {
+    value: &'a T,
+    cx: Cx<'a>,
+    mode: Mode,
+}
+
+impl<T: Serialize + ?Sized> Serialize for MaskedValue<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(MaskingSerializer {
+            inner: serializer,
+            cx: self.cx,
+            mode: self.mode,
+        })
+    }
+}
+
+/// Forwards to `inner`, replacing strings (and in `Mode::Mask` numbers)
+/// with their masked text.
+struct MaskingSerializer<'a, S> {
+    inner: S,
+    cx: Cx<'a>,
+    mode: Mode,
+}
+
+impl<'a, S: Serializer> MaskingSerializer<'a, S> {
+    fn masked_text(&self, text: &str, mode: Mode) -> Result<String, S::Error> {
+        let result = match mode {
+            Mode::Scan => self.cx.engine.mask_text(text),
+            Mode::Mask(pii_type) => self.cx.engine.mask_whole_value(text, pii_type, ""),
+        };
+        result
+            .map(|result| result.masked_text)
+            .map_err(ser::Error::custom)
+    }
+
+    fn scalar<V: Display>(
+        self,
+        value: V,
+        forward: fn(S, V) -> Result<S::Ok, S::Error>,
+    ) -> Result<S::Ok, S::Error> {
+        match self.mode {
+            Mode::Scan => forward(self.inner, value),
+            Mode::Mask(_) => {
+                let masked = self.masked_text(&value.to_string(), self.mode)?;
+                self.inner.serialize_str(&masked)
+            }
+        }
+    }
+}
+
+impl<'a, S: Serializer> Serializer for MaskingSerializer<'a, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<'a, S::SerializeSeq>;
+    type SerializeTuple = Compound<'a, S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<'a, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<'a, S::SerializeTupleVariant>;
+    type SerializeMap = Compound<'a, S::SerializeMap>;
+    type SerializeStruct = Compound<'a, S::SerializeStruct>;
+    type SerializeStructVariant = Compound<'a, S::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_i8)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_i16)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_i32)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_i64)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_i128)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_u8)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_u16)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_u32)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_u64)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_u128)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_f32)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.scalar(v, S::serialize_f64)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        let masked = self.masked_text(v.encode_utf8(&mut [0; 4]), self.mode)?;
+        self.inner.serialize_str(&masked)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        let masked = self.masked_text(v, self.mode)?;
+        self.inner.serialize_str(&masked)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        let value = MaskedValue {
+            value,
+            cx: self.cx,
+            mode: self.mode,
+        };
+        self.inner.serialize_some(&value)
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = MaskedValue {
+            value,
+            cx: self.cx,
+            mode: self.mode,
+        };
+        self.inner.serialize_newtype_struct(name, &value)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = MaskedValue {
+            value,
+            cx: self.cx,
+            mode: self.mode,
+        };
+        self.inner
+            .serialize_newtype_variant(name, variant_index, variant, &value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        let inner = self.inner.serialize_seq(len)?;
+        Ok(Compound::new(inner, self.cx, self.mode, None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        let inner = self.inner.serialize_tuple(len)?;
+        Ok(Compound::new(inner, self.cx, self.mode, None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let inner = self.inner.serialize_tuple_struct(name, len)?;
+        Ok(Compound::new(inner, self.cx, self.mode, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let inner = self
+            .inner
+            .serialize_tuple_variant(name, variant_index, variant, len)?;
+        Ok(Compound::new(inner, self.cx, self.mode, None))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        let inner = self.inner.serialize_map(len)?;
+        Ok(Compound::new(inner, self.cx, self.mode, None))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        let inner = self.inner.serialize_struct(name, len)?;
+        Ok(Compound::new(inner, self.cx, self.mode, Some(name)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let inner = self
+            .inner
+            .serialize_struct_variant(name, variant_index, variant, len)?;
+        Ok(Compound::new(inner, self.cx, self.mode, Some(name)))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Wraps each element of a sequence, map or struct being serialized.
+struct Compound<'a, C> {
+    inner: C,
+    cx: Cx<'a>,
+    mode: Mode,
+    /// Set for structs, to look up their field rules.
+    struct_name: Option<&'static str>,
+}
+
+impl<'a, C> Compound<'a, C> {
+    fn new(inner: C, cx: Cx<'a>, mode: Mode, struct_name: Option<&'static str>) -> Self {
+        Self {
+            inner,
+            cx,
+            mode,
+            struct_name,
+        }
+    }
+
+    fn value<'v, T: ?Sized>(&self, value: &'v T, mode: Mode) -> MaskedValue<'v, T>
+    where
+        'a: 'v,
+    {
+        MaskedValue {
+            value,
+            cx: self.cx,
+            mode,
+        }
+    }
+
+    fn field_rule(&self, field: &str) -> Option<FieldRule> {
+        self.cx.rules.get(self.struct_name?, field)
+    }
+}
+
+impl<C: SerializeSeq> SerializeSeq for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_element(&self.value(value, self.mode))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTuple> SerializeTuple for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_element(&self.value(value, self.mode))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleStruct> SerializeTupleStruct for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_field(&self.value(value, self.mode))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleVariant> SerializeTupleVariant for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_field(&self.value(value, self.mode))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeMap> SerializeMap for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.inner.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_value(&self.value(value, self.mode))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeStruct> SerializeStruct for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        match self.field_rule(key) {
+            Some(FieldRule::Skip) => self.inner.serialize_field(key, value),
+            Some(FieldRule::Mask(pii_type)) => self
+                .inner
+                .serialize_field(key, &self.value(value, Mode::Mask(pii_type))),
+            None => self
+                .inner
+                .serialize_field(key, &self.value(value, self.mode)),
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeStructVariant> SerializeStructVariant for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        match self.field_rule(key) {
+            Some(FieldRule::Skip) => self.inner.serialize_field(key, value),
+            Some(FieldRule::Mask(pii_type)) => self
+                .inner
+                .serialize_field(key, &self.value(value, Mode::Mask(pii_type))),
+            None => self
+                .inner
+                .serialize_field(key, &self.value(value, self.mode)),
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    #[derive(Serialize)]
+    struct Address {
+        line: String,
+        postcode: u32,
+    }
+
+    #[derive(Serialize)]
+    struct User {
+        id: u64,
+        contact: String,
+        notes: String,
+        internal_ref: String,
+        home: Option<Address>,
+    }
+
+    impl MaskRules for Address {
+        fn mask_rules(rules: &mut FieldRules) {
+            if rules.visit("Address") {
+                rules.add("Address", "postcode", FieldRule::Mask("postcode"));
+            }
+        }
+    }
+
+    impl MaskRules for User {
+        fn mask_rules(rules: &mut FieldRules) {
+            if rules.visit("User") {
+                rules.add("User", "contact", FieldRule::Mask("email"));
+                rules.add("User", "internal_ref", FieldRule::Skip);
+                <Option<Address> as MaskRules>::mask_rules(rules);
+            }
+        }
+    }
+
+    fn user() -> User {
+        User {
+            id: 7,
+            contact: "jane@example.com".to_string(),
+            notes: "call 555-123-4567".to_string(),
+            internal_ref: "ops@example.com".to_string(),
+            home: Some(Address {
+                line: "1 Main St".to_string(),
+                postcode: 90210,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_masked_scans_every_string_without_rules() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let value = serde_json::to_value(engine.masked(&user())).unwrap();
+
+        assert_eq!(value["id"], 7);
+        assert_eq!(value["contact"], "j***@example.com");
+        assert_eq!(value["notes"], "call ***-***-4567");
+        assert_eq!(value["internal_ref"], "o***@example.com");
+        assert_eq!(value["home"]["postcode"], 90210);
+    }
+
+    #[test]
+    fn test_field_rules_mask_skip_and_nest() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let users = vec![user()];
+        let value = serde_json::to_value(engine.masked(&users).with_rules::<User>()).unwrap();
+
+        assert_eq!(
+            value,
+            json!([{
+                "id": 7,
+                "contact": "[EMAIL_1]",
+                "notes": "call [PHONE_1]",
+                "internal_ref": "ops@example.com",
+                "home": { "line": "1 Main St", "postcode": "[POSTCODE_1]" },
+            }])
+        );
+    }
+}
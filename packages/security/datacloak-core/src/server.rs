@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use axum::extract::{DefaultBodyLimit, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::{DataCloakEngine, DataCloakError, MaskingResult, PIIDetectionResult};
+
+/// Request bodies larger than this are refused with 413 by default.
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Header accepted as an alternative to `Authorization: Bearer <key>`.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Settings for `http_router` and `serve_http`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Keys accepted in `Authorization: Bearer <key>` or `X-API-Key`. At
+    /// least one is required; there is no unauthenticated mode.
+    pub api_keys: Vec<String>,
+    /// Largest request body accepted, in bytes.
+    pub max_body_bytes: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: Vec::new(),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+}
+
+/// Body of `/v1/detect` and `/v1/mask` requests.
+#[derive(Debug, Deserialize)]
+struct TextRequest {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DetectResponse {
+    detections: Vec<PIIDetectionResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+struct ServerState {
+    engine: Arc<DataCloakEngine>,
+    api_keys: Vec<String>,
+}
+
+/// Builds the HTTP API for `engine`:
+///
+/// - `POST /v1/detect` with `{"text": "..."}` returns `{"detections": [...]}`
+/// - `POST /v1/mask` with `{"text": "..."}` returns a `MaskingResult`
+///
+/// Every request must carry one of `config.api_keys`, and bodies over
+/// `config.max_body_bytes` are refused with 413. Scans run on tokio's
+/// blocking pool. Engine errors are returned as `{"error": "..."}` with a
+/// 4xx status for bad input and 5xx otherwise.
+pub fn http_router(
+    engine: Arc<DataCloakEngine>,
+    config: ServerConfig,
+) -> Result<Router, DataCloakError> {
+    if config.api_keys.iter().all(|key| key.is_empty()) {
+        return Err(DataCloakError::InvalidConfig(
+            "The HTTP server needs at least one API key".to_string(),
+        ));
+    }
+    let state = Arc::new(ServerState {
+        engine,
+        api_keys: config.api_keys,
+    });
+
+    Ok(Router::new()
+        .route("/v1/detect", post(detect))
+        .route("/v1/mask", post(mask))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            require_api_key,
+        ))
+        .layer(DefaultBodyLimit::max(config.max_body_bytes))
+        .with_state(state))
+}
+
+/// Serves `http_router` on `listener` until the process exits or the
+/// listener fails.
+pub async fn serve_http(
+    listener: tokio::net::TcpListener,
+    engine: Arc<DataCloakEngine>,
+    config: ServerConfig,
+) -> Result<(), DataCloakError> {
+    let router = http_router(engine, config)?;
+    axum::serve(listener, router)
+        .await
+        .map_err(|e| DataCloakError::Io(e.to_string()))
+}
+
+async fn detect(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<TextRequest>,
+) -> Result<Json<DetectResponse>, ApiError> {
+    let detections = Arc::clone(&state.engine)
+        .detect_pii_async(request.text)
+        .await?;
+    Ok(Json(DetectResponse { detections }))
+}
+
+async fn mask(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<TextRequest>,
+) -> Result<Json<MaskingResult>, ApiError> {
+    let result = Arc::clone(&state.engine)
+        .mask_text_async(request.text)
+        .await?;
+    Ok(Json(result))
+}
+
+async fn require_api_key(
+    State(state): State<Arc<ServerState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if authorized(request.headers(), &state.api_keys) {
+        next.run(request).await
+    } else {
+        let body = ErrorResponse {
+            error: "Missing or invalid API key".to_string(),
+        };
+        (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+    }
+}
+
+fn authorized(headers: &HeaderMap, api_keys: &[String]) -> bool {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let header_key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let Some(presented) = bearer.or(header_key).map(str::trim) else {
+        return false;
+    };
+    api_keys
+        .iter()
+        .filter(|key| !key.is_empty())
+        .any(|key| keys_match(key, presented))
+}
+
+/// Compares keys without exiting at the first differing byte, so response
+/// timing does not reveal how much of a guess was right.
+fn keys_match(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+struct ApiError(DataCloakError);
+
+impl From<DataCloakError> for ApiError {
+    fn from(error: DataCloakError) -> Self {
+        ApiError(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            DataCloakError::TextTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            DataCloakError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            DataCloakError::Timeout { .. } | DataCloakError::Cancelled => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = ErrorResponse {
+            error: self.0.to_string(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_api_key_from_either_header() {
+        let keys = vec!["s3cret".to_string()];
+        let mut headers = HeaderMap::new();
+        assert!(!authorized(&headers, &keys));
+
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert!(authorized(&headers, &keys));
+        headers.insert(header::AUTHORIZATION, "Bearer s3cre".parse().unwrap());
+        assert!(!authorized(&headers, &keys));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(API_KEY_HEADER, "s3cret".parse().unwrap());
+        assert!(authorized(&headers, &keys));
+        assert!(!authorized(&headers, &[String::new()]));
+    }
+
+    #[test]
+    fn test_router_requires_an_api_key() {
+        let engine = Arc::new(DataCloakEngine::new(DataCloakConfig::default()).unwrap());
+        assert!(http_router(Arc::clone(&engine), ServerConfig::default()).is_err());
+
+        let config = ServerConfig {
+            api_keys: vec!["s3cret".to_string()],
+            ..ServerConfig::default()
+        };
+        assert!(http_router(engine, config).is_ok());
+    }
+}
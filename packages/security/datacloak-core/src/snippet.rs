@@ -0,0 +1,101 @@
+use crate::PIIDetectionResult;
+
+/// Sets the `snippet` of each finding to up to `width` characters of `text`
+/// on either side of it, with every finding inside that window replaced by
+/// its masked value so snippets never leak neighbouring PII.
+pub(crate) fn attach_snippets(text: &str, results: &mut [PIIDetectionResult], width: usize) {
+    let mut spans: Vec<(usize, usize, String)> = results
+        .iter()
+        .map(|pii| (pii.start, pii.end, pii.masked.clone()))
+        .collect();
+    spans.sort_by_key(|&(start, end, _)| (start, std::cmp::Reverse(end)));
+    let longest = spans
+        .iter()
+        .map(|(start, end, _)| end - start)
+        .max()
+        .unwrap_or(0);
+
+    for pii in results.iter_mut() {
+        let from = char_back(text, pii.start, width);
+        let to = char_forward(text, pii.end, width);
+        // Spans can only reach into the window from this far back
+        let first = spans.partition_point(|&(start, ..)| start + longest <= from);
+
+        let mut snippet = String::with_capacity(to - from);
+        let mut cursor = from;
+        for (start, end, masked) in &spans[first..] {
+            if *start >= to {
+                break;
+            }
+            if *end <= cursor {
+                continue;
+            }
+            if *start > cursor {
+                snippet.push_str(&text[cursor..*start]);
+            }
+            // A span cut by the window edge is masked whole
+            snippet.push_str(masked);
+            cursor = *end;
+        }
+        if cursor < to {
+            snippet.push_str(&text[cursor..to]);
+        }
+        pii.snippet = Some(snippet.replace(['\r', '\n', '\t'], " "));
+    }
+}
+
+/// Byte offset `chars` characters before `offset`, or the start of `text`.
+fn char_back(text: &str, offset: usize, chars: usize) -> usize {
+    match chars.checked_sub(1) {
+        Some(n) => text[..offset]
+            .char_indices()
+            .rev()
+            .nth(n)
+            .map_or(0, |(i, _)| i),
+        None => offset,
+    }
+}
+
+/// Byte offset `chars` characters after `offset`, or the end of `text`.
+fn char_forward(text: &str, offset: usize, chars: usize) -> usize {
+    text[offset..]
+        .char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(i, _)| offset + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataCloakConfig, DataCloakEngine};
+
+    #[test]
+    fn test_snippets_mask_the_match_and_its_neighbours() {
+        let config = DataCloakConfig::builder()
+            .snippet_context_chars(12)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let text = "Customer SSN 123-45-6789 and\nmail john@example.com for details";
+
+        let findings = engine.detect_pii(text).unwrap();
+        let ssn = findings.iter().find(|pii| pii.pii_type == "ssn").unwrap();
+        assert_eq!(
+            ssn.snippet.as_deref(),
+            Some("ustomer SSN ***-**-6789 and mail j***@example.com")
+        );
+        let email = findings.iter().find(|pii| pii.pii_type == "email").unwrap();
+        assert_eq!(
+            email.snippet.as_deref(),
+            Some("***-**-6789 and mail j***@example.com for details")
+        );
+    }
+
+    #[test]
+    fn test_snippets_are_off_by_default() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let findings = engine.detect_pii("call 555-123-4567").unwrap();
+        assert_eq!(findings[0].snippet, None);
+        let json = serde_json::to_string(&findings[0]).unwrap();
+        assert!(!json.contains("snippet"));
+    }
+}
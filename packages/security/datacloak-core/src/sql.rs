@@ -0,0 +1,278 @@
+use crate::document::FieldTotals;
+use crate::{DataCloakEngine, DataCloakError, DocumentMaskingResult, PIIDetectionResult};
+
+/// A string literal inside the `VALUES` list of an `INSERT` statement.
+#[derive(Debug, PartialEq)]
+struct SqlLiteral {
+    /// `table.column`, or `table.<n>` when the statement lists no columns.
+    field: String,
+    /// Byte range of the literal's content, without its quotes.
+    start: usize,
+    end: usize,
+    /// The literal escapes quotes as `\'` rather than `''`.
+    backslash_quotes: bool,
+}
+
+impl DataCloakEngine {
+    /// Detects PII in the string literals of the `INSERT INTO ... VALUES`
+    /// statements of a SQL dump. Each finding's `field_name` is the
+    /// `table.column` the value is inserted into, with offsets relative to
+    /// the unescaped value.
+    pub fn detect_pii_sql(&self, sql: &str) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut findings = Vec::new();
+        for literal in sql_literals(sql) {
+            let value = unescape_quotes(&sql[literal.start..literal.end]);
+            findings.extend(self.detect_pii(&value)?.into_iter().map(|mut pii| {
+                pii.field_name = literal.field.clone();
+                pii
+            }));
+        }
+        Ok(findings)
+    }
+
+    /// Masks the string literals of the `INSERT INTO ... VALUES` statements
+    /// of a SQL dump, such as the output of `mysqldump` or
+    /// `pg_dump --inserts`. Numbers, identifiers, comments, DDL and every
+    /// other statement are copied byte for byte, and masks are written back
+    /// with the literal's own quote escaping, so the dump still loads.
+    pub fn mask_sql(&self, sql: &str) -> Result<DocumentMaskingResult, DataCloakError> {
+        let mut totals = FieldTotals::new();
+        let mut masked = String::with_capacity(sql.len());
+        let mut copied = 0;
+
+        for literal in sql_literals(sql) {
+            let value = unescape_quotes(&sql[literal.start..literal.end]);
+            let result = totals.add(&literal.field, self.mask_text(&value)?);
+            if result == value {
+                continue;
+            }
+            let quote = if literal.backslash_quotes {
+                "\\'"
+            } else {
+                "''"
+            };
+            masked.push_str(&sql[copied..literal.start]);
+            masked.push_str(&result.replace('\'', quote));
+            copied = literal.end;
+        }
+        masked.push_str(&sql[copied..]);
+
+        Ok(totals.into_document(masked))
+    }
+}
+
+/// State of the statement being scanned.
+#[derive(Default)]
+struct Statement {
+    /// Number of words seen, to recognize the leading `INSERT`.
+    words: usize,
+    insert: bool,
+    /// The previous word was `INTO`.
+    expect_table: bool,
+    table: String,
+    columns: Vec<String>,
+    in_values: bool,
+    depth: usize,
+    column: usize,
+}
+
+/// Every string literal that is a value of an `INSERT ... VALUES` tuple.
+/// Quoted identifiers, comments and literals anywhere else are skipped.
+/// Backslash escapes are honored inside literals, as MySQL dumps use them.
+fn sql_literals(sql: &str) -> Vec<SqlLiteral> {
+    let bytes = sql.as_bytes();
+    let mut literals = Vec::new();
+    let mut statement = Statement::default();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i += sql[i..].find('\n').unwrap_or(sql.len() - i);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += sql[i + 2..].find("*/").map_or(sql.len() - i, |end| end + 4);
+            }
+            b';' => {
+                statement = Statement::default();
+                i += 1;
+            }
+            b'\'' => {
+                let (end, backslash_quotes) = literal_end(bytes, i + 1);
+                if statement.insert && statement.in_values && statement.depth > 0 {
+                    let field = match statement.columns.get(statement.column) {
+                        Some(column) => format!("{}.{}", statement.table, column),
+                        None => format!("{}.{}", statement.table, statement.column + 1),
+                    };
+                    literals.push(SqlLiteral {
+                        field,
+                        start: i + 1,
+                        end,
+                        backslash_quotes,
+                    });
+                }
+                i = (end + 1).min(bytes.len());
+            }
+            b'`' | b'"' => {
+                let quote = bytes[i];
+                let end = sql[i + 1..]
+                    .find(quote as char)
+                    .map_or(sql.len(), |end| i + 1 + end);
+                statement.identifier(&sql[i + 1..end]);
+                i = (end + 1).min(bytes.len());
+            }
+            b'(' => {
+                statement.depth += 1;
+                if statement.in_values && statement.depth == 1 {
+                    statement.column = 0;
+                }
+                i += 1;
+            }
+            b')' => {
+                statement.depth = statement.depth.saturating_sub(1);
+                i += 1;
+            }
+            b',' => {
+                if statement.in_values && statement.depth == 1 {
+                    statement.column += 1;
+                }
+                i += 1;
+            }
+            b'.' if statement.insert
+                && !statement.in_values
+                && !statement.table.is_empty()
+                && statement.columns.is_empty() =>
+            {
+                // schema-qualified table name
+                statement.table.push('.');
+                statement.expect_table = true;
+                i += 1;
+            }
+            b if b.is_ascii_alphanumeric() || b == b'_' || b == b'$' => {
+                let start = i;
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'$')
+                {
+                    i += 1;
+                }
+                statement.word(&sql[start..i]);
+            }
+            _ => i += 1,
+        }
+    }
+    literals
+}
+
+impl Statement {
+    fn word(&mut self, word: &str) {
+        self.words += 1;
+        if self.words == 1 {
+            self.insert =
+                word.eq_ignore_ascii_case("insert") || word.eq_ignore_ascii_case("replace");
+            return;
+        }
+        if !self.insert || self.in_values {
+            return;
+        }
+        if self.table.is_empty() && word.eq_ignore_ascii_case("into") {
+            self.expect_table = true;
+        } else if word.eq_ignore_ascii_case("values") || word.eq_ignore_ascii_case("value") {
+            self.in_values = true;
+        } else {
+            self.identifier(word);
+        }
+    }
+
+    fn identifier(&mut self, name: &str) {
+        if !self.insert || self.in_values {
+            return;
+        }
+        if self.expect_table {
+            self.table.push_str(name);
+            self.expect_table = false;
+        } else if self.depth == 1 {
+            self.columns.push(name.to_string());
+        }
+    }
+}
+
+/// Index of the quote closing a literal whose content starts at `start`,
+/// and whether the literal escaped a quote with a backslash.
+fn literal_end(bytes: &[u8], start: usize) -> (usize, bool) {
+    let mut backslash_quotes = false;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => {
+                backslash_quotes |= bytes.get(i + 1) == Some(&b'\'');
+                i += 2;
+            }
+            b'\'' if bytes.get(i + 1) == Some(&b'\'') => i += 2,
+            b'\'' => return (i, backslash_quotes),
+            _ => i += 1,
+        }
+    }
+    (bytes.len(), backslash_quotes)
+}
+
+/// Literal content with `''` and `\'` turned back into quotes. Other
+/// backslash sequences are kept as written, so they survive a round trip.
+fn unescape_quotes(content: &str) -> String {
+    content.replace("''", "'").replace("\\'", "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    fn engine() -> DataCloakEngine {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        DataCloakEngine::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_mask_sql_masks_only_insert_literals() {
+        let dump = "-- contact a@example.com\n\
+                    CREATE TABLE users (email varchar(64) DEFAULT 'x@example.com');\n\
+                    INSERT INTO `shop`.`users` (`id`, `email`, `note`) VALUES \
+                    (1,'a@example.com','it''s 555-123-4567'),(2,'b@example.com',NULL);\n\
+                    SELECT * FROM users WHERE email = 'a@example.com';\n";
+        let result = engine().mask_sql(dump).unwrap();
+        assert_eq!(
+            result.masked,
+            "-- contact a@example.com\n\
+             CREATE TABLE users (email varchar(64) DEFAULT 'x@example.com');\n\
+             INSERT INTO `shop`.`users` (`id`, `email`, `note`) VALUES \
+             (1,'[EMAIL_1]','it''s [PHONE_1]'),(2,'[EMAIL_2]',NULL);\n\
+             SELECT * FROM users WHERE email = 'a@example.com';\n"
+        );
+        let fields: Vec<&str> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.field_name.as_str())
+            .collect();
+        assert_eq!(
+            fields,
+            ["shop.users.email", "shop.users.note", "shop.users.email"]
+        );
+    }
+
+    #[test]
+    fn test_sql_literals_without_column_list() {
+        let sql = "INSERT INTO t VALUES ('O\\'Brien; a@example.com', 'done');";
+        let literals = sql_literals(sql);
+        assert_eq!(literals.len(), 2);
+        assert_eq!(literals[0].field, "t.1");
+        assert!(literals[0].backslash_quotes);
+        assert_eq!(literals[1].field, "t.2");
+
+        let findings = engine().detect_pii_sql(sql).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].field_name, "t.1");
+        assert_eq!(findings[0].start, "O'Brien; ".len());
+    }
+}
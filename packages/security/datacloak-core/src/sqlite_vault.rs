@@ -0,0 +1,128 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::DataCloakError;
+use crate::tokenization::{new_token, TokenVault};
+
+/// A `TokenVault` persisted in an SQLCipher-encrypted SQLite database, so
+/// tokenization mappings survive restarts and can be shared between runs.
+pub struct SqliteTokenVault {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteTokenVault {
+    /// Opens (or creates) the vault at `path`, unlocking it with `key`.
+    pub fn open<P: AsRef<Path>>(path: P, key: &str) -> Result<Self, DataCloakError> {
+        let conn = Connection::open(path)
+            .map_err(|e| DataCloakError::Vault(format!("Failed to open token vault: {}", e)))?;
+        Self::init(conn, key)
+    }
+
+    /// Opens a throwaway vault that lives only as long as this value.
+    pub fn open_in_memory(key: &str) -> Result<Self, DataCloakError> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| DataCloakError::Vault(format!("Failed to open token vault: {}", e)))?;
+        Self::init(conn, key)
+    }
+
+    fn init(conn: Connection, key: &str) -> Result<Self, DataCloakError> {
+        if key.is_empty() {
+            return Err(DataCloakError::InvalidConfig(
+                "Token vault requires a non-empty encryption key".to_string(),
+            ));
+        }
+
+        conn.pragma_update(None, "key", key)
+            .map_err(|e| DataCloakError::Vault(format!("Failed to set token vault key: {}", e)))?;
+
+        // SQLCipher only reports a wrong key on first read of the database
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map_err(|e| DataCloakError::Vault(format!("Failed to unlock token vault: {}", e)))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tokens (
+                token TEXT PRIMARY KEY,
+                pii_type TEXT NOT NULL,
+                value TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                UNIQUE (pii_type, value)
+            );",
+        )
+        .map_err(|e| DataCloakError::Vault(format!("Failed to initialize token vault: {}", e)))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl TokenVault for SqliteTokenVault {
+    fn tokenize(&self, pii_type: &str, value: &str) -> Result<String, DataCloakError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Token vault"))?;
+
+        let existing: Option<String> = conn
+            .query_row(
+                "SELECT token FROM tokens WHERE pii_type = ?1 AND value = ?2",
+                params![pii_type, value],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| DataCloakError::Vault(format!("Token vault lookup failed: {}", e)))?;
+        if let Some(token) = existing {
+            return Ok(token);
+        }
+
+        let token = new_token(pii_type);
+        conn.execute(
+            "INSERT INTO tokens (token, pii_type, value, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![token, pii_type, value, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| DataCloakError::Vault(format!("Token vault insert failed: {}", e)))?;
+
+        Ok(token)
+    }
+
+    fn detokenize(&self, token: &str) -> Result<Option<String>, DataCloakError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Token vault"))?;
+
+        conn.query_row(
+            "SELECT value FROM tokens WHERE token = ?1",
+            params![token],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| DataCloakError::Vault(format!("Token vault lookup failed: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlite_vault_round_trip() {
+        let vault = SqliteTokenVault::open_in_memory("vault-key").unwrap();
+
+        let token = vault.tokenize("ssn", "123-45-6789").unwrap();
+        assert_eq!(vault.tokenize("ssn", "123-45-6789").unwrap(), token);
+        assert_eq!(
+            vault.detokenize(&token).unwrap().as_deref(),
+            Some("123-45-6789")
+        );
+    }
+
+    #[test]
+    fn test_sqlite_vault_rejects_empty_key() {
+        assert!(SqliteTokenVault::open_in_memory("").is_err());
+    }
+}
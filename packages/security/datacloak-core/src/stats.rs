@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MaskingResult, PIIDetectionResult};
+
+/// Accumulates findings across many scans, e.g. every chunk of every file in
+/// a batch job, so totals per type and per source can be reported at the end.
+///
+/// `ScanStats` is `Sync`: share it behind an `Arc` and record from any
+/// thread. Recording takes a short lock per call.
+#[derive(Debug, Default)]
+pub struct ScanStats {
+    totals: Mutex<StatsSnapshot>,
+}
+
+/// A point-in-time copy of a `ScanStats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    /// Totals over every recorded scan.
+    pub overall: SourceStats,
+    /// Totals per source label passed to `record_*`, such as a file name.
+    pub by_source: HashMap<String, SourceStats>,
+}
+
+/// Totals for one source, or for all of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStats {
+    pub scans: u64,
+    pub bytes_scanned: u64,
+    pub findings: u64,
+    pub counts_by_type: HashMap<String, u64>,
+}
+
+impl SourceStats {
+    fn add(&mut self, bytes: usize, findings: &[PIIDetectionResult]) {
+        self.scans += 1;
+        self.bytes_scanned += bytes as u64;
+        self.findings += findings.len() as u64;
+        for pii in findings {
+            *self.counts_by_type.entry(pii.pii_type.clone()).or_insert(0) += 1;
+        }
+    }
+}
+
+impl ScanStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the findings of a `detect_pii` call over `bytes` bytes of
+    /// input from `source`.
+    pub fn record_detections(&self, source: &str, bytes: usize, findings: &[PIIDetectionResult]) {
+        let mut totals = self.lock();
+        totals.overall.add(bytes, findings);
+        totals
+            .by_source
+            .entry(source.to_string())
+            .or_default()
+            .add(bytes, findings);
+    }
+
+    /// Records the findings of a `mask_text` call on input from `source`.
+    pub fn record_masking(&self, source: &str, result: &MaskingResult) {
+        self.record_detections(source, result.original_text.len(), &result.detected_pii);
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        self.lock().clone()
+    }
+
+    /// Returns the totals so far and starts again from zero.
+    pub fn take(&self) -> StatsSnapshot {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, StatsSnapshot> {
+        // Counters stay consistent even if a recording thread panicked
+        self.totals
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, DataCloakEngine};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_stats_aggregate_per_source_across_threads() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let stats = Arc::new(ScanStats::new());
+
+        let handles: Vec<_> = ["a.csv", "b.csv"]
+            .into_iter()
+            .map(|source| {
+                let (engine, stats) = (engine.clone(), stats.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..3 {
+                        let text = "mail x@example.com or call 555-123-4567";
+                        let findings = engine.detect_pii(text).unwrap();
+                        stats.record_detections(source, text.len(), &findings);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        stats.record_masking("c.txt", &engine.mask_text("x@example.com").unwrap());
+
+        let snapshot = stats.take();
+        assert_eq!(snapshot.overall.scans, 7);
+        assert_eq!(snapshot.overall.counts_by_type["email"], 7);
+        assert_eq!(snapshot.by_source["a.csv"].counts_by_type["phone"], 3);
+        assert_eq!(snapshot.by_source["c.txt"].findings, 1);
+        assert_eq!(stats.snapshot(), StatsSnapshot::default());
+    }
+}
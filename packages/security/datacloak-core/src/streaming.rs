@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compression::{Compression, Decoder, Encoder};
+use crate::encoding::decode_text;
+use crate::{CancellationToken, DataCloakEngine, DataCloakError, PIIDetectionResult};
+
+/// Bytes pulled from a reader per read by `detect_stream`.
+const READ_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Running totals reported to `StreamOptions::on_progress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanProgress {
+    pub bytes_processed: usize,
+    /// Size of the input when known up front, e.g. for `scan_file`.
+    pub total_bytes: Option<usize>,
+    pub findings: usize,
+}
+
+/// Totals from `mask_stream`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamMaskingReport {
+    pub lines: u64,
+    /// Bytes of input read, after decompression.
+    pub bytes_processed: u64,
+    pub counts_by_type: HashMap<String, u64>,
+    /// Set when any line hit a match limit or the memory budget.
+    pub limits_exceeded: bool,
+    /// Lines that weren't valid UTF-8 and were decoded as `decode_text`
+    /// decodes them instead.
+    pub warnings: Vec<String>,
+}
+
+/// Optional hooks for `detect_stream_with_options` and
+/// `scan_file_with_options`.
+#[derive(Default)]
+pub struct StreamOptions<'a> {
+    /// Called after every chunk read and once more when the scan completes.
+    pub on_progress: Option<&'a mut dyn FnMut(&ScanProgress)>,
+    /// Aborts the scan between chunks and pattern passes once cancelled.
+    pub cancel: Option<CancellationToken>,
+}
+
+/// Scans text delivered in chunks, reporting every finding exactly once with
+/// byte offsets relative to the start of the stream.
+///
+/// Only the unscanned tail of the stream is buffered, so memory stays bounded
+/// by the chunk size as long as the text contains whitespace. A run of
+/// non-whitespace longer than `max_text_length` fails with `TextTooLarge`.
+///
+/// The last `max_match_len` bytes before each scan's end are scanned again
+/// with the next chunk, so values up to that long are reported whole
+/// wherever the chunks split them.
+#[derive(Debug)]
+pub struct StreamScanner<'e> {
+    engine: &'e DataCloakEngine,
+    buffer: String,
+    /// Stream offset of `buffer[0]`.
+    buffer_offset: usize,
+    /// Findings ending at or before this stream offset were already reported.
+    reported_through: usize,
+    /// The start of a multi-byte character split across `feed_bytes` calls.
+    pending: Vec<u8>,
+    cancel: Option<CancellationToken>,
+}
+
+impl<'e> StreamScanner<'e> {
+    pub fn new(engine: &'e DataCloakEngine) -> Self {
+        Self {
+            engine,
+            buffer: String::new(),
+            buffer_offset: 0,
+            reported_through: 0,
+            pending: Vec::new(),
+            cancel: None,
+        }
+    }
+
+    /// Makes `feed` and `finish` fail with `Cancelled` once `token` is
+    /// cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Appends `chunk` and returns the findings that can no longer be
+    /// affected by text still to come.
+    pub fn feed(&mut self, chunk: &str) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        self.buffer.push_str(chunk);
+        let carry = self.engine.config.max_match_len;
+        if self.buffer.len() < 2 * carry {
+            return Ok(Vec::new());
+        }
+
+        let mut cut = self.buffer.len() - carry;
+        while !self.buffer.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        self.scan(cut)
+    }
+
+    /// `feed` for UTF-8 bytes split anywhere, even inside a character. An
+    /// incomplete trailing character is held back until the next chunk
+    /// completes it.
+    pub fn feed_bytes(&mut self, chunk: &[u8]) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(chunk);
+        let valid = match std::str::from_utf8(&bytes) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(invalid_utf8(self.stream_len() + e.valid_up_to())),
+        };
+        let text = std::str::from_utf8(&bytes[..valid]).expect("validated above");
+        let findings = self.feed(text)?;
+        bytes.drain(..valid);
+        self.pending = bytes;
+        Ok(findings)
+    }
+
+    /// Scans whatever is still buffered and returns the remaining findings.
+    /// Fails if `feed_bytes` was left holding part of a character.
+    pub fn finish(mut self) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        if !self.pending.is_empty() {
+            return Err(invalid_utf8(self.stream_len()));
+        }
+        let end = self.buffer.len();
+        self.scan(end)
+    }
+
+    /// Bytes of text fed so far, not counting held-back `pending` bytes.
+    fn stream_len(&self) -> usize {
+        self.buffer_offset + self.buffer.len()
+    }
+
+    /// Reports findings ending at or before `cut` and drops the buffered text
+    /// that no unreported finding can start in.
+    fn scan(&mut self, cut: usize) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "datacloak.chunk",
+            offset = self.buffer_offset,
+            bytes = self.buffer.len()
+        )
+        .entered();
+        let detected = self
+            .engine
+            .detect_pii_cancellable(&self.buffer, self.cancel.as_ref())?
+            .results;
+
+        let mut keep = cut;
+        let mut ready = Vec::new();
+        for mut pii in detected {
+            if pii.end > cut {
+                keep = keep.min(pii.start);
+                continue;
+            }
+            pii.start += self.buffer_offset;
+            pii.end += self.buffer_offset;
+            if pii.end > self.reported_through {
+                ready.push(pii);
+            }
+        }
+        ready.sort_by_key(|pii| (pii.start, pii.end));
+        self.reported_through = self.buffer_offset + cut;
+
+        // Restart at a whitespace boundary so word-boundary anchors see the
+        // same context on the next scan as they would on the whole text
+        let keep = self.buffer[..keep]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        self.buffer.drain(..keep);
+        self.buffer_offset += keep;
+
+        Ok(ready)
+    }
+}
+
+impl DataCloakEngine {
+    /// Detects PII in everything `reader` yields, holding only a bounded
+    /// window of the input in memory, so inputs far beyond `max_text_length`
+    /// can be scanned. Offsets are bytes from the start of the stream. The
+    /// input must be UTF-8, or gzip or zstd compressed UTF-8 when the
+    /// matching feature is enabled; compression is detected automatically
+    /// and offsets refer to the decompressed text.
+    pub fn detect_stream<R: Read>(
+        &self,
+        reader: R,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        self.detect_stream_with_options(reader, StreamOptions::default())
+    }
+
+    /// `detect_stream` with progress reporting.
+    pub fn detect_stream_with_options<R: Read>(
+        &self,
+        reader: R,
+        options: StreamOptions<'_>,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        self.scan_reader(reader, None, options)
+    }
+
+    /// Masks `reader` line by line into `writer`, compressing the output as
+    /// `output` asks. Like `detect_stream`, gzip and zstd input is
+    /// decompressed transparently, so archived logs can be masked without
+    /// unpacking them first. Line endings are kept, and each line must fit
+    /// within `max_text_length`. A line that isn't valid UTF-8 is decoded
+    /// like `decode_text` input and noted in `warnings` rather than ending
+    /// the run.
+    pub fn mask_stream<R: Read, W: Write>(
+        &self,
+        reader: R,
+        writer: W,
+        output: Compression,
+    ) -> Result<StreamMaskingReport, DataCloakError> {
+        let io_error = |e: std::io::Error| DataCloakError::Io(e.to_string());
+        let mut reader = BufReader::new(Decoder::new(BufReader::new(reader))?);
+        let mut writer = Encoder::new(writer, output)?;
+        let mut report = StreamMaskingReport::default();
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line).map_err(io_error)? == 0 {
+                break;
+            }
+            let (text, encoding) = decode_text(&line);
+            if std::str::from_utf8(&line).is_err() {
+                report.warnings.push(format!(
+                    "Line {} is not valid UTF-8; decoded as {:?}",
+                    report.lines + 1,
+                    encoding
+                ));
+            }
+            let content = text.trim_end_matches(['\n', '\r']);
+            let result = self.mask_text(content)?;
+            writer
+                .write_all(result.masked_text.as_bytes())
+                .and_then(|()| writer.write_all(text[content.len()..].as_bytes()))
+                .map_err(io_error)?;
+
+            report.lines += 1;
+            report.bytes_processed += line.len() as u64;
+            report.limits_exceeded |= result.metadata.limits_exceeded;
+            for pii in &result.detected_pii {
+                *report
+                    .counts_by_type
+                    .entry(pii.pii_type.clone())
+                    .or_insert(0) += 1;
+            }
+        }
+
+        writer.finish().map_err(io_error)?;
+        Ok(report)
+    }
+
+    fn scan_reader<R: Read>(
+        &self,
+        reader: R,
+        total_bytes: Option<usize>,
+        mut options: StreamOptions<'_>,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut reader = Decoder::new(BufReader::new(reader))?;
+        let mut report = |consumed: usize, findings: usize| {
+            if let Some(on_progress) = options.on_progress.as_mut() {
+                on_progress(&ScanProgress {
+                    bytes_processed: consumed,
+                    total_bytes,
+                    findings,
+                });
+            }
+        };
+
+        // Keep each scan window well inside the configured limit
+        let chunk_size = READ_CHUNK_BYTES.min(self.config.max_text_length / 2).max(1);
+        let mut scanner = StreamScanner::new(self);
+        if let Some(cancel) = options.cancel.clone() {
+            scanner = scanner.with_cancellation(cancel);
+        }
+        let mut findings = Vec::new();
+        let mut bytes = vec![0; chunk_size];
+        let mut consumed = 0;
+
+        loop {
+            if let Some(cancel) = &options.cancel {
+                cancel.check()?;
+            }
+            let read = match reader.read(&mut bytes) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(DataCloakError::Io(e.to_string())),
+            };
+            // A multi-byte character split across reads waits for the rest
+            findings.extend(scanner.feed_bytes(&bytes[..read])?);
+            consumed += read;
+            report(consumed, findings.len());
+        }
+
+        findings.extend(scanner.finish()?);
+        report(consumed, findings.len());
+        Ok(findings)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl DataCloakEngine {
+    /// Memory-maps the file at `path` and scans it like `detect_stream`, so
+    /// multi-gigabyte exports are never read into memory as a whole. Offsets
+    /// are byte offsets into the file, or into its decompressed content for
+    /// gzip and zstd files.
+    pub fn scan_file<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        self.scan_file_with_options(path, StreamOptions::default())
+    }
+
+    /// `scan_file` with progress reporting; progress includes the file size
+    /// unless the file is compressed.
+    pub fn scan_file_with_options<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        options: StreamOptions<'_>,
+    ) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let path = path.as_ref();
+        let io_error = |e: std::io::Error| DataCloakError::Io(format!("{}: {}", path.display(), e));
+
+        let file = std::fs::File::open(path).map_err(io_error)?;
+        // Mapping an empty file fails on some platforms
+        if file.metadata().map_err(io_error)?.len() == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Safety: the map is read-only and dropped before returning. Another
+        // process truncating the file meanwhile is outside our control, as
+        // with any mmap-based reader.
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+        let total_bytes = match Compression::detect(&map) {
+            Compression::None => Some(map.len()),
+            _ => None,
+        };
+        self.scan_reader(&map[..], total_bytes, options)
+    }
+}
+
+fn invalid_utf8(offset: usize) -> DataCloakError {
+    DataCloakError::Io(format!("Input is not valid UTF-8 at byte {}", offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_stream_reports_values_straddling_chunks_once() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let mut text = "filler words ".repeat(60);
+        text.push_str("reach jane.doe@example.com today ");
+        text.push_str(&"more filler ".repeat(60));
+        text.push_str("or call 555-123-4567");
+
+        let mut scanner = StreamScanner::new(&engine);
+        let mut findings = Vec::new();
+        for chunk in text.as_bytes().chunks(100) {
+            findings.extend(scanner.feed(std::str::from_utf8(chunk).unwrap()).unwrap());
+        }
+        findings.extend(scanner.finish().unwrap());
+
+        let emails: Vec<_> = findings.iter().filter(|f| f.pii_type == "email").collect();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(
+            &text[emails[0].start..emails[0].end],
+            "jane.doe@example.com"
+        );
+        assert!(findings
+            .iter()
+            .any(|f| f.pii_type == "phone" && f.sample == "555-123-4567"));
+    }
+
+    #[test]
+    fn test_stream_carries_max_match_len() {
+        let config = DataCloakConfig::builder()
+            .custom_pattern("key_block", r"\bBEGIN(?: [a-z]{3}){200} END\b")
+            .max_match_len(1024)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        // Spaces let the scanner cut inside the value
+        let key = format!("BEGIN{} END", " abc".repeat(200));
+        let text = format!("{}{} {}", "filler ".repeat(100), key, "tail ".repeat(100));
+
+        let mut scanner = StreamScanner::new(&engine);
+        let mut findings = Vec::new();
+        for chunk in text.as_bytes().chunks(100) {
+            findings.extend(scanner.feed(std::str::from_utf8(chunk).unwrap()).unwrap());
+        }
+        findings.extend(scanner.finish().unwrap());
+
+        let keys: Vec<_> = findings
+            .iter()
+            .filter(|f| f.pii_type == "key_block")
+            .collect();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(&text[keys[0].start..keys[0].end], key);
+    }
+
+    #[test]
+    fn test_detect_stream_beyond_max_text_length() {
+        let config = DataCloakConfig::builder()
+            .max_text_length(1_000)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+
+        let mut text = "caf\u{e9} notes ".repeat(300);
+        let offset = text.len();
+        text.push_str("ssn 123-45-6789 ");
+        text.push_str(&"r\u{e9}sum\u{e9} ".repeat(300));
+        assert!(engine.detect_pii(&text).is_err());
+
+        let findings = engine.detect_stream(text.as_bytes()).unwrap();
+        let ssn: Vec<_> = findings.iter().filter(|f| f.pii_type == "ssn").collect();
+        assert_eq!(ssn.len(), 1);
+        assert_eq!(ssn[0].start, offset + 4);
+
+        assert!(engine.detect_stream(&b"ok \xff"[..]).is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_scan_file_reports_file_offsets() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let path = std::env::temp_dir().join(format!("datacloak-scan-{}.csv", std::process::id()));
+        let mut csv = "id,note\n".to_string();
+        for i in 0..20_000 {
+            csv.push_str(&format!("{},nothing to see\n", i));
+        }
+        let offset = csv.len() + 2;
+        csv.push_str("0,jane@example.com\n");
+        std::fs::write(&path, &csv).unwrap();
+
+        let findings = engine.scan_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].start, offset);
+        assert!(engine.scan_file("/nonexistent/export.csv").is_err());
+    }
+
+    #[test]
+    fn test_detect_stream_reports_progress() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let text = format!("{} jane@example.com", "lorem ipsum ".repeat(20_000));
+
+        let mut updates = Vec::new();
+        let mut on_progress = |progress: &ScanProgress| updates.push(*progress);
+        let findings = engine
+            .detect_stream_with_options(
+                text.as_bytes(),
+                StreamOptions {
+                    on_progress: Some(&mut on_progress),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert!(updates.len() > 2);
+        assert!(updates
+            .windows(2)
+            .all(|w| w[0].bytes_processed <= w[1].bytes_processed));
+        let last = updates.last().unwrap();
+        assert_eq!(last.bytes_processed, text.len());
+        assert_eq!(last.findings, findings.len());
+    }
+
+    #[test]
+    fn test_mask_stream_keeps_line_endings() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let input = "a jane@example.com\r\n\nssn 123-45-6789";
+        let mut output = Vec::new();
+        let report = engine
+            .mask_stream(input.as_bytes(), &mut output, Compression::None)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "a j***@example.com\r\n\nssn ***-**-6789"
+        );
+        assert_eq!(report.lines, 3);
+        assert_eq!(report.bytes_processed, input.len() as u64);
+        assert_eq!(report.counts_by_type["email"], 1);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_mask_stream_decodes_invalid_lines_lossily() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let input = b"Jos\xe9 jane@example.com\nssn 123-45-6789\n";
+        let mut output = Vec::new();
+        let report = engine
+            .mask_stream(&input[..], &mut output, Compression::None)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Jos\u{e9} j***@example.com\nssn ***-**-6789\n"
+        );
+        assert_eq!(report.lines, 2);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].starts_with("Line 1 is not valid UTF-8"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_input_is_decompressed_and_output_recompressed() {
+        use flate2::read::MultiGzDecoder;
+        use flate2::write::GzEncoder;
+
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"login jane@example.com\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let findings = engine.detect_stream(&compressed[..]).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].start, "login ".len());
+
+        let mut output = Vec::new();
+        engine
+            .mask_stream(&compressed[..], &mut output, Compression::Gzip)
+            .unwrap();
+        let mut masked = String::new();
+        MultiGzDecoder::new(&output[..])
+            .read_to_string(&mut masked)
+            .unwrap();
+        assert_eq!(masked, "login j***@example.com\n");
+    }
+
+    #[test]
+    fn test_cancelled_scans_stop() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let cancel = CancellationToken::new();
+        let text = "jane@example.com ".repeat(1_000);
+
+        let mut cancel_after_first_chunk = {
+            let cancel = cancel.clone();
+            move |_: &ScanProgress| cancel.cancel()
+        };
+        let result = engine.detect_stream_with_options(
+            text.as_bytes(),
+            StreamOptions {
+                on_progress: Some(&mut cancel_after_first_chunk),
+                cancel: Some(cancel.clone()),
+            },
+        );
+        assert_eq!(result.unwrap_err(), DataCloakError::Cancelled);
+
+        let options = crate::DetectionOptions {
+            cancel: Some(cancel),
+            ..Default::default()
+        };
+        assert_eq!(
+            engine
+                .detect_pii_with_options("a@b.com", &options)
+                .unwrap_err(),
+            DataCloakError::Cancelled
+        );
+    }
+}
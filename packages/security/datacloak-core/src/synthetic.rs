@@ -0,0 +1,158 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const FIRST_NAMES: &[&str] = &[
+    "alex", "jordan", "taylor", "morgan", "casey", "riley", "jamie", "avery", "quinn", "harper",
+    "rowan", "emerson", "parker", "sage", "drew", "reese",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "smith", "johnson", "lee", "garcia", "brown", "miller", "davis", "wilson", "moore", "clark",
+    "lewis", "walker", "young", "hall", "allen", "king",
+];
+
+const DOMAINS: &[&str] = &["example.com", "example.net", "example.org"];
+
+/// Deterministic byte stream derived from the seed and the original value, so
+/// the same input always synthesizes the same replacement.
+struct ByteStream {
+    bytes: Vec<u8>,
+    pos: usize,
+}
+
+impl ByteStream {
+    fn new(seed: &[u8], pii_type: &str, value: &str) -> Self {
+        let mut bytes = Vec::with_capacity(64);
+        for block in 0u8..2 {
+            let mut mac =
+                Hmac::<Sha256>::new_from_slice(seed).expect("HMAC accepts keys of any length");
+            mac.update(&[block]);
+            mac.update(pii_type.as_bytes());
+            mac.update(b":");
+            mac.update(value.as_bytes());
+            bytes.extend(mac.finalize().into_bytes());
+        }
+        Self { bytes, pos: 0 }
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        let b = self.bytes[self.pos % self.bytes.len()];
+        self.pos += 1;
+        b
+    }
+
+    fn digit(&mut self) -> u32 {
+        (self.next_byte() % 10) as u32
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.next_byte() as usize % items.len()]
+    }
+}
+
+/// Produces a realistic but fake replacement for `value`. Formatting such as
+/// separators and length is kept wherever the type allows it.
+pub(crate) fn synthesize(value: &str, pii_type: &str, seed: &[u8]) -> String {
+    let mut stream = ByteStream::new(seed, pii_type, value);
+
+    match pii_type {
+        "email" => format!(
+            "{}.{}{}@{}",
+            stream.pick(FIRST_NAMES),
+            stream.pick(LAST_NAMES),
+            stream.next_byte() % 100,
+            stream.pick(DOMAINS)
+        ),
+        "phone" => {
+            // 555-0100 through 555-0199 are reserved for fictional use
+            let mut digits = vec![2 + stream.digit() % 8, stream.digit(), stream.digit()];
+            digits.extend([5, 5, 5, 0, 1, stream.digit(), stream.digit()]);
+            replace_digits(value, &digits)
+        }
+        "ssn" => {
+            // Area numbers 900-999 are never issued, so the result can't be a real SSN
+            let mut digits = vec![9];
+            while digits.len() < 9 {
+                digits.push(stream.digit());
+            }
+            if digits[3..5] == [0, 0] {
+                digits[4] = 1;
+            }
+            replace_digits(value, &digits)
+        }
+        "credit_card" => {
+            let len = value.chars().filter(|c| c.is_ascii_digit()).count();
+            let mut digits = vec![4];
+            while digits.len() + 1 < len {
+                digits.push(stream.digit());
+            }
+            digits.push(luhn_check_digit(&digits));
+            replace_digits(value, &digits)
+        }
+        _ => "***".to_string(),
+    }
+}
+
+fn replace_digits(template: &str, digits: &[u32]) -> String {
+    let mut digits = digits.iter();
+    template
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                digits
+                    .next()
+                    .and_then(|&d| char::from_digit(d, 10))
+                    .unwrap_or(c)
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn luhn_check_digit(payload: &[u32]) -> u32 {
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 0 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_card_is_luhn_valid_and_stable() {
+        let card = "4532-0151-1283-0366";
+        let fake = synthesize(card, "credit_card", b"seed");
+
+        assert_eq!(fake.len(), card.len());
+        assert_ne!(fake, card);
+        assert_eq!(fake, synthesize(card, "credit_card", b"seed"));
+
+        let digits: Vec<u32> = fake.chars().filter_map(|c| c.to_digit(10)).collect();
+        let (payload, check) = digits.split_at(digits.len() - 1);
+        assert_eq!(luhn_check_digit(payload), check[0]);
+    }
+
+    #[test]
+    fn test_synthetic_email_is_plausible() {
+        let fake = synthesize("jane.doe@corp.com", "email", b"seed");
+        assert!(fake.contains('@'));
+        assert!(fake.ends_with(".com") || fake.ends_with(".net") || fake.ends_with(".org"));
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DataCloakEngine, DataCloakError, FieldContext};
+
+/// Dialect of a delimited file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    /// Treat the first record as column names: passed through unmasked,
+    /// used to label findings and passed as each column's `FieldContext`.
+    pub has_headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_headers: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Tab-separated values with a header row.
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::default()
+        }
+    }
+}
+
+/// Totals from `mask_csv`. Columns are labelled by header name, or by
+/// zero-based index for files without headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CsvMaskingReport {
+    /// Data records processed, excluding the header.
+    pub rows: u64,
+    /// Cells in which at least one value was masked.
+    pub cells_masked: u64,
+    pub counts_by_type: HashMap<String, u64>,
+    pub counts_by_column: HashMap<String, u64>,
+    /// Set when any cell hit a match limit or the memory budget.
+    pub limits_exceeded: bool,
+}
+
+/// Classification of one column from `profile_csv`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    pub index: usize,
+    /// Header name, or the index as a string for files without headers.
+    pub name: String,
+    /// Non-empty cells examined.
+    pub cells_sampled: u64,
+    /// Sampled cells containing at least one finding of each type.
+    pub counts_by_type: HashMap<String, u64>,
+    /// The most frequent type, if any cell held PII.
+    pub pii_type: Option<String>,
+    /// Share of sampled cells containing `pii_type`, from 0.0 to 1.0.
+    pub confidence: f64,
+}
+
+impl ColumnProfile {
+    fn new(index: usize, name: String) -> Self {
+        Self {
+            index,
+            name,
+            cells_sampled: 0,
+            counts_by_type: HashMap::new(),
+            pii_type: None,
+            confidence: 0.0,
+        }
+    }
+}
+
+impl DataCloakEngine {
+    /// Masks every cell of the delimited file read from `input` and writes
+    /// the result to `output` one record at a time, so masks containing the
+    /// delimiter or quotes can never shift columns. Output fields are quoted
+    /// where their content requires it; rows keep their original field
+    /// counts. Each cell must fit within `max_text_length`. Cells are masked
+    /// with `mask_text_with_context` under their column's header, so an
+    /// `ssn` column of undashed numbers is masked and an `order_id` column
+    /// is not read as phone numbers.
+    pub fn mask_csv<R: Read, W: Write>(
+        &self,
+        input: R,
+        output: W,
+        options: &CsvOptions,
+    ) -> Result<CsvMaskingReport, DataCloakError> {
+        let mut reader = csv_reader(input, options);
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .quote_style(csv::QuoteStyle::Necessary)
+            .flexible(true)
+            .from_writer(output);
+
+        let headers: Vec<String> = if options.has_headers {
+            let headers = reader.headers().map_err(csv_error)?.clone();
+            writer.write_record(&headers).map_err(csv_error)?;
+            headers.iter().map(str::to_string).collect()
+        } else {
+            Vec::new()
+        };
+        let column_label = |index: usize| {
+            headers
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| index.to_string())
+        };
+        let contexts: Vec<FieldContext> = headers
+            .iter()
+            .map(|header| FieldContext::named(header))
+            .collect();
+        let no_context = FieldContext::default();
+
+        let mut report = CsvMaskingReport::default();
+        let mut masked = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(csv_error)?;
+            masked.clear();
+            for (index, cell) in record.iter().enumerate() {
+                let context = contexts.get(index).unwrap_or(&no_context);
+                let result = self.mask_text_with_context(cell, context)?;
+                if !result.detected_pii.is_empty() {
+                    report.cells_masked += 1;
+                    *report
+                        .counts_by_column
+                        .entry(column_label(index))
+                        .or_insert(0) += result.detected_pii.len() as u64;
+                    for pii in &result.detected_pii {
+                        *report
+                            .counts_by_type
+                            .entry(pii.pii_type.clone())
+                            .or_insert(0) += 1;
+                    }
+                }
+                report.limits_exceeded |= result.metadata.limits_exceeded;
+                masked.push(result.masked_text);
+            }
+            writer.write_record(&masked).map_err(csv_error)?;
+            report.rows += 1;
+        }
+
+        writer
+            .flush()
+            .map_err(|e| DataCloakError::Io(e.to_string()))?;
+        Ok(report)
+    }
+}
+
+impl DataCloakEngine {
+    /// Classifies each column from at most `sample_rows` data records,
+    /// without masking anything. A column's type is the one detected in the
+    /// most non-empty cells, and its confidence is the share of non-empty
+    /// sampled cells containing that type, so a notes column with the odd
+    /// email in it reports `email` at a low confidence.
+    pub fn profile_csv<R: Read>(
+        &self,
+        input: R,
+        options: &CsvOptions,
+        sample_rows: usize,
+    ) -> Result<Vec<ColumnProfile>, DataCloakError> {
+        let mut reader = csv_reader(input, options);
+        let mut profiles: Vec<ColumnProfile> = Vec::new();
+        if options.has_headers {
+            for (index, name) in reader.headers().map_err(csv_error)?.iter().enumerate() {
+                profiles.push(ColumnProfile::new(index, name.to_string()));
+            }
+        }
+
+        for record in reader.records().take(sample_rows) {
+            let record = record.map_err(csv_error)?;
+            for (index, cell) in record.iter().enumerate() {
+                while profiles.len() <= index {
+                    profiles.push(ColumnProfile::new(
+                        profiles.len(),
+                        profiles.len().to_string(),
+                    ));
+                }
+                if cell.trim().is_empty() {
+                    continue;
+                }
+                let profile = &mut profiles[index];
+                profile.cells_sampled += 1;
+                let context = FieldContext::named(&profile.name);
+                let mut types: Vec<String> = self
+                    .detect_pii_with_context(cell, &context)?
+                    .into_iter()
+                    .map(|pii| pii.pii_type)
+                    .collect();
+                types.sort();
+                types.dedup();
+                for pii_type in types {
+                    *profile.counts_by_type.entry(pii_type).or_insert(0) += 1;
+                }
+            }
+        }
+
+        for profile in &mut profiles {
+            let dominant = profile
+                .counts_by_type
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(pii_type, &count)| (pii_type.clone(), count));
+            if let Some((pii_type, count)) = dominant {
+                profile.confidence = count as f64 / profile.cells_sampled as f64;
+                profile.pii_type = Some(pii_type);
+            }
+        }
+        Ok(profiles)
+    }
+}
+
+fn csv_reader<R: Read>(input: R, options: &CsvOptions) -> csv::Reader<R> {
+    csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers)
+        .flexible(true)
+        .from_reader(input)
+}
+
+fn csv_error(e: csv::Error) -> DataCloakError {
+    DataCloakError::Io(format!("CSV: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCloakConfig, MaskingStrategy};
+
+    #[test]
+    fn test_mask_csv_keeps_columns_intact() {
+        let config = DataCloakConfig::builder()
+            .mask_template("email", "{first1}***, redacted")
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let input = "name,contact,note\nJane,jane@example.com,\"says \"\"hi\"\"\"\nBob,none,ok\n";
+
+        let mut output = Vec::new();
+        let report = engine
+            .mask_csv(input.as_bytes(), &mut output, &CsvOptions::default())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "name,contact,note\nJane,\"j***, redacted\",\"says \"\"hi\"\"\"\nBob,none,ok\n"
+        );
+        assert_eq!(report.rows, 2);
+        assert_eq!(report.counts_by_column["contact"], 1);
+    }
+
+    #[test]
+    fn test_mask_csv_uses_headers_as_context() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let input = "ssn,order_id,notes\n123456789,5551234567,call 5551234567\n";
+
+        let mut output = Vec::new();
+        engine
+            .mask_csv(input.as_bytes(), &mut output, &CsvOptions::default())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "ssn,order_id,notes\n***-**-6789,5551234567,call ***-***-4567\n"
+        );
+    }
+
+    #[test]
+    fn test_mask_tsv_without_headers() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let options = CsvOptions {
+            has_headers: false,
+            ..CsvOptions::tsv()
+        };
+
+        let mut output = Vec::new();
+        let report = engine
+            .mask_csv(
+                "a@example.com\tx\nb\ta@example.com\n".as_bytes(),
+                &mut output,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "[EMAIL_1]\tx\nb\t[EMAIL_1]\n"
+        );
+        assert_eq!(report.counts_by_column["0"], 1);
+        assert_eq!(report.counts_by_column["1"], 1);
+    }
+
+    #[test]
+    fn test_profile_csv_classifies_columns() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let input = "id,email,ssn\n\
+                     1,a@example.com,123-45-6789\n\
+                     2,b@example.com,\n\
+                     3,not given,234-56-7890\n\
+                     4,c@example.com,345-67-8901\n\
+                     5,d@example.com,456-78-9012\n";
+
+        let profiles = engine
+            .profile_csv(input.as_bytes(), &CsvOptions::default(), 4)
+            .unwrap();
+        assert_eq!(profiles.len(), 3);
+        assert_eq!(profiles[0].pii_type, None);
+        assert_eq!(profiles[1].name, "email");
+        assert_eq!(profiles[1].pii_type.as_deref(), Some("email"));
+        assert_eq!(profiles[1].cells_sampled, 4);
+        assert!((profiles[1].confidence - 0.75).abs() < 1e-9);
+        assert_eq!(profiles[2].pii_type.as_deref(), Some("ssn"));
+        assert_eq!(profiles[2].cells_sampled, 3);
+        assert!((profiles[2].confidence - 1.0).abs() < 1e-9);
+    }
+}
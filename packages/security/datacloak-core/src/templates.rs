@@ -0,0 +1,146 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::error::DataCloakError;
+
+/// A parsed mask template such as `{first1}***@{domain}` or `XXX-XX-{last4}`.
+///
+/// Supported placeholders:
+/// - `{firstN}` / `{lastN}`: the first or last N characters of the value,
+///   counted as grapheme clusters so accents and emoji stay whole
+/// - `{domain}`: the part of the value after the last `@` (empty if none)
+/// - `{type}`: the PII type name
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MaskTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    First(usize),
+    Last(usize),
+    Domain,
+    Type,
+}
+
+impl MaskTemplate {
+    pub(crate) fn parse(template: &str) -> Result<Self, DataCloakError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '{' {
+                literal.push(c);
+                continue;
+            }
+
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(ch) => name.push(ch),
+                    None => {
+                        return Err(DataCloakError::InvalidConfig(format!(
+                            "Unterminated placeholder in mask template '{}'",
+                            template
+                        )))
+                    }
+                }
+            }
+
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(Self::parse_placeholder(&name).ok_or_else(|| {
+                DataCloakError::InvalidConfig(format!(
+                    "Unknown placeholder '{{{}}}' in mask template '{}'",
+                    name, template
+                ))
+            })?);
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    fn parse_placeholder(name: &str) -> Option<Segment> {
+        match name {
+            "domain" => Some(Segment::Domain),
+            "type" => Some(Segment::Type),
+            _ => {
+                if let Some(n) = name.strip_prefix("first") {
+                    n.parse().ok().map(Segment::First)
+                } else if let Some(n) = name.strip_prefix("last") {
+                    n.parse().ok().map(Segment::Last)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub(crate) fn render(&self, value: &str, pii_type: &str) -> String {
+        let graphemes: Vec<&str> = value.graphemes(true).collect();
+        let mut out = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::First(n) => out.extend(graphemes.iter().take(*n).copied()),
+                Segment::Last(n) => {
+                    let skip = graphemes.len().saturating_sub(*n);
+                    out.extend(graphemes.iter().skip(skip).copied())
+                }
+                Segment::Domain => {
+                    if let Some(at) = value.rfind('@') {
+                        out.push_str(&value[at + 1..]);
+                    }
+                }
+                Segment::Type => out.push_str(pii_type),
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_templates() {
+        let email = MaskTemplate::parse("{first1}***@{domain}").unwrap();
+        assert_eq!(
+            email.render("jane@example.com", "email"),
+            "j***@example.com"
+        );
+
+        let ssn = MaskTemplate::parse("XXX-XX-{last4}").unwrap();
+        assert_eq!(ssn.render("123-45-6789", "ssn"), "XXX-XX-6789");
+
+        let typed = MaskTemplate::parse("[{type}]").unwrap();
+        assert_eq!(typed.render("555-123-4567", "phone"), "[phone]");
+    }
+
+    #[test]
+    fn test_render_keeps_graphemes_whole() {
+        // `é` as `e` plus a combining accent, and a two-codepoint flag
+        let template = MaskTemplate::parse("{first1}***{last1}").unwrap();
+        assert_eq!(
+            template.render("e\u{301}milie\u{1F1EB}\u{1F1F7}", "custom"),
+            "e\u{301}***\u{1F1EB}\u{1F1F7}"
+        );
+        assert_eq!(template.render("", "custom"), "***");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_placeholders() {
+        assert!(MaskTemplate::parse("{middle2}").is_err());
+        assert!(MaskTemplate::parse("{last4").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use uuid::Uuid;
+
+use crate::error::DataCloakError;
+
+/// Storage for the token→original mapping produced by tokenizing masks.
+///
+/// Implementations must hand out the same token for a value that was already
+/// tokenized, so repeated occurrences stay joinable on masked data.
+pub trait TokenVault: Send + Sync {
+    /// Returns the token for `value`, creating and storing one if needed.
+    fn tokenize(&self, pii_type: &str, value: &str) -> Result<String, DataCloakError>;
+
+    /// Looks up the original value behind `token`.
+    fn detokenize(&self, token: &str) -> Result<Option<String>, DataCloakError>;
+}
+
+pub(crate) fn new_token(pii_type: &str) -> String {
+    format!("tok_{}_{}", pii_type, Uuid::new_v4().simple())
+}
+
+#[derive(Debug, Default)]
+struct VaultEntries {
+    by_value: HashMap<(String, String), String>,
+    by_token: HashMap<String, String>,
+}
+
+/// A `TokenVault` that keeps its mapping in process memory.
+#[derive(Debug, Default)]
+pub struct InMemoryTokenVault {
+    entries: Mutex<VaultEntries>,
+}
+
+impl InMemoryTokenVault {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.by_token.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl TokenVault for InMemoryTokenVault {
+    fn tokenize(&self, pii_type: &str, value: &str) -> Result<String, DataCloakError> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Token vault"))?;
+
+        let key = (pii_type.to_string(), value.to_string());
+        if let Some(token) = entries.by_value.get(&key) {
+            return Ok(token.clone());
+        }
+
+        let token = new_token(pii_type);
+        entries.by_token.insert(token.clone(), value.to_string());
+        entries.by_value.insert(key, token.clone());
+        Ok(token)
+    }
+
+    fn detokenize(&self, token: &str) -> Result<Option<String>, DataCloakError> {
+        let entries = self
+            .entries
+            .lock()
+            .map_err(|_| DataCloakError::poisoned("Token vault"))?;
+        Ok(entries.by_token.get(token).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_vault_round_trip() {
+        let vault = InMemoryTokenVault::new();
+
+        let token = vault.tokenize("email", "jane@example.com").unwrap();
+        assert!(token.starts_with("tok_email_"));
+        assert_eq!(vault.tokenize("email", "jane@example.com").unwrap(), token);
+        assert_eq!(
+            vault.detokenize(&token).unwrap().as_deref(),
+            Some("jane@example.com")
+        );
+        assert_eq!(vault.detokenize("tok_email_unknown").unwrap(), None);
+        assert_eq!(vault.len(), 1);
+    }
+}
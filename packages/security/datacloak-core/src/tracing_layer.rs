@@ -0,0 +1,207 @@
+use std::cell::Cell;
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::DataCloakEngine;
+
+/// Written in place of a value the engine could not scan, e.g. one longer
+/// than `max_text_length`, so it can never reach the sink unmasked.
+const UNSCANNABLE: &str = "[unscannable]";
+
+thread_local! {
+    /// Set while this thread formats an event, so events the engine itself
+    /// emits during masking are not scrubbed recursively.
+    static SCRUBBING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A `tracing_subscriber::Layer` that writes each event as a log line with
+/// its message and every field value masked by the engine:
+///
+/// ```text
+/// 2024-01-15T10:23:45.123456+00:00  INFO app::auth: login by j***@example.com user=j***@example.com
+/// ```
+///
+/// Use it in place of `tracing_subscriber::fmt::layer()`. Lines go to
+/// stderr unless `with_writer` picks another `MakeWriter`. Events emitted
+/// by the engine while it masks a line are dropped.
+pub struct DataCloakLayer<W = fn() -> io::Stderr> {
+    engine: Arc<DataCloakEngine>,
+    make_writer: W,
+}
+
+impl DataCloakLayer {
+    pub fn new(engine: Arc<DataCloakEngine>) -> Self {
+        Self {
+            engine,
+            make_writer: io::stderr,
+        }
+    }
+}
+
+impl<W> DataCloakLayer<W> {
+    /// Sends scrubbed lines to `make_writer` instead of stderr.
+    pub fn with_writer<W2>(self, make_writer: W2) -> DataCloakLayer<W2>
+    where
+        W2: for<'w> MakeWriter<'w> + 'static,
+    {
+        DataCloakLayer {
+            engine: self.engine,
+            make_writer,
+        }
+    }
+
+    fn scrub(&self, value: &str) -> String {
+        match self.engine.mask_text(value) {
+            Ok(result) => result.masked_text,
+            Err(_) => UNSCANNABLE.to_string(),
+        }
+    }
+
+    /// The event as one newline-terminated line, with its values masked.
+    fn format(&self, event: &Event<'_>) -> String {
+        let mut fields = FieldCollector::default();
+        event.record(&mut fields);
+
+        let metadata = event.metadata();
+        let mut line = format!(
+            "{} {:>5} {}:",
+            chrono::Utc::now().to_rfc3339(),
+            metadata.level(),
+            metadata.target()
+        );
+        if let Some(message) = &fields.message {
+            line.push(' ');
+            line.push_str(&self.scrub(message));
+        }
+        for (name, value) in &fields.values {
+            let _ = write!(line, " {}={}", name, self.scrub(value));
+        }
+        line.push('\n');
+        line
+    }
+}
+
+impl<S, W> Layer<S> for DataCloakLayer<W>
+where
+    S: Subscriber,
+    W: for<'w> MakeWriter<'w> + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let Some(_guard) = ScrubGuard::enter() else {
+            return;
+        };
+        let line = self.format(event);
+        // A failing sink must not take the application down with it
+        let _ = self
+            .make_writer
+            .make_writer_for(event.metadata())
+            .write_all(line.as_bytes());
+    }
+}
+
+/// Marks this thread as formatting an event until dropped.
+struct ScrubGuard;
+
+impl ScrubGuard {
+    fn enter() -> Option<Self> {
+        (!SCRUBBING.with(|scrubbing| scrubbing.replace(true))).then_some(ScrubGuard)
+    }
+}
+
+impl Drop for ScrubGuard {
+    fn drop(&mut self) {
+        SCRUBBING.with(|scrubbing| scrubbing.set(false));
+    }
+}
+
+/// Event fields rendered as text, numbers included since a phone number or
+/// account ID logged as an integer is still personal data.
+#[derive(Default)]
+struct FieldCollector {
+    message: Option<String>,
+    values: Vec<(&'static str, String)>,
+}
+
+impl Visit for FieldCollector {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, format!("{:?}", value));
+    }
+}
+
+impl FieldCollector {
+    fn push(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = Some(value);
+        } else {
+            self.values.push((field.name(), value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_layer_masks_message_and_fields() {
+        let engine = Arc::new(DataCloakEngine::new(DataCloakConfig::default()).unwrap());
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(DataCloakLayer::new(engine).with_writer(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(
+                user = "jane@example.com",
+                phone = 5551234567u64,
+                attempts = 3,
+                "login by {}",
+                "jane@example.com"
+            );
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains(" INFO "));
+        assert!(output.contains("login by j***@example.com"));
+        assert!(output.contains("user=j***@example.com"));
+        assert!(output.contains("attempts=3"));
+        assert!(!output.contains("jane@"));
+        assert!(!output.contains("5551234567"));
+        assert!(output.ends_with('\n'));
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{splice_masks, DataCloakEngine, DataCloakError, MaskingStrategy, PIIDetectionResult};
+
+/// How much of each finding a caller gets to see. One detection pass can be
+/// rendered at every level, e.g. for analysts and auditors, without
+/// rescanning. Serialized in snake_case, e.g. `"partial"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskingView {
+    /// Every value redacted, with nothing revealed: `***-**-****`.
+    #[default]
+    Full,
+    /// Values masked as `mask_text` masks them, under the configured
+    /// `masking_strategy`, reveal lengths and templates. Placeholders are
+    /// numbered from the engine's registry, like `[EMAIL_1]`.
+    Partial,
+    /// Values left as they are.
+    None,
+}
+
+impl DataCloakEngine {
+    /// `text` with `findings`, the result of `detect_pii` on it, masked as
+    /// `view` shows them. Only the spans of `findings` change.
+    pub fn mask_with_view(
+        &self,
+        text: &str,
+        findings: &[PIIDetectionResult],
+        view: MaskingView,
+    ) -> Result<String, DataCloakError> {
+        let mut findings = findings.to_vec();
+        match view {
+            MaskingView::None => return Ok(text.to_string()),
+            MaskingView::Full => {
+                for pii in &mut findings {
+                    pii.masked = Self::reveal_mask(&pii.sample, &pii.pii_type, 0);
+                }
+            }
+            MaskingView::Partial => {
+                if matches!(self.config.masking_strategy, MaskingStrategy::Placeholder) {
+                    self.assign_placeholders(text, &mut findings)?;
+                }
+            }
+        }
+
+        Ok(splice_masks(text, &findings))
+    }
+
+    /// `mask_with_view` with the view `role_views` assigns to `role`, or
+    /// `MaskingView::Full` for roles it doesn't list.
+    pub fn mask_for_role(
+        &self,
+        text: &str,
+        findings: &[PIIDetectionResult],
+        role: &str,
+    ) -> Result<String, DataCloakError> {
+        let view = self
+            .config
+            .role_views
+            .get(role)
+            .copied()
+            .unwrap_or_default();
+        self.mask_with_view(text, findings, view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCloakConfig;
+
+    #[test]
+    fn test_views_render_one_detection_pass() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let text = "SSN 123-45-6789, mail jane@example.com";
+        let findings = engine.detect_pii(text).unwrap();
+
+        assert_eq!(
+            engine
+                .mask_with_view(text, &findings, MaskingView::Full)
+                .unwrap(),
+            "SSN ***-**-****, mail ***@domain.com"
+        );
+        assert_eq!(
+            engine
+                .mask_with_view(text, &findings, MaskingView::Partial)
+                .unwrap(),
+            engine.mask_text(text).unwrap().masked_text
+        );
+        assert_eq!(
+            engine
+                .mask_with_view(text, &findings, MaskingView::None)
+                .unwrap(),
+            text
+        );
+    }
+
+    #[test]
+    fn test_roles_pick_their_view() {
+        let config = DataCloakConfig::builder()
+            .role_view("analyst", MaskingView::Partial)
+            .role_view("auditor", MaskingView::None)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let text = "Call 555-123-4567";
+        let findings = engine.detect_pii(text).unwrap();
+
+        assert_eq!(
+            engine.mask_for_role(text, &findings, "analyst").unwrap(),
+            "Call ***-***-4567"
+        );
+        assert_eq!(
+            engine.mask_for_role(text, &findings, "auditor").unwrap(),
+            text
+        );
+        assert_eq!(
+            engine.mask_for_role(text, &findings, "intern").unwrap(),
+            "Call ***-***-****"
+        );
+    }
+
+    #[test]
+    fn test_partial_view_numbers_placeholders() {
+        let config = DataCloakConfig::builder()
+            .masking_strategy(MaskingStrategy::Placeholder)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let text = "Mail jane@example.com or john@example.com";
+        let findings = engine.detect_pii(text).unwrap();
+
+        let partial = engine
+            .mask_with_view(text, &findings, MaskingView::Partial)
+            .unwrap();
+        assert_eq!(partial, "Mail [EMAIL_1] or [EMAIL_2]");
+        assert_eq!(partial, engine.mask_text(text).unwrap().masked_text);
+    }
+
+    #[test]
+    fn test_views_mask_only_the_given_spans() {
+        let config = DataCloakConfig::builder()
+            .max_matches_per_type(1)
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let text = "jane@example.com, again jane@example.com";
+        let findings = engine.detect_pii(text).unwrap();
+        assert_eq!(findings.len(), 1);
+
+        assert_eq!(
+            engine
+                .mask_with_view(text, &findings, MaskingView::Full)
+                .unwrap(),
+            "***@domain.com, again jane@example.com"
+        );
+        assert_eq!(
+            engine
+                .mask_with_view(text, &findings, MaskingView::Partial)
+                .unwrap(),
+            "j***@example.com, again jane@example.com"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesCData, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+
+use crate::document::{FieldTotals, ValueVisitor};
+use crate::{DataCloakEngine, DataCloakError, DocumentMaskingResult, PIIDetectionResult};
+
+impl DataCloakEngine {
+    /// Detects PII in the text nodes, CDATA sections and attribute values of
+    /// an XML document. Each finding's `field_name` is the element path of
+    /// its value, such as `/order/customer/email` or `/order/customer/@id`,
+    /// with offsets relative to the unescaped value.
+    pub fn detect_pii_xml(&self, xml: &str) -> Result<Vec<PIIDetectionResult>, DataCloakError> {
+        let mut findings = Vec::new();
+        rewrite_xml(xml, &mut |path, text| {
+            findings.extend(self.detect_pii(text)?.into_iter().map(|mut pii| {
+                pii.field_name = path.to_string();
+                pii
+            }));
+            Ok(text.to_string())
+        })?;
+        Ok(findings)
+    }
+
+    /// Masks the text nodes, CDATA sections and attribute values of an XML
+    /// document and re-serializes it. Masks are escaped as they are written,
+    /// so they cannot break entities or attribute quoting; markup, comments
+    /// and values without findings are copied byte for byte.
+    pub fn mask_xml(&self, xml: &str) -> Result<DocumentMaskingResult, DataCloakError> {
+        let mut totals = FieldTotals::new();
+        let masked = rewrite_xml(xml, &mut |path, text| {
+            Ok(totals.add(path, self.mask_text(text)?))
+        })?;
+        Ok(totals.into_document(masked))
+    }
+}
+
+fn rewrite_xml(xml: &str, visit: &mut ValueVisitor<'_>) -> Result<String, DataCloakError> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::with_capacity(xml.len()));
+    let mut path = String::new();
+    let mut parents = Vec::new();
+
+    loop {
+        let event = match reader.read_event().map_err(xml_error)? {
+            Event::Start(start) => {
+                parents.push(path.len());
+                path.push('/');
+                path.push_str(&utf8(start.name().as_ref())?);
+                Event::Start(rewrite_attributes(start, &path, visit)?)
+            }
+            Event::Empty(start) => {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&utf8(start.name().as_ref())?);
+                let start = rewrite_attributes(start, &path, visit)?;
+                path.truncate(len);
+                Event::Empty(start)
+            }
+            Event::End(end) => {
+                if let Some(len) = parents.pop() {
+                    path.truncate(len);
+                }
+                Event::End(end)
+            }
+            Event::Text(text) => {
+                let content = text.unescape().map_err(xml_error)?.into_owned();
+                if content.trim().is_empty() {
+                    Event::Text(text)
+                } else {
+                    let masked = visit(&path, &content)?;
+                    if masked == content {
+                        Event::Text(text)
+                    } else {
+                        Event::Text(BytesText::new(&masked).into_owned())
+                    }
+                }
+            }
+            Event::CData(data) => {
+                let content = utf8(&data)?;
+                let masked = visit(&path, &content)?;
+                if masked == content {
+                    Event::CData(data)
+                } else {
+                    Event::CData(BytesCData::new(masked))
+                }
+            }
+            Event::Eof => break,
+            other => other,
+        };
+        writer
+            .write_event(event)
+            .map_err(|e| DataCloakError::Io(e.to_string()))?;
+    }
+
+    String::from_utf8(writer.into_inner())
+        .map_err(|e| DataCloakError::InvalidArgument(format!("XML: {}", e)))
+}
+
+/// `start` with each attribute value passed through `visit`, or unchanged if
+/// no value changed so attribute order and quoting survive.
+fn rewrite_attributes<'a>(
+    start: BytesStart<'a>,
+    path: &str,
+    visit: &mut ValueVisitor<'_>,
+) -> Result<BytesStart<'a>, DataCloakError> {
+    let mut values = Vec::new();
+    let mut changed = false;
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| xml_error(e.into()))?;
+        let key = utf8(attribute.key.as_ref())?;
+        let value = attribute.unescape_value().map_err(xml_error)?;
+        let masked = visit(&format!("{}/@{}", path, key), &value)?;
+        changed |= masked != value;
+        values.push((key, masked));
+    }
+    if !changed {
+        return Ok(start);
+    }
+
+    let mut rewritten = start.to_owned();
+    rewritten.clear_attributes();
+    for (key, value) in &values {
+        rewritten.push_attribute(Attribute::from((key.as_str(), value.as_str())));
+    }
+    Ok(rewritten)
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, DataCloakError> {
+    std::str::from_utf8(bytes)
+        .map(str::to_string)
+        .map_err(|e| DataCloakError::InvalidArgument(format!("XML: {}", e)))
+}
+
+fn xml_error(e: quick_xml::Error) -> DataCloakError {
+    DataCloakError::InvalidArgument(format!("XML: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataCloakConfig, DataCloakEngine, DataCloakError};
+
+    #[test]
+    fn test_mask_xml_escapes_masks() {
+        let config = DataCloakConfig::builder()
+            .mask_template("email", "<{first1}> & co")
+            .build()
+            .unwrap();
+        let engine = DataCloakEngine::new(config).unwrap();
+        let xml = "<?xml version=\"1.0\"?>\n<order id=\"7\" contact=\"jo@example.com\">\
+                   <!-- keep -->\n  <note>Tom &amp; Jo: jo@example.com</note>\
+                   <raw><![CDATA[ssn 123-45-6789]]></raw></order>";
+
+        let result = engine.mask_xml(xml).unwrap();
+        assert_eq!(
+            result.masked,
+            "<?xml version=\"1.0\"?>\n<order id=\"7\" contact=\"&lt;j&gt; &amp; co\">\
+             <!-- keep -->\n  <note>Tom &amp; Jo: &lt;j&gt; &amp; co</note>\
+             <raw><![CDATA[ssn ***-**-6789]]></raw></order>"
+        );
+        let fields: Vec<&str> = result
+            .detected_pii
+            .iter()
+            .map(|pii| pii.field_name.as_str())
+            .collect();
+        assert_eq!(fields, ["/order/@contact", "/order/note", "/order/raw"]);
+    }
+
+    #[test]
+    fn test_detect_pii_xml_rejects_malformed_documents() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        let findings = engine
+            .detect_pii_xml("<a><b x=\"a@example.com\"/>y</a>")
+            .unwrap();
+        assert_eq!(findings[0].field_name, "/a/b/@x");
+        assert!(matches!(
+            engine.detect_pii_xml("<a><b></a>"),
+            Err(DataCloakError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_mask_stream_pipes_lines_through() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_datacloak"))
+        .args(["mask", "--stream"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"login jane@example.com\nno pii here\r\nssn 123-45-6789\n")
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "login j***@example.com\nno pii here\r\nssn ***-**-6789\n"
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_mask_stream_warns_on_invalid_utf8() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_datacloak"))
+        .args(["mask", "--stream"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"Jos\xe9 jane@example.com\n")
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Jos\u{e9} j***@example.com\n"
+    );
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Line 1 is not valid UTF-8"));
+}
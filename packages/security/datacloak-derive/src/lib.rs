@@ -0,0 +1,241 @@
+//! `#[derive(DataCloak)]` for datacloak-core, re-exported there behind the
+//! `derive` feature.
+//!
+//! The derive implements `datacloak_core::MaskRules` from field attributes,
+//! which `DataCloakEngine::masked(..).with_rules::<T>()` applies while the
+//! struct is serialized:
+//!
+//! - `#[datacloak(mask = "email")]` masks the whole value as that PII type
+//! - `#[datacloak(skip)]` writes the value unchanged
+//! - `#[datacloak(nested)]` adds the rules of the field's type, which must
+//!   implement `MaskRules` itself
+//!
+//! Fields without an attribute are scanned like any other string.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, LitStr, Token};
+
+#[proc_macro_derive(DataCloak, attributes(datacloak))]
+pub fn derive_data_cloak(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+enum Rule {
+    Mask(LitStr),
+    Skip,
+    Nested,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "DataCloak can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "DataCloak can only be derived for structs",
+            ))
+        }
+    };
+
+    let serde = SerdeNames::parse(&input.attrs)?;
+    // Rules are looked up by the names serde writes, which these change
+    if let Some(span) = serde.unsupported {
+        return Err(syn::Error::new(
+            span,
+            "DataCloak does not support `#[serde(rename_all)]` or `#[serde(flatten)]`",
+        ));
+    }
+    let struct_name = serde
+        .rename
+        .unwrap_or_else(|| input.ident.unraw().to_string());
+
+    let mut rules = Vec::new();
+    for field in fields {
+        // A flattened field turns the whole struct into a map
+        let serde = SerdeNames::parse(&field.attrs)?;
+        if let Some(span) = serde.unsupported {
+            return Err(syn::Error::new(
+                span,
+                "DataCloak does not support `#[serde(flatten)]`",
+            ));
+        }
+        let Some(rule) = field_rule(&field.attrs)? else {
+            continue;
+        };
+        let ident = field.ident.as_ref().expect("named fields have identifiers");
+        let field_name = serde.rename.unwrap_or_else(|| ident.unraw().to_string());
+        let ty = &field.ty;
+        rules.push(match rule {
+            Rule::Mask(pii_type) => quote! {
+                rules.add(#struct_name, #field_name, ::datacloak_core::FieldRule::Mask(#pii_type));
+            },
+            Rule::Skip => quote! {
+                rules.add(#struct_name, #field_name, ::datacloak_core::FieldRule::Skip);
+            },
+            Rule::Nested => quote! {
+                <#ty as ::datacloak_core::MaskRules>::mask_rules(rules);
+            },
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::datacloak_core::MaskRules for #ident #ty_generics #where_clause {
+            fn mask_rules(rules: &mut ::datacloak_core::FieldRules) {
+                if rules.visit(#struct_name) {
+                    #(#rules)*
+                }
+            }
+        }
+    })
+}
+
+fn field_rule(attrs: &[Attribute]) -> syn::Result<Option<Rule>> {
+    let mut rule = None;
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("datacloak"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let parsed = if meta.path.is_ident("mask") {
+                let pii_type: LitStr = meta.value()?.parse()?;
+                if pii_type.value().is_empty() {
+                    return Err(meta.error("the PII type must not be empty"));
+                }
+                Rule::Mask(pii_type)
+            } else if meta.path.is_ident("skip") {
+                Rule::Skip
+            } else if meta.path.is_ident("nested") {
+                Rule::Nested
+            } else {
+                return Err(meta.error("expected `mask = \"<pii type>\"`, `skip` or `nested`"));
+            };
+            if rule.replace(parsed).is_some() {
+                return Err(meta.error("a field takes one datacloak rule"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(rule)
+}
+
+/// What `#[serde(...)]` attributes do to the serialized name.
+#[derive(Default)]
+struct SerdeNames {
+    rename: Option<String>,
+    /// Span of a `rename_all` or `flatten`.
+    unsupported: Option<proc_macro2::Span>,
+}
+
+impl SerdeNames {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut names = SerdeNames::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if meta.input.peek(Token![=]) {
+                        names.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else {
+                        meta.parse_nested_meta(|inner| {
+                            if inner.path.is_ident("serialize") {
+                                names.rename = Some(inner.value()?.parse::<LitStr>()?.value());
+                                Ok(())
+                            } else {
+                                skip_meta(&inner)
+                            }
+                        })?;
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("rename_all") || meta.path.is_ident("flatten") {
+                    names.unsupported = Some(meta.path.span());
+                    skip_meta(&meta)
+                } else {
+                    skip_meta(&meta)
+                }
+            })?;
+        }
+        Ok(names)
+    }
+}
+
+/// Consumes a serde attribute this derive does not care about.
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream2>()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_expands_rules_under_serialized_names() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Serialize)]
+            #[serde(rename = "Customer")]
+            struct User {
+                id: u64,
+                #[serde(rename = "mail", skip_serializing_if = "String::is_empty")]
+                #[datacloak(mask = "email")]
+                email: String,
+                #[datacloak(skip)]
+                r#ref: String,
+                #[datacloak(nested)]
+                home: Option<Address>,
+            }
+        };
+
+        let expanded = expand(&input).unwrap().to_string();
+        assert!(expanded.contains("rules . visit (\"Customer\")"));
+        assert!(expanded.contains(
+            "rules . add (\"Customer\" , \"mail\" , :: datacloak_core :: FieldRule :: Mask (\"email\"))"
+        ));
+        assert!(expanded.contains("\"ref\" , :: datacloak_core :: FieldRule :: Skip"));
+        assert!(expanded.contains("< Option < Address > as :: datacloak_core :: MaskRules >"));
+        assert!(!expanded.contains("\"id\""));
+    }
+
+    #[test]
+    fn test_rejects_renamed_fields_it_cannot_follow() {
+        let input: DeriveInput = parse_quote! {
+            #[serde(rename_all = "camelCase")]
+            struct User {
+                #[datacloak(mask = "email")]
+                email_address: String,
+            }
+        };
+        assert!(expand(&input).is_err());
+
+        let input: DeriveInput = parse_quote! {
+            struct User {
+                #[datacloak(mask = "email", skip)]
+                email: String,
+            }
+        };
+        assert!(expand(&input).is_err());
+    }
+}
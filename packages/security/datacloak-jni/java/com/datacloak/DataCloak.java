@@ -0,0 +1,49 @@
+package com.datacloak;
+
+/**
+ * PII detection and masking backed by the native DataCloak engine.
+ *
+ * <p>Instances are thread-safe. Results are returned as JSON, in the same
+ * shape as the C FFI. Close the engine to release its native memory.
+ */
+public final class DataCloak implements AutoCloseable {
+    static {
+        System.loadLibrary("datacloak_jni");
+    }
+
+    private long handle;
+
+    /** Creates an engine with the default configuration. */
+    public DataCloak() {
+        this(null);
+    }
+
+    /** Creates an engine from a JSON-encoded {@code DataCloakConfig}. */
+    public DataCloak(String configJson) {
+        this.handle = nativeCreate(configJson);
+    }
+
+    public String detectPii(String text) {
+        return nativeDetectPii(handle, text);
+    }
+
+    public String maskText(String text) {
+        return nativeMaskText(handle, text);
+    }
+
+    @Override
+    public synchronized void close() {
+        if (handle != 0) {
+            nativeDestroy(handle);
+            handle = 0;
+        }
+    }
+
+    private static native long nativeCreate(String configJson);
+
+    private static native void nativeDestroy(long handle);
+
+    private static native String nativeDetectPii(long handle, String text);
+
+    private static native String nativeMaskText(long handle, String text);
+}
@@ -0,0 +1,16 @@
+package com.datacloak;
+
+/** Thrown for every failure reported by the native DataCloak engine. */
+public class DataCloakException extends RuntimeException {
+    private final int code;
+
+    public DataCloakException(int code, String message) {
+        super(message);
+        this.code = code;
+    }
+
+    /** Stable error code, matching {@code DataCloakError::code} in Rust. */
+    public int getCode() {
+        return code;
+    }
+}
@@ -0,0 +1,190 @@
+//! JNI bindings backing `com.datacloak.DataCloak` (see `java/`).
+//!
+//! Engines live on the Rust heap and are addressed from Java by a `long`
+//! handle. Every `DataCloakError` surfaces as a `DataCloakException` carrying
+//! the error's stable code; panics are caught and reported the same way.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use datacloak_core::{ConfigFormat, DataCloakConfig, DataCloakEngine, DataCloakError};
+use jni::objects::{JClass, JObject, JString, JValue};
+use jni::sys::{jlong, jstring};
+use jni::JNIEnv;
+use serde::Serialize;
+
+const EXCEPTION_CLASS: &str = "com/datacloak/DataCloakException";
+
+fn jni_error(error: jni::errors::Error) -> DataCloakError {
+    DataCloakError::Internal(format!("JNI call failed: {}", error))
+}
+
+fn throw(env: &mut JNIEnv, error: &DataCloakError) {
+    let thrown = env
+        .new_string(error.to_string())
+        .and_then(|message| {
+            env.new_object(
+                EXCEPTION_CLASS,
+                "(ILjava/lang/String;)V",
+                &[JValue::Int(error.code()), JValue::Object(&message)],
+            )
+        })
+        .and_then(|exception| env.throw(jni::objects::JThrowable::from(exception)));
+
+    if thrown.is_err() {
+        // The exception class itself is unavailable; fall back to a JDK type
+        let _ = env.throw_new("java/lang/IllegalStateException", error.to_string());
+    }
+}
+
+/// Runs a native method body, translating errors and panics into a
+/// `DataCloakException` and returning `default` to the JVM in that case.
+fn call<T>(
+    env: &mut JNIEnv,
+    default: T,
+    body: impl FnOnce(&mut JNIEnv) -> Result<T, DataCloakError>,
+) -> T {
+    guard(env, default, body, throw)
+}
+
+/// `call` with the throwing step passed in, so it can run without a JVM.
+fn guard<E, T>(
+    env: &mut E,
+    default: T,
+    body: impl FnOnce(&mut E) -> Result<T, DataCloakError>,
+    throw: impl FnOnce(&mut E, &DataCloakError),
+) -> T {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| body(env)))
+        .unwrap_or_else(|_| Err(DataCloakError::Internal("panic in native code".to_string())));
+
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            throw(env, &error);
+            default
+        }
+    }
+}
+
+fn read_string(env: &mut JNIEnv, value: &JString) -> Result<String, DataCloakError> {
+    if value.is_null() {
+        return Err(DataCloakError::InvalidArgument(
+            "string is null".to_string(),
+        ));
+    }
+    Ok(env.get_string(value).map_err(jni_error)?.into())
+}
+
+fn engine_ref<'a>(handle: jlong) -> Result<&'a DataCloakEngine, DataCloakError> {
+    unsafe { (handle as *const DataCloakEngine).as_ref() }
+        .ok_or_else(|| DataCloakError::InvalidArgument("engine is closed".to_string()))
+}
+
+fn to_json_string(env: &mut JNIEnv, value: &impl Serialize) -> Result<jstring, DataCloakError> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| DataCloakError::Internal(format!("Failed to serialize result: {}", e)))?;
+    Ok(env.new_string(json).map_err(jni_error)?.into_raw())
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_datacloak_DataCloak_nativeCreate(
+    mut env: JNIEnv,
+    _class: JClass,
+    config_json: JString,
+) -> jlong {
+    call(&mut env, 0, |env| {
+        let config = if config_json.is_null() {
+            DataCloakConfig::default()
+        } else {
+            let json = read_string(env, &config_json)?;
+            DataCloakConfig::parse(&json, ConfigFormat::Json)?
+        };
+        let engine = DataCloakEngine::new(config)?;
+        Ok(Box::into_raw(Box::new(engine)) as jlong)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_datacloak_DataCloak_nativeDestroy(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    call(&mut env, (), |_| {
+        if handle != 0 {
+            drop(unsafe { Box::from_raw(handle as *mut DataCloakEngine) });
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_datacloak_DataCloak_nativeDetectPii(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    text: JString,
+) -> jstring {
+    call(&mut env, JObject::null().into_raw(), |env| {
+        let engine = engine_ref(handle)?;
+        let text = read_string(env, &text)?;
+        to_json_string(env, &engine.detect_pii(&text)?)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_datacloak_DataCloak_nativeMaskText(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    text: JString,
+) -> jstring {
+    call(&mut env, JObject::null().into_raw(), |env| {
+        let engine = engine_ref(handle)?;
+        let text = read_string(env, &text)?;
+        to_json_string(env, &engine.mask_text(&text)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(thrown: &mut Vec<(i32, String)>, error: &DataCloakError) {
+        thrown.push((error.code(), error.to_string()));
+    }
+
+    #[test]
+    fn test_call_returns_the_body_result() {
+        let mut thrown = Vec::new();
+        assert_eq!(guard(&mut thrown, 0, |_| Ok(7), record), 7);
+        assert!(thrown.is_empty());
+    }
+
+    #[test]
+    fn test_call_maps_errors_to_the_default() {
+        let mut thrown = Vec::new();
+        let error = DataCloakError::InvalidArgument("engine is closed".to_string());
+        let code = error.code();
+        assert_eq!(guard(&mut thrown, 0, |_| Err(error), record), 0);
+        assert_eq!(thrown.len(), 1);
+        assert_eq!(thrown[0].0, code);
+        assert!(thrown[0].1.contains("engine is closed"));
+    }
+
+    #[test]
+    fn test_call_maps_panics_to_the_default() {
+        let mut thrown = Vec::new();
+        let value: jlong = guard(&mut thrown, -1, |_| panic!("boom"), record);
+        assert_eq!(value, -1);
+        assert_eq!(thrown.len(), 1);
+        assert!(thrown[0].1.contains("panic in native code"));
+    }
+
+    #[test]
+    fn test_engine_ref_rejects_null_handles() {
+        assert!(matches!(
+            engine_ref(0),
+            Err(DataCloakError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+//! Node.js bindings for the DataCloak engine.
+//!
+//! `detectPii` and `maskText` return promises and run on the libuv thread
+//! pool, so long scans never block the Electron main process. The `*Sync`
+//! variants are there for short strings where a round trip isn't worth it.
+
+#[macro_use]
+extern crate napi_derive;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use datacloak_core::{
+    utf16_offsets, ConfigFormat, DataCloakConfig, DataCloakEngine, MaskingResult,
+    PIIDetectionResult,
+};
+use napi::bindgen_prelude::*;
+
+fn to_napi_err(error: datacloak_core::DataCloakError) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+/// A finding with `start`/`end` in UTF-16 code units, the way JavaScript
+/// strings are indexed.
+#[napi(object)]
+pub struct Detection {
+    pub pii_type: String,
+    pub sample: String,
+    pub masked: String,
+    pub start: u32,
+    pub end: u32,
+    pub confidence: f64,
+}
+
+/// `results` found in `text`, with their byte offsets converted to UTF-16.
+fn detections(text: &str, results: Vec<PIIDetectionResult>) -> Result<Vec<Detection>> {
+    let bytes: Vec<usize> = results
+        .iter()
+        .flat_map(|pii| [pii.start, pii.end])
+        .collect();
+    let units = utf16_offsets(text, &bytes);
+    results
+        .into_iter()
+        .zip(units.chunks_exact(2))
+        .map(|(pii, span)| {
+            Ok(Detection {
+                pii_type: pii.pii_type,
+                sample: pii.sample,
+                masked: pii.masked,
+                start: to_u32(span[0])?,
+                end: to_u32(span[1])?,
+                confidence: pii.confidence,
+            })
+        })
+        .collect()
+}
+
+fn to_u32(offset: usize) -> Result<u32> {
+    u32::try_from(offset)
+        .map_err(|_| Error::from_reason(format!("Offset {} does not fit in a u32", offset)))
+}
+
+#[napi(object)]
+pub struct MaskResult {
+    pub masked_text: String,
+    pub detections: Vec<Detection>,
+    pub token_map: HashMap<String, String>,
+}
+
+/// `result` of masking `text`, with detection offsets into `text`.
+fn mask_result(text: &str, result: MaskingResult) -> Result<MaskResult> {
+    Ok(MaskResult {
+        masked_text: result.masked_text,
+        detections: detections(text, result.detected_pii)?,
+        token_map: result.token_map,
+    })
+}
+
+pub struct DetectTask {
+    engine: Arc<DataCloakEngine>,
+    text: String,
+}
+
+impl Task for DetectTask {
+    type Output = Vec<PIIDetectionResult>;
+    type JsValue = Vec<Detection>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        self.engine.detect_pii(&self.text).map_err(to_napi_err)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        detections(&self.text, output)
+    }
+}
+
+pub struct MaskTask {
+    engine: Arc<DataCloakEngine>,
+    text: String,
+}
+
+impl Task for MaskTask {
+    type Output = MaskingResult;
+    type JsValue = MaskResult;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        self.engine.mask_text(&self.text).map_err(to_napi_err)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        mask_result(&self.text, output)
+    }
+}
+
+#[napi]
+pub struct DataCloak {
+    engine: Arc<DataCloakEngine>,
+}
+
+#[napi]
+impl DataCloak {
+    /// Creates an engine from a JSON-encoded `DataCloakConfig`, or from the
+    /// defaults when no config is given.
+    #[napi(constructor)]
+    pub fn new(config_json: Option<String>) -> Result<Self> {
+        let config = match config_json {
+            Some(json) => DataCloakConfig::parse(&json, ConfigFormat::Json).map_err(to_napi_err)?,
+            None => DataCloakConfig::default(),
+        };
+        let engine = DataCloakEngine::new(config).map_err(to_napi_err)?;
+        Ok(DataCloak {
+            engine: Arc::new(engine),
+        })
+    }
+
+    #[napi(ts_return_type = "Promise<Array<Detection>>")]
+    pub fn detect_pii(&self, text: String) -> AsyncTask<DetectTask> {
+        AsyncTask::new(DetectTask {
+            engine: Arc::clone(&self.engine),
+            text,
+        })
+    }
+
+    #[napi(ts_return_type = "Promise<MaskResult>")]
+    pub fn mask_text(&self, text: String) -> AsyncTask<MaskTask> {
+        AsyncTask::new(MaskTask {
+            engine: Arc::clone(&self.engine),
+            text,
+        })
+    }
+
+    #[napi]
+    pub fn detect_pii_sync(&self, text: String) -> Result<Vec<Detection>> {
+        let results = self.engine.detect_pii(&text).map_err(to_napi_err)?;
+        detections(&text, results)
+    }
+
+    #[napi]
+    pub fn mask_text_sync(&self, text: String) -> Result<MaskResult> {
+        let result = self.engine.mask_text(&text).map_err(to_napi_err)?;
+        mask_result(&text, result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offsets_are_utf16_code_units() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        // "é" is 2 bytes / 1 unit, "😀" is 4 bytes / 2 units
+        let text = "é😀 mail jane@example.com";
+
+        let found = detections(text, engine.detect_pii(text).unwrap()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].start, found[0].end), (9, 25));
+
+        let masked = mask_result(text, engine.mask_text(text).unwrap()).unwrap();
+        assert_eq!(
+            (masked.detections[0].start, masked.detections[0].end),
+            (9, 25)
+        );
+    }
+
+    #[test]
+    fn test_offsets_beyond_u32_are_rejected() {
+        assert_eq!(to_u32(7).unwrap(), 7);
+        assert!(to_u32(u32::MAX as usize + 1).is_err());
+    }
+}
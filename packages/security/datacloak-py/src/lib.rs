@@ -0,0 +1,157 @@
+//! Python bindings for the DataCloak engine.
+//!
+//! ```python
+//! import datacloak
+//!
+//! engine = datacloak.Engine('{"enabled_types": ["email", "ssn"]}')
+//! df["notes"] = engine.mask_many(df["notes"].tolist())
+//! ```
+
+use std::collections::HashMap;
+
+use datacloak_core::{
+    char_offsets, ConfigFormat, DataCloakConfig, DataCloakEngine, PIIDetectionResult,
+};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+create_exception!(datacloak, DataCloakError, PyException);
+
+fn to_py_err(error: datacloak_core::DataCloakError) -> PyErr {
+    DataCloakError::new_err(error.to_string())
+}
+
+/// A single PII detection. `start`/`end` are code point offsets, so they
+/// slice the Python string the text came from.
+#[pyclass(frozen, get_all)]
+#[derive(Clone)]
+pub struct Detection {
+    pii_type: String,
+    sample: String,
+    masked: String,
+    start: usize,
+    end: usize,
+    confidence: f64,
+}
+
+/// `results` found in `text`, with their byte offsets converted to code
+/// points.
+fn detections(text: &str, results: Vec<PIIDetectionResult>) -> Vec<Detection> {
+    let bytes: Vec<usize> = results
+        .iter()
+        .flat_map(|pii| [pii.start, pii.end])
+        .collect();
+    let chars = char_offsets(text, &bytes);
+    results
+        .into_iter()
+        .zip(chars.chunks_exact(2))
+        .map(|(pii, span)| Detection {
+            pii_type: pii.pii_type,
+            sample: pii.sample,
+            masked: pii.masked,
+            start: span[0],
+            end: span[1],
+            confidence: pii.confidence,
+        })
+        .collect()
+}
+
+#[pymethods]
+impl Detection {
+    fn __repr__(&self) -> String {
+        format!(
+            "Detection(pii_type={:?}, start={}, end={}, masked={:?})",
+            self.pii_type, self.start, self.end, self.masked
+        )
+    }
+}
+
+/// The outcome of masking one text.
+#[pyclass(frozen, get_all)]
+pub struct MaskResult {
+    masked_text: String,
+    detections: Vec<Detection>,
+    token_map: HashMap<String, String>,
+}
+
+#[pyclass]
+pub struct Engine {
+    inner: DataCloakEngine,
+}
+
+#[pymethods]
+impl Engine {
+    /// Creates an engine from a JSON-encoded config, or the defaults.
+    #[new]
+    #[pyo3(signature = (config_json=None))]
+    fn new(config_json: Option<&str>) -> PyResult<Self> {
+        let config = match config_json {
+            Some(json) => DataCloakConfig::parse(json, ConfigFormat::Json).map_err(to_py_err)?,
+            None => DataCloakConfig::default(),
+        };
+        let inner = DataCloakEngine::new(config).map_err(to_py_err)?;
+        Ok(Engine { inner })
+    }
+
+    fn detect(&self, py: Python<'_>, text: &str) -> PyResult<Vec<Detection>> {
+        let results = py
+            .allow_threads(|| self.inner.detect_pii(text))
+            .map_err(to_py_err)?;
+        Ok(detections(text, results))
+    }
+
+    fn mask(&self, py: Python<'_>, text: &str) -> PyResult<MaskResult> {
+        let result = py
+            .allow_threads(|| self.inner.mask_text(text))
+            .map_err(to_py_err)?;
+        Ok(MaskResult {
+            masked_text: result.masked_text,
+            detections: detections(text, result.detected_pii),
+            token_map: result.token_map,
+        })
+    }
+
+    /// Masks every text and returns only the masked strings, for scrubbing a
+    /// whole column in one call. The GIL is released for the duration.
+    fn mask_many(&self, py: Python<'_>, texts: Vec<String>) -> PyResult<Vec<String>> {
+        py.allow_threads(|| {
+            texts
+                .iter()
+                .map(|text| self.inner.mask_text(text).map(|r| r.masked_text))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(to_py_err)
+    }
+}
+
+#[pymodule]
+fn datacloak(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Engine>()?;
+    m.add_class::<Detection>()?;
+    m.add_class::<MaskResult>()?;
+    m.add("DataCloakError", m.py().get_type_bound::<DataCloakError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offsets_are_code_points() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        // "é" is 2 bytes, "😀" is 4 bytes; one code point each
+        let text = "é😀 mail jane@example.com";
+
+        let found = detections(text, engine.detect_pii(text).unwrap());
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].start, found[0].end), (8, 24));
+        let sample: String = text
+            .chars()
+            .skip(found[0].start)
+            .take(found[0].end - found[0].start)
+            .collect();
+        assert_eq!(sample, found[0].sample);
+    }
+}
@@ -0,0 +1,78 @@
+//! Browser bindings for the DataCloak engine, so the workbench frontend can
+//! mask preview data before it ever leaves the page.
+//!
+//! Build with `wasm-pack build --target web datacloak-wasm`.
+//!
+//! Detection `start`/`end` are UTF-16 code unit offsets, so they index the
+//! JavaScript string the text came from.
+
+use datacloak_core::{
+    utf16_offsets, ConfigFormat, DataCloakConfig, DataCloakEngine, PIIDetectionResult,
+};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct DataCloak {
+    engine: DataCloakEngine,
+}
+
+#[wasm_bindgen]
+impl DataCloak {
+    /// Creates an engine from a JSON-encoded `DataCloakConfig`, or from the
+    /// defaults when no config is given.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: Option<String>) -> Result<DataCloak, JsError> {
+        let config = match config_json {
+            Some(json) => DataCloakConfig::parse(&json, ConfigFormat::Json)?,
+            None => DataCloakConfig::default(),
+        };
+        Ok(DataCloak {
+            engine: DataCloakEngine::new(config)?,
+        })
+    }
+
+    /// Returns the detections in `text` as an array of plain objects.
+    #[wasm_bindgen(js_name = detectPii)]
+    pub fn detect_pii(&self, text: &str) -> Result<JsValue, JsError> {
+        let mut results = self.engine.detect_pii(text)?;
+        to_utf16(text, &mut results);
+        serde_wasm_bindgen::to_value(&results).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Returns the `MaskingResult` for `text` as a plain object.
+    #[wasm_bindgen(js_name = maskText)]
+    pub fn mask_text(&self, text: &str) -> Result<JsValue, JsError> {
+        let mut result = self.engine.mask_text(text)?;
+        to_utf16(text, &mut result.detected_pii);
+        serde_wasm_bindgen::to_value(&result).map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+/// Converts the byte offsets of `results`, found in `text`, to UTF-16.
+fn to_utf16(text: &str, results: &mut [PIIDetectionResult]) {
+    let bytes: Vec<usize> = results
+        .iter()
+        .flat_map(|pii| [pii.start, pii.end])
+        .collect();
+    let units = utf16_offsets(text, &bytes);
+    for (pii, span) in results.iter_mut().zip(units.chunks_exact(2)) {
+        pii.start = span[0];
+        pii.end = span[1];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offsets_are_utf16_code_units() {
+        let engine = DataCloakEngine::new(DataCloakConfig::default()).unwrap();
+        // "é" is 2 bytes / 1 unit, "😀" is 4 bytes / 2 units
+        let text = "é😀 mail jane@example.com";
+        let mut results = engine.detect_pii(text).unwrap();
+        to_utf16(text, &mut results);
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].start, results[0].end), (9, 25));
+    }
+}